@@ -17,6 +17,7 @@ async fn main() -> Result<()> {
         UrlType::EpisodeOrMovie(_) => println!("url points to a crunchyroll episode or movie"),
         UrlType::MusicVideo(_) => println!("url points to a crunchyroll music video"),
         UrlType::Concert(_) => println!("url points to a crunchyroll concert"),
+        UrlType::Artist(_) => println!("url points to a crunchyroll artist"),
     }
 
     Ok(())
@@ -1,5 +1,7 @@
 //! Account specific actions.
 
+use crate::error::Error;
+use crate::macros::enum_values;
 use crate::{options, Crunchyroll, EmptyJsonProxy, Executor, Locale, Request, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -44,6 +46,8 @@ pub struct Account {
     #[serde(rename = "preferred_content_subtitle_language")]
     pub preferred_subtitle_language: Locale,
 
+    /// Email notification settings. All of them are opt-outs, so `true` means that the
+    /// corresponding emails are **not** sent. Update them via [`Account::update_preferences`].
     pub opt_out_free_trials: bool,
     pub opt_out_pm_updates: bool,
     #[serde(rename = "opt_out_store_deals")]
@@ -106,7 +110,14 @@ impl Account {
                 "preferred_communication_language".into(),
                 email_language.to_string().into(),
             );
-            updated_self.preferred_subtitle_language = email_language;
+            updated_self.email_language = email_language;
+        }
+        if let Some(audio_language) = preferences.audio_language {
+            profile_update.insert(
+                "preferred_content_audio_language".into(),
+                audio_language.to_string().into(),
+            );
+            updated_self.preferred_audio_language = audio_language;
         }
         if let Some(subtitle_language) = preferences.subtitle_language {
             profile_update.insert(
@@ -169,7 +180,23 @@ impl Account {
         Ok(())
     }
 
-    /// Changes the current account password.
+    /// Updates the language in which audio should be played. Shortcut for
+    /// [`Account::update_preferences`] with [`UpdatePreferences::audio_language`].
+    pub async fn set_audio_language(&mut self, audio_language: Locale) -> Result<()> {
+        self.update_preferences(UpdatePreferences::default().audio_language(audio_language))
+            .await
+    }
+
+    /// Updates the language in which subtitles should be shown if available. Shortcut for
+    /// [`Account::update_preferences`] with [`UpdatePreferences::subtitle_language`].
+    pub async fn set_subtitle_language(&mut self, subtitle_language: Locale) -> Result<()> {
+        self.update_preferences(UpdatePreferences::default().subtitle_language(subtitle_language))
+            .await
+    }
+
+    /// Changes the current account password. Returns [`Error::Authentication`] if
+    /// `current_password` is wrong or Crunchyroll requires you to log in again before changing
+    /// credentials.
     pub async fn change_password(
         &self,
         current_password: String,
@@ -184,11 +211,13 @@ impl Account {
                 "new_password": new_password,
             }))
             .request::<EmptyJsonProxy>()
-            .await?;
+            .await
+            .map_err(credentials_error)?;
         Ok(())
     }
 
-    /// Changes the current account email.
+    /// Changes the current account email. Returns [`Error::Authentication`] if `current_password`
+    /// is wrong or Crunchyroll requires you to log in again before changing credentials.
     pub async fn change_email(&self, current_password: String, new_email: String) -> Result<()> {
         let endpoint = "https://www.crunchyroll.com/accounts/v1/me/credentials";
         self.executor
@@ -198,7 +227,8 @@ impl Account {
                 "new_email": new_email,
             }))
             .request_raw(true)
-            .await?;
+            .await
+            .map_err(credentials_error)?;
         Ok(())
     }
 
@@ -215,7 +245,97 @@ impl Account {
     }
 }
 
+enum_values! {
+    /// Subscription tier of an account.
+    pub enum SubscriptionTier {
+        Free = "free"
+        Fan = "fan"
+        MegaFan = "mega_fan"
+        UltimateFan = "ultimate_fan"
+    }
+}
+
+/// Subscription information of the current account. Get it via [`Crunchyroll::subscription`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Subscription {
+    /// The tier, derived from [`Subscription::benefits`].
+    pub tier: SubscriptionTier,
+    /// All raw benefits of the account, e.g. `cr_premium` or `concurrent_streams.4`.
+    pub benefits: Vec<String>,
+    /// How many streams can be watched at the same time.
+    pub concurrent_streams: u32,
+    /// If episodes / movies can be downloaded for offline viewing in the official apps.
+    pub offline_viewing: bool,
+    /// When the current subscription period ends. Always [`None`] for
+    /// [`SubscriptionTier::Free`].
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    fn from_benefits(benefits: Vec<String>) -> Self {
+        let premium = benefits.iter().any(|b| b == "cr_premium");
+        let concurrent_streams = benefits
+            .iter()
+            .filter_map(|b| b.strip_prefix("concurrent_streams."))
+            .filter_map(|s| s.parse::<u32>().ok())
+            .max()
+            .unwrap_or(1);
+        let offline_viewing = benefits.iter().any(|b| b == "offline_viewing");
+
+        let tier = if !premium {
+            SubscriptionTier::Free
+        } else if concurrent_streams >= 6 {
+            SubscriptionTier::UltimateFan
+        } else if concurrent_streams >= 4 {
+            SubscriptionTier::MegaFan
+        } else {
+            SubscriptionTier::Fan
+        };
+
+        Self {
+            tier,
+            benefits,
+            concurrent_streams,
+            offline_viewing,
+            expires: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Request)]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
+struct SubscriptionDetails {
+    #[serde(alias = "nextRenewalDate")]
+    next_renewal_date: Option<DateTime<Utc>>,
+}
+
 impl Crunchyroll {
+    /// Return the subscription of the current account. The tier and benefits are read from the
+    /// current session, the expiry date is requested if the account has premium.
+    pub async fn subscription(&self) -> Result<Subscription> {
+        let benefits = self
+            .executor
+            .jwt_claim::<Vec<String>>("benefits")
+            .await?
+            .unwrap_or_default();
+        let mut subscription = Subscription::from_benefits(benefits);
+
+        if subscription.tier != SubscriptionTier::Free {
+            let endpoint = format!(
+                "https://www.crunchyroll.com/subs/v3/subscriptions/{}",
+                self.executor.details.account_id.clone()?
+            );
+            subscription.expires = self
+                .executor
+                .get(endpoint)
+                .request::<SubscriptionDetails>()
+                .await?
+                .next_renewal_date;
+        }
+
+        Ok(subscription)
+    }
+
     /// Return information about the current account. [`Account`] can be used to modify account
     /// settings like the email or web interface language.
     pub async fn account(&self) -> Result<Account> {
@@ -251,6 +371,73 @@ impl Crunchyroll {
 
         Ok(account)
     }
+
+    /// Request an email to reset the password of the account with the given email. Can be called
+    /// on every session, including anonymous ones. Crunchyroll doesn't tell if an account with the
+    /// email exists, so this also succeeds if none exists.
+    pub async fn request_password_reset<S: AsRef<str>>(&self, email: S) -> Result<()> {
+        let endpoint = "https://www.crunchyroll.com/accounts/v1/reset_password";
+        self.executor
+            .post(endpoint)
+            .json(&json!({ "email": email.as_ref() }))
+            .request::<EmptyJsonProxy>()
+            .await?;
+        Ok(())
+    }
+
+    /// Resend the email to verify the email address of the current account. No email is sent if
+    /// the address is already verified.
+    pub async fn resend_verification_email(&self) -> Result<EmailVerification> {
+        #[derive(Default, Deserialize, Request)]
+        #[serde(default)]
+        struct Me {
+            email_verified: bool,
+        }
+
+        let me_endpoint = "https://www.crunchyroll.com/accounts/v1/me";
+        if self
+            .executor
+            .get(me_endpoint)
+            .request::<Me>()
+            .await?
+            .email_verified
+        {
+            return Ok(EmailVerification::AlreadyVerified);
+        }
+
+        let endpoint = "https://www.crunchyroll.com/accounts/v1/me/verify_email";
+        self.executor
+            .post(endpoint)
+            .request::<EmptyJsonProxy>()
+            .await?;
+        Ok(EmailVerification::Sent)
+    }
+}
+
+/// Result of [`Crunchyroll::resend_verification_email`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum EmailVerification {
+    /// A new verification email was sent.
+    Sent,
+    /// The email address is already verified, no email was sent.
+    AlreadyVerified,
+}
+
+/// Changing credentials requires the current password and, depending on the age of the session,
+/// a fresh login. Crunchyroll signals both with an unauthorized / forbidden status.
+fn credentials_error(error: Error) -> Error {
+    match &error {
+        Error::Request {
+            message,
+            status: Some(status),
+            ..
+        } if status.as_u16() == 401 || status.as_u16() == 403 => Error::Authentication {
+            message: format!(
+                "current password is wrong or a new login is required to change credentials: {message}"
+            ),
+        },
+        _ => error,
+    }
 }
 
 fn mature_content_flag_manga<'de, D: serde::Deserializer<'de>>(
@@ -1,13 +1,18 @@
 //! Commonly used types.
 
+use crate::error::Error;
 use crate::{Executor, Result};
+use futures_util::future::{select, Either};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
-use std::pin::Pin;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::Notify;
 
 pub(crate) use crunchyroll_rs_internal::Request;
 
@@ -112,7 +117,75 @@ impl<T: Default + DeserializeOwned + Request> From<BulkResult<T>> for Pagination
     }
 }
 
-/// Pagination for results which can be continuously be fetched.
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Handle to cancel requests from outside, e.g. from another task when the user aborts a download
+/// or when the consumer of an infinite scrolling view went away. Get the handle of a [`Pagination`]
+/// via [`Pagination::cancellation`], or create one and pass it to
+/// [`crate::media::EpisodeDownloader::cancellation`] or
+/// [`crate::media::StreamSegment::data_cancellable`]. Cancelling drops the requests which are
+/// currently in flight; a [`Pagination`] returns no further items and a download returns
+/// [`Error::Cancelled`].
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation {
+    state: Arc<CancellationState>,
+}
+
+impl Cancellation {
+    /// Create a new, not cancelled handle.
+    pub fn new() -> Cancellation {
+        Self::default()
+    }
+
+    /// Cancel everything which uses this handle.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.notify.notify_waiters()
+    }
+
+    /// Check if [`Cancellation::cancel`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Completes when [`Cancellation::cancel`] is called.
+    pub async fn cancelled(&self) {
+        let mut notified = pin!(self.state.notify.notified());
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await
+    }
+
+    /// Run `future` until it completes or this handle gets cancelled.
+    pub(crate) async fn run<T, F: Future<Output = Result<T>>>(
+        &self,
+        future: F,
+        url: &str,
+    ) -> Result<T> {
+        let cancelled_error = || Error::Cancelled {
+            message: format!("request to {url} was cancelled"),
+        };
+        if self.is_cancelled() {
+            return Err(cancelled_error());
+        }
+
+        match select(pin!(future), pin!(self.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(cancelled_error()),
+        }
+    }
+}
+
+/// Pagination for results which can be continuously be fetched. It implements [`Stream`], so all
+/// combinators of [`StreamExt`] and [`TryStreamExt`] can be used on it. The
+/// upper bound of [`Stream::size_hint`] is the amount of remaining items once the first page was
+/// fetched, if Crunchyroll reports the total amount of items (see [`Pagination::total`]).
 #[allow(clippy::type_complexity)]
 pub struct Pagination<T: Default + DeserializeOwned + Request> {
     data: Vec<T>,
@@ -130,14 +203,27 @@ pub struct Pagination<T: Default + DeserializeOwned + Request> {
 
     count: u32,
     next_type: Option<PaginationNextType>,
+
+    page_timeout: Option<Duration>,
+    cancellation: Cancellation,
+    cancelled: Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
 }
 
-impl<T: Default + DeserializeOwned + Request> Stream for Pagination<T> {
+impl<T: Default + DeserializeOwned + Request + 'static> Stream for Pagination<T> {
     type Item = Result<T>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
+        // the cancelled future only completes after the flag is set, so it's never polled again
+        // once it has completed
+        if this.cancellation.is_cancelled() || this.cancelled.as_mut().poll(cx).is_ready() {
+            // dropping the pending request also drops the executor reference it holds
+            this.next_state = None;
+            this.data.clear();
+            return Poll::Ready(None);
+        }
+
         if !this.data.is_empty() || this.has_next_page() {
             if !this.data.is_empty() {
                 this.count += 1;
@@ -149,7 +235,30 @@ impl<T: Default + DeserializeOwned + Request> Stream for Pagination<T> {
                 let options = &mut this.paginator_options;
                 options.start = this.count;
                 options.page += 1;
-                this.next_state = Some(f(options.clone()));
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    page = options.page,
+                    start = options.start,
+                    "requesting pagination page"
+                );
+                let next = f(options.clone());
+                this.next_state = Some(if let Some(timeout) = this.page_timeout {
+                    Box::pin(async move {
+                        tokio::time::timeout(timeout, next)
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(Error::Timeout {
+                                    message: format!(
+                                        "Pagination page request timed out after {}ms",
+                                        timeout.as_millis()
+                                    ),
+                                    timeout,
+                                })
+                            })
+                    })
+                } else {
+                    next
+                });
             }
 
             let fut = this.next_state.as_mut().unwrap();
@@ -163,7 +272,18 @@ impl<T: Default + DeserializeOwned + Request> Stream for Pagination<T> {
 
                             Pin::new(this).poll_next(cx)
                         }
-                        Err(e) => Poll::Ready(Some(Err(e))),
+                        Err(e) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(
+                                page = this.paginator_options.page,
+                                error = %e,
+                                "pagination page request failed"
+                            );
+                            // make sure that the same page is requested again when polled the next
+                            // time
+                            this.paginator_options.page -= 1;
+                            Poll::Ready(Some(Err(e)))
+                        }
                     }
                 }
                 Poll::Pending => Poll::Pending,
@@ -172,11 +292,27 @@ impl<T: Default + DeserializeOwned + Request> Stream for Pagination<T> {
             Poll::Ready(None)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.cancellation.is_cancelled() {
+            return (0, Some(0));
+        }
+
+        let buffered = self.data.len();
+        match self.next_type {
+            Some(PaginationNextType::Total(total)) => {
+                let remaining = total.saturating_sub(self.count) as usize;
+                (buffered, Some(remaining.max(buffered)))
+            }
+            Some(PaginationNextType::NextPage(false)) => (buffered, Some(buffered)),
+            _ => (buffered, None),
+        }
+    }
 }
 
 impl<T: Default + DeserializeOwned + Request> Unpin for Pagination<T> {}
 
-impl<T: Default + DeserializeOwned + Request> Pagination<T> {
+impl<T: Default + DeserializeOwned + Request + 'static> Pagination<T> {
     pub(crate) fn new<F>(
         pagination_fn: F,
         executor: Arc<Executor>,
@@ -191,6 +327,8 @@ impl<T: Default + DeserializeOwned + Request> Pagination<T> {
             + Send
             + 'static,
     {
+        let handle = Cancellation::new();
+        let cancellation = handle.clone();
         Self {
             data: vec![],
             next_fn: Box::new(pagination_fn),
@@ -205,6 +343,9 @@ impl<T: Default + DeserializeOwned + Request> Pagination<T> {
             },
             count: 0,
             next_type: None,
+            page_timeout: None,
+            cancelled: Box::pin(async move { cancellation.cancelled().await }),
+            cancellation: handle,
         }
     }
 
@@ -226,6 +367,36 @@ impl<T: Default + DeserializeOwned + Request> Pagination<T> {
         self.paginator_options.page_size = size
     }
 
+    /// Set the maximal time a single page request may take. If a request takes longer, it is
+    /// aborted and an [`Error::Timeout`] is returned instead. Calling [`StreamExt::next`] again
+    /// retries the page.
+    pub fn page_timeout(&mut self, timeout: Duration) {
+        self.page_timeout = Some(timeout)
+    }
+
+    /// Return a [`Cancellation`] handle which can be used to cancel this pagination from anywhere
+    /// else, e.g. another task. Dropping the pagination itself cancels it too.
+    pub fn cancellation(&self) -> Cancellation {
+        self.cancellation.clone()
+    }
+
+    /// Fetch all remaining items and return the ones which match `predicate`. Use it together with
+    /// [`crate::media::MediaFilter::matches`] to filter media results like the watchlist, watch
+    /// history or browse results.
+    pub async fn filter_collect<F: FnMut(&T) -> bool>(
+        mut self,
+        mut predicate: F,
+    ) -> Result<Vec<T>> {
+        let mut result = vec![];
+        while let Some(item) = StreamExt::next(&mut self).await {
+            let item = item?;
+            if predicate(&item) {
+                result.push(item)
+            }
+        }
+        Ok(result)
+    }
+
     /// Return the total amount of items which can be fetched. Is [`Some`] if the total amount is
     /// known, else [`None`] (Crunchyroll has two different pagination implementations, one doesn't
     /// report the total amount).
@@ -233,8 +404,10 @@ impl<T: Default + DeserializeOwned + Request> Pagination<T> {
         if self.next_type.is_none() {
             StreamExt::next(self).await;
         }
-        if let PaginationNextType::Total(total) = self.next_type.as_ref().unwrap() {
-            Some(*total)
+        // the first page request failed or the pagination got cancelled if there's still no next
+        // type
+        if let Some(PaginationNextType::Total(total)) = self.next_type {
+            Some(total)
         } else {
             None
         }
@@ -254,18 +427,54 @@ pub(crate) struct BulkResult<T: Default + DeserializeOwned + Request> {
     pub total: u32,
 }
 
+/// The raw json a struct was deserialized from. It's only stored if the `raw-json` feature is
+/// enabled, otherwise this struct is zero-sized.
+#[derive(Clone, Default)]
+pub(crate) struct RawJson {
+    #[cfg(feature = "raw-json")]
+    value: Option<Arc<serde_json::Value>>,
+}
+
+#[cfg(feature = "raw-json")]
+impl RawJson {
+    pub(crate) fn new(value: serde_json::Value) -> Self {
+        Self {
+            value: Some(Arc::new(value)),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<Arc<serde_json::Value>> {
+        self.value.clone()
+    }
+}
+
+impl std::fmt::Debug for RawJson {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the raw json is usually large and duplicates the struct it's part of
+        write!(f, "<raw json>")
+    }
+}
+
 /// The standard representation of images how the api returns them.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct Image {
     pub source: String,
-    #[serde(rename(deserialize = "type"))]
+    #[serde(rename(deserialize = "type"), alias = "image_type")]
     pub image_type: String,
     pub height: u32,
     pub width: u32,
 }
 
+impl Image {
+    /// Downloads the image. Images are served by a CDN, so the request is neither authenticated
+    /// nor rate limited.
+    pub async fn data(&self, crunchyroll: &crate::Crunchyroll) -> Result<Vec<u8>> {
+        crunchyroll.executor.request_external(&self.source).await
+    }
+}
+
 /// Helper trait for [`Crunchyroll::request`] generic returns.
 /// Must be implemented for every struct which is used as generic parameter for [`Crunchyroll::request`].
 #[doc(hidden)]
@@ -282,3 +491,41 @@ impl Request for () {}
 impl<K: Send, V: Send> Request for HashMap<K, V> {}
 impl<K: Send, V: Send> Request for serde_json::Map<K, V> {}
 impl Request for serde_json::Value {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::NewsFeed;
+
+    #[tokio::test]
+    async fn pagination_cancel_pending_page() {
+        let mut pagination: Pagination<NewsFeed> = Pagination::new(
+            |_| Box::pin(std::future::pending()),
+            Arc::new(Executor::default()),
+            None,
+            None,
+        );
+        let cancellation = pagination.cancellation();
+
+        let next = tokio::spawn(async move { pagination.next().await.is_none() });
+        tokio::task::yield_now().await;
+        cancellation.cancel();
+        assert!(tokio::time::timeout(Duration::from_secs(1), next)
+            .await
+            .unwrap()
+            .unwrap())
+    }
+
+    #[tokio::test]
+    async fn cancellation_run() {
+        let cancellation = Cancellation::new();
+        assert_eq!(cancellation.run(async { Ok(1) }, "url").await.unwrap(), 1);
+
+        cancellation.cancel();
+        assert!(cancellation.is_cancelled());
+        assert!(matches!(
+            cancellation.run(async { Ok(1) }, "url").await,
+            Err(Error::Cancelled { .. })
+        ))
+    }
+}
@@ -0,0 +1,128 @@
+//! Mitigation of requests which are blocked by the Cloudflare bot protection.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Information about a block mitigation attempt. Passed to the callback set via
+/// [`BlockMitigation::on_attempt`].
+#[derive(Clone, Debug)]
+pub struct BlockMitigationEvent {
+    /// Number of the attempt, starting at 1.
+    pub attempt: u32,
+    /// The url which got blocked.
+    pub url: String,
+    /// The user agent which is used from now on. [`None`] if no user agents to rotate are set.
+    pub user_agent: Option<String>,
+    /// If the Cloudflare cookies were refreshed.
+    pub refreshed_cookies: bool,
+    /// How long is waited until the request is retried.
+    pub delay: std::time::Duration,
+}
+
+/// Configuration how to mitigate requests which are blocked by the Cloudflare bot protection
+/// ([`crate::error::Error::Block`]). Set it via [`crate::crunchyroll::CrunchyrollBuilder::block_mitigation`].
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct BlockMitigation {
+    user_agents: Vec<String>,
+    user_agent_index: Arc<AtomicUsize>,
+    pub(crate) refresh_cookies: bool,
+    pub(crate) max_retries: u32,
+    retry_delay: std::time::Duration,
+    jitter: std::time::Duration,
+    pub(crate) on_attempt: Option<Arc<dyn Fn(&BlockMitigationEvent) + Send + Sync>>,
+}
+
+impl Default for BlockMitigation {
+    fn default() -> Self {
+        Self {
+            user_agents: vec![],
+            user_agent_index: Arc::new(AtomicUsize::new(0)),
+            refresh_cookies: true,
+            max_retries: 2,
+            retry_delay: std::time::Duration::from_secs(1),
+            jitter: std::time::Duration::from_secs(1),
+            on_attempt: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for BlockMitigation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockMitigation")
+            .field("user_agents", &self.user_agents)
+            .field("refresh_cookies", &self.refresh_cookies)
+            .field("max_retries", &self.max_retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BlockMitigation {
+    /// User agents which are rotated through on every mitigation attempt. Once rotated, the
+    /// user agent is used for all following requests. If empty (the default), the user agent
+    /// of the client is always used.
+    pub fn user_agents(mut self, user_agents: Vec<String>) -> BlockMitigation {
+        self.user_agents = user_agents;
+        self
+    }
+
+    /// If the index page of crunchyroll.com should be requested again to refresh the
+    /// Cloudflare cookies before retrying. Default is `true`.
+    pub fn refresh_cookies(mut self, enable: bool) -> BlockMitigation {
+        self.refresh_cookies = enable;
+        self
+    }
+
+    /// How often a blocked request is retried. Default is 2.
+    pub fn max_retries(mut self, max_retries: u32) -> BlockMitigation {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait before a blocked request is retried. Default is 1 second.
+    pub fn retry_delay(mut self, retry_delay: std::time::Duration) -> BlockMitigation {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Maximal random time which is added to [`BlockMitigation::retry_delay`]. Default is 1
+    /// second.
+    pub fn jitter(mut self, jitter: std::time::Duration) -> BlockMitigation {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set a callback which is called on every mitigation attempt.
+    pub fn on_attempt<F>(mut self, callback: F) -> BlockMitigation
+    where
+        F: Fn(&BlockMitigationEvent) + Send + Sync + 'static,
+    {
+        self.on_attempt = Some(Arc::new(callback));
+        self
+    }
+
+    pub(crate) fn current_user_agent(&self) -> Option<String> {
+        let index = self.user_agent_index.load(Ordering::SeqCst);
+        if index == 0 || self.user_agents.is_empty() {
+            return None;
+        }
+        Some(self.user_agents[(index - 1) % self.user_agents.len()].clone())
+    }
+
+    pub(crate) fn rotate_user_agent(&self) -> Option<String> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        self.user_agent_index.fetch_add(1, Ordering::SeqCst);
+        self.current_user_agent()
+    }
+
+    pub(crate) fn delay(&self) -> std::time::Duration {
+        let jitter_millis = self.jitter.as_millis() as u64;
+        // a uuid is random anyway, so it can be used here instead of pulling in a rng crate
+        let random = uuid::Uuid::new_v4().as_u128() as u64;
+        self.retry_delay + std::time::Duration::from_millis(random % (jitter_millis + 1))
+    }
+}
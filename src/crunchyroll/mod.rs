@@ -0,0 +1,2507 @@
+//! Builder and access to the [`Crunchyroll`] struct which is required to make any action.
+
+use crate::enum_values;
+use crate::error::{Error, ParseWarnings};
+use reqwest::Client;
+use std::sync::Arc;
+
+mod block_mitigation;
+mod proxy;
+mod rate_limit;
+mod token_store;
+
+enum_values! {
+    /// Enum of supported languages by Crunchyroll.
+    /// Crunchyroll lists the available languages in the following api results:
+    /// - <https://static.crunchyroll.com/config/i18n/v3/audio_languages.json>
+    /// - <https://static.crunchyroll.com/config/i18n/v3/timed_text_languages.json>
+    #[allow(non_camel_case_types)]
+    #[derive(Hash, Ord, PartialOrd)]
+    pub enum Locale {
+        ar_ME = "ar-ME"
+        ar_SA = "ar-SA"
+        ca_ES = "ca-ES"
+        de_DE = "de-DE"
+        en_IN = "en-IN"
+        en_US = "en-US"
+        es_419 = "es-419"
+        es_ES = "es-ES"
+        es_LA = "es-LA"
+        fr_FR = "fr-FR"
+        hi_IN = "hi-IN"
+        id_ID = "id-ID"
+        it_IT = "it-IT"
+        ja_JP = "ja-JP"
+        ko_KR = "ko-KR"
+        ms_MY = "ms-MY"
+        pl_PL = "pl-PL"
+        pt_BR = "pt-BR"
+        pt_PT = "pt-PT"
+        ru_RU = "ru-RU"
+        ta_IN = "ta-IN"
+        te_IN = "te-IN"
+        th_TH = "th-TH"
+        tr_TR = "tr-TR"
+        vi_VN = "vi-VN"
+        zh_CN = "zh-CN"
+        zh_HK = "zh_HK"
+        zh_TW = "zh-TW"
+    }
+}
+
+impl Locale {
+    /// If the locale is unknown. Locale fields which Crunchyroll doesn't always populate (like
+    /// [`crate::Episode::audio_locale`]) are set to this sentinel, which is [`Locale::default`]
+    /// (an empty [`Locale::Custom`]), if the api returns no locale or a placeholder.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Locale::Custom(raw) if raw.is_empty())
+    }
+
+    pub fn all() -> Vec<Locale> {
+        vec![
+            Locale::ar_ME,
+            Locale::ar_SA,
+            Locale::ca_ES,
+            Locale::de_DE,
+            Locale::en_IN,
+            Locale::en_US,
+            Locale::es_419,
+            Locale::es_ES,
+            Locale::es_LA,
+            Locale::fr_FR,
+            Locale::hi_IN,
+            Locale::id_ID,
+            Locale::it_IT,
+            Locale::ja_JP,
+            Locale::ko_KR,
+            Locale::ms_MY,
+            Locale::pl_PL,
+            Locale::pt_BR,
+            Locale::pt_PT,
+            Locale::ru_RU,
+            Locale::ta_IN,
+            Locale::te_IN,
+            Locale::th_TH,
+            Locale::tr_TR,
+            Locale::vi_VN,
+            Locale::zh_CN,
+            Locale::zh_HK,
+            Locale::zh_TW,
+        ]
+    }
+
+    pub fn to_human_readable(&self) -> String {
+        match self {
+            Locale::ar_ME => "Arabic",
+            Locale::ar_SA => "Arabic (Saudi Arabia)",
+            Locale::ca_ES => "Catalan",
+            Locale::de_DE => "German",
+            Locale::en_IN => "English (India)",
+            Locale::en_US => "English (US)",
+            Locale::es_419 | Locale::es_LA => "Spanish (Latin America)",
+            Locale::es_ES => "Spanish (European)",
+            Locale::fr_FR => "French",
+            Locale::hi_IN => "Hindi",
+            Locale::id_ID => "Indonesian",
+            Locale::it_IT => "Italian",
+            Locale::ja_JP => "Japanese",
+            Locale::ko_KR => "Korean",
+            Locale::ms_MY => "Malay",
+            Locale::pl_PL => "Polish",
+            Locale::pt_BR => "Portuguese (Brazil)",
+            Locale::pt_PT => "Portuguese (Europe)",
+            Locale::ru_RU => "Russian",
+            Locale::ta_IN => "Tamil",
+            Locale::te_IN => "Telugu",
+            Locale::th_TH => "Thai",
+            Locale::tr_TR => "Turkish",
+            Locale::vi_VN => "Vietnamese",
+            Locale::zh_CN => "Chinese (China)",
+            Locale::zh_HK => "Chinese (Cantonese)",
+            Locale::zh_TW => "Chinese (Mandarin)",
+            Locale::Custom(custom) => custom.as_str(),
+        }
+        .to_string()
+    }
+
+    /// Returns the locale which exactly matches the given language tag (e.g. `en-US`, `en_US` or
+    /// `en_US.UTF-8`). Use [`Locale::closest`] if the tag doesn't have to match exactly.
+    pub fn from_language_tag(tag: &str) -> Option<Locale> {
+        let (language, _, region) = split_language_tag(tag)?;
+        let region = region?;
+        Locale::all().into_iter().find(|locale| {
+            let locale = locale.to_string().replace('_', "-");
+            let (l, r) = locale.split_once('-').unwrap();
+            l.eq_ignore_ascii_case(&language) && r.eq_ignore_ascii_case(&region)
+        })
+    }
+
+    /// Returns the locale which fits best to the given language tag, e.g. from system or browser
+    /// settings. If there is no exact match (see [`Locale::from_language_tag`]), the language and
+    /// region are mapped to the closest locale, e.g. `es-MX` to [`Locale::es_419`], `pt` to
+    /// [`Locale::pt_BR`] or `en-GB` to [`Locale::en_US`]. Returns [`None`] if Crunchyroll doesn't
+    /// support the language at all.
+    pub fn closest(tag: &str) -> Option<Locale> {
+        if let Some(locale) = Locale::from_language_tag(tag) {
+            return Some(locale);
+        }
+
+        let (language, script, region) = split_language_tag(tag)?;
+        let region = region.unwrap_or_default();
+        let locale = match language.as_str() {
+            "ar" => match region.as_str() {
+                "SA" => Locale::ar_SA,
+                _ => Locale::ar_ME,
+            },
+            "en" => match region.as_str() {
+                "IN" => Locale::en_IN,
+                _ => Locale::en_US,
+            },
+            "es" => match region.as_str() {
+                "" | "ES" => Locale::es_ES,
+                _ => Locale::es_419,
+            },
+            "pt" => match region.as_str() {
+                "PT" | "AO" | "MZ" => Locale::pt_PT,
+                _ => Locale::pt_BR,
+            },
+            "zh" => match (script.as_deref(), region.as_str()) {
+                (_, "HK" | "MO") => Locale::zh_HK,
+                (Some("Hant"), _) | (_, "TW") => Locale::zh_TW,
+                _ => Locale::zh_CN,
+            },
+            _ => Locale::all().into_iter().find(|locale| {
+                locale
+                    .to_string()
+                    .split(['-', '_'])
+                    .next()
+                    .unwrap()
+                    .eq_ignore_ascii_case(&language)
+            })?,
+        };
+        Some(locale)
+    }
+}
+
+/// Splits a BCP-47 (`zh-Hant-TW`) or POSIX (`en_US.UTF-8`) language tag into language, script
+/// and region. Language is lowercase, script titlecase and region uppercase.
+fn split_language_tag(tag: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let tag = tag.split(['.', '@']).next().unwrap();
+    let mut subtags = tag.split(['-', '_']).filter(|s| !s.is_empty());
+
+    let language = subtags.next()?.to_ascii_lowercase();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut script = None;
+    let mut region = None;
+    for subtag in subtags {
+        // extensions and private use subtags are starting with a single character subtag
+        if subtag.len() == 1 {
+            break;
+        }
+        if subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+            let mut chars = subtag.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            script = Some(format!("{first}{}", chars.as_str().to_ascii_lowercase()))
+        } else if (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+            || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+        {
+            region = Some(subtag.to_ascii_uppercase());
+            break;
+        }
+    }
+
+    Some((language, script, region))
+}
+
+enum_values! {
+    /// Maturity rating.
+    pub enum MaturityRating {
+        NotMature = "M2"
+        Mature = "M3"
+    }
+}
+
+enum_values! {
+    /// Identity provider of an account which was created via a partner login. Used by
+    /// [`CrunchyrollBuilder::login_with_external_token`]. The values which are sent for the
+    /// providers weren't verified against the api.
+    pub enum ExternalLoginProvider {
+        Google = "google"
+        Apple = "apple"
+    }
+}
+
+/// Result of [`Crunchyroll::health_check`].
+#[derive(Clone, Debug)]
+pub struct HealthCheck {
+    /// If Crunchyroll responded at all (even if the response was an error).
+    pub reachable: bool,
+    /// If the current session is valid and requests can be made with it.
+    pub authenticated: bool,
+    /// How long the check took.
+    pub latency: std::time::Duration,
+    /// The error which occurred while checking, if any.
+    pub error: Option<Error>,
+}
+
+/// Starting point of this whole library.
+#[derive(Clone, Debug)]
+pub struct Crunchyroll {
+    pub(crate) executor: Arc<Executor>,
+}
+
+impl Crunchyroll {
+    pub fn builder() -> CrunchyrollBuilder {
+        CrunchyrollBuilder::default()
+    }
+
+    /// Return the (cloned) [`Client`] which is internally used to make requests.
+    pub fn client(&self) -> Client {
+        self.executor.client.clone()
+    }
+
+    /// Check if the current used account has premium.
+    pub async fn premium(&self) -> bool {
+        self.executor.premium().await
+    }
+
+    /// Return the country (ISO 3166-1 alpha-2 code, e.g. `US`) Crunchyroll detected for this
+    /// session. The available catalog depends on it. Crunchyroll detects the country by the ip the
+    /// login requests were made from, use a proxy (see [`CrunchyrollBuilder::proxy`]) to get a
+    /// session in another country.
+    pub async fn region(&self) -> Option<String> {
+        self.executor
+            .jwt_claim::<String>("country")
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Return the log of response anomalies which were tolerated because
+    /// [`CrunchyrollBuilder::lenient_parsing`] is enabled. The log is shared with this instance, so
+    /// it also contains warnings of requests made after this call.
+    pub fn parse_warnings(&self) -> ParseWarnings {
+        self.executor.hooks.parse_warnings.clone()
+    }
+
+    /// Return the access token used to make requests. The token changes every 5 minutes, so you
+    /// might have to re-call this function if you have a long-living session where you need it.
+    pub async fn access_token(&self) -> String {
+        self.executor.config.read().await.access_token.clone()
+    }
+
+    /// Return the current session token. It can be used to log-in later with
+    /// [`CrunchyrollBuilder::login_with_refresh_token`] or [`CrunchyrollBuilder::login_with_etp_rt`].
+    pub async fn session_token(&self) -> SessionToken {
+        self.executor.config.read().await.session_token.clone()
+    }
+
+    /// Sends a lightweight, authenticated request to Crunchyroll to check if the api is reachable
+    /// and the current session is still valid. If the request takes longer than `timeout`, the
+    /// check fails. Useful for e.g. readiness probes of long-running services.
+    pub async fn health_check(&self, timeout: std::time::Duration) -> HealthCheck {
+        let endpoint = "https://www.crunchyroll.com/index/v2";
+
+        let start = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            timeout,
+            self.executor
+                .get(endpoint)
+                .request::<serde_json::Map<String, serde_json::Value>>(),
+        )
+        .await;
+        let latency = start.elapsed();
+
+        match result {
+            Ok(Ok(_)) => HealthCheck {
+                reachable: true,
+                authenticated: true,
+                latency,
+                error: None,
+            },
+            Ok(Err(error)) => HealthCheck {
+                reachable: matches!(
+                    error,
+                    Error::Request {
+                        status: Some(_),
+                        ..
+                    } | Error::Decode { .. }
+                        | Error::Authentication { .. }
+                        | Error::Block { .. }
+                        | Error::GeoRestricted { .. }
+                ),
+                authenticated: false,
+                latency,
+                error: Some(error),
+            },
+            Err(_) => HealthCheck {
+                reachable: false,
+                authenticated: false,
+                latency,
+                error: Some(Error::Request {
+                    message: format!("Health check timed out after {}ms", timeout.as_millis()),
+                    status: None,
+                    url: endpoint.to_string(),
+                    code: None,
+                    request_id: None,
+                }),
+            },
+        }
+    }
+}
+
+mod auth {
+    use crate::crunchyroll::block_mitigation::{BlockMitigation, BlockMitigationEvent};
+    use crate::crunchyroll::proxy::{is_api_url, ProxyConfig};
+    use crate::crunchyroll::rate_limit::{RateLimitRetry, RateLimiter};
+    use crate::crunchyroll::token_store::TokenStore;
+    use crate::crunchyroll::ExternalLoginProvider;
+    use crate::error::{check_request, rate_limited, request_id, ApiErrorCode, Error};
+    use crate::internal::hooks::Hooks;
+    use crate::{Crunchyroll, Locale, Request, Result};
+    use chrono::{DateTime, Duration, Utc};
+    use reqwest::{
+        header, Client, ClientBuilder, IntoUrl, Method, RequestBuilder, StatusCode, Url,
+    };
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+    use std::borrow::Cow;
+    use std::ops::Add;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    /// Stores if the refresh token or etp-rt cookie was used for login. Extract the token and use
+    /// it as argument in their associated function ([`CrunchyrollBuilder::login_with_refresh_token`]
+    /// or [`CrunchyrollBuilder::login_with_etp_rt`]) if you want to re-login into the account again.
+    #[derive(Clone, Debug)]
+    pub enum SessionToken {
+        RefreshToken(String),
+        EtpRt(String),
+        Anonymous,
+    }
+
+    /// A basic auth token of an official Crunchyroll client which is required to get a session
+    /// token. Crunchyroll rotates these tokens from time to time, use
+    /// [`CrunchyrollBuilder::basic_auth_token`] to set a newer one or
+    /// [`CrunchyrollBuilder::auto_basic_auth_token`] to always use the current token of the web
+    /// app.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct BasicAuthToken(Cow<'static, str>);
+
+    impl BasicAuthToken {
+        /// Token of the Nintendo Switch app. This is the default token.
+        pub const NINTENDO_SWITCH: BasicAuthToken = BasicAuthToken(Cow::Borrowed(
+            "dC1rZGdwMmg4YzNqdWI4Zm4wZnE6eWZMRGZNZnJZdktYaDRKWFMxTEVJMmNDcXUxdjVXYW4=",
+        ));
+        /// Token of the web app. Always used with [`CrunchyrollBuilder::login_with_etp_rt`].
+        pub const WEB: BasicAuthToken =
+            BasicAuthToken(Cow::Borrowed("bm9haWhkZXZtXzZpeWcwYThsMHE6"));
+
+        /// Create a new token from its raw (base64 encoded) representation.
+        pub fn new<S: Into<String>>(token: S) -> Self {
+            Self(Cow::Owned(token.into()))
+        }
+
+        /// Create a new token from a client id and secret. The secret is empty for some clients.
+        pub fn from_client_credentials<S: AsRef<str>>(client_id: S, client_secret: S) -> Self {
+            use base64::Engine;
+
+            Self::new(base64::engine::general_purpose::STANDARD.encode(format!(
+                "{}:{}",
+                client_id.as_ref(),
+                client_secret.as_ref()
+            )))
+        }
+
+        /// Extract the token from the app config which is embedded in the html of the
+        /// crunchyroll.com index page.
+        fn from_app_config(html: &str) -> Result<Self> {
+            let key = "\"accountAuthClientId\":\"";
+            let client_id = html
+                .split_once(key)
+                .and_then(|(_, rest)| rest.split_once('"'))
+                .map(|(client_id, _)| client_id)
+                .ok_or_else(|| Error::Authentication {
+                    message: "Could not find basic auth token in the Crunchyroll app config"
+                        .to_string(),
+                })?;
+            Ok(Self::from_client_credentials(client_id, ""))
+        }
+
+        /// The raw (base64 encoded) representation of the token.
+        pub fn as_str(&self) -> &str {
+            self.0.as_ref()
+        }
+    }
+
+    impl Default for BasicAuthToken {
+        fn default() -> Self {
+            Self::NINTENDO_SWITCH
+        }
+    }
+
+    impl std::fmt::Display for BasicAuthToken {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    /// Information about the device that creates a new session.
+    #[derive(Clone, Debug)]
+    pub struct DeviceIdentifier {
+        /// The device id, this is specific for every device type, but usually represented as UUID.
+        /// Using [`Uuid::new_v4`] for it works fine.
+        device_id: String,
+        /// Type of the device which issues the session, e.g. `Chrome on Windows` or `iPhone 15`.
+        device_type: String,
+        /// Name of the device which issues the session. This may be empty, for example all session
+        /// that are created over the website have an empty name; when issues via the app, the name
+        /// is the name of your phone (which you can modify/set when you set up the phone).
+        device_name: String,
+    }
+
+    impl DeviceIdentifier {
+        /// Fields of the identifier in the form body of a token request.
+        fn form_fields(&self) -> [(&'static str, &str); 3] {
+            [
+                ("device_id", self.device_id.as_str()),
+                ("device_type", self.device_type.as_str()),
+                ("device_name", self.device_name.as_str()),
+            ]
+        }
+    }
+
+    /// Base urls of the hosts the library sends its requests to. Every request to one of the default
+    /// hosts is sent to the configured base url instead, e.g. to run tests against a mock server or
+    /// to route requests through a regional edge proxy. Set it via
+    /// [`CrunchyrollBuilder::endpoints`].
+    #[derive(Clone, Debug)]
+    pub struct Endpoints {
+        api: String,
+        static_assets: String,
+        playback: String,
+    }
+
+    impl Endpoints {
+        const DEFAULT_API: &'static str = "https://www.crunchyroll.com";
+        const DEFAULT_STATIC_ASSETS: &'static str = "https://static.crunchyroll.com";
+        const DEFAULT_PLAYBACK: &'static str = "https://cr-play-service.prd.crunchyrollsvc.com";
+
+        /// Base url of the api, including authentication. Default is
+        /// `https://www.crunchyroll.com`.
+        pub fn api(mut self, url: impl Into<String>) -> Endpoints {
+            self.api = url.into();
+            self
+        }
+
+        /// Base url of static assets like skip events or wallpapers. Default is
+        /// `https://static.crunchyroll.com`.
+        pub fn static_assets(mut self, url: impl Into<String>) -> Endpoints {
+            self.static_assets = url.into();
+            self
+        }
+
+        /// Base url of the playback service which issues streams. Default is
+        /// `https://cr-play-service.prd.crunchyrollsvc.com`.
+        pub fn playback(mut self, url: impl Into<String>) -> Endpoints {
+            self.playback = url.into();
+            self
+        }
+
+        /// Returns the url with the overwritten base url, or [`None`] if the url isn't affected.
+        fn rewrite(&self, url: &Url) -> Result<Option<Url>> {
+            for (default, base) in [
+                (Self::DEFAULT_API, &self.api),
+                (Self::DEFAULT_STATIC_ASSETS, &self.static_assets),
+                (Self::DEFAULT_PLAYBACK, &self.playback),
+            ] {
+                let base = base.trim_end_matches('/');
+                if base == default {
+                    continue;
+                }
+                let Some(rest) = url.as_str().strip_prefix(default) else {
+                    continue;
+                };
+                if !rest.is_empty() && !rest.starts_with(['/', '?']) {
+                    continue;
+                }
+                let rewritten = format!("{base}{rest}");
+                return Url::parse(&rewritten).map(Some).map_err(|e| Error::Input {
+                    message: format!("invalid endpoint url '{rewritten}': {e}"),
+                });
+            }
+            Ok(None)
+        }
+    }
+
+    impl Default for Endpoints {
+        fn default() -> Self {
+            Self {
+                api: Self::DEFAULT_API.to_string(),
+                static_assets: Self::DEFAULT_STATIC_ASSETS.to_string(),
+                playback: Self::DEFAULT_PLAYBACK.to_string(),
+            }
+        }
+    }
+
+    /// Source of the current time. It's used to check if the session has expired and must be
+    /// refreshed, to resolve the date of a `Retry-After` header, to time the playhead updates of
+    /// [`crate::media::WatchProgressTracker`] and to track the limits of a
+    /// [`crate::media::StreamSessionGuard`]. Set it via [`CrunchyrollBuilder::clock`], e.g. to
+    /// simulate token expiry in tests or to correct the time of a device with a skewed clock (for
+    /// example by comparing it to the `Date` header of a response).
+    ///
+    /// Durations which are only measured, like the throughput of downloads, the interval of a
+    /// [`RateLimiter`] or how long a request took, use the monotonic clock of the system and aren't
+    /// affected by it.
+    pub trait Clock: std::fmt::Debug + Send + Sync {
+        /// Return the current time.
+        fn now(&self) -> DateTime<Utc>;
+    }
+
+    /// [`Clock`] which uses the system time. This is the default.
+    #[derive(Clone, Debug, Default)]
+    pub struct SystemClock;
+
+    impl Clock for SystemClock {
+        fn now(&self) -> DateTime<Utc> {
+            Utc::now()
+        }
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+    #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+    #[allow(dead_code)]
+    struct AuthResponse {
+        access_token: String,
+        /// Is [`None`] if generated via [`Executor::auth_anonymously`].
+        refresh_token: Option<String>,
+        expires_in: i32,
+        token_type: String,
+        scope: String,
+        country: String,
+        /// Is [`None`] if generated via [`Executor::auth_anonymously`].
+        account_id: Option<String>,
+        /// Is [`None`] if generated via [`Executor::auth_anonymously`].
+        profile_id: Option<String>,
+    }
+
+    #[derive(Clone, Debug)]
+    pub(crate) struct ExecutorConfig {
+        pub(crate) token_type: String,
+        pub(crate) access_token: String,
+        pub(crate) session_token: SessionToken,
+        pub(crate) session_expire: DateTime<Utc>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Clone, Debug)]
+    pub(crate) struct ExecutorDetails {
+        pub(crate) basic_auth_token: BasicAuthToken,
+
+        pub(crate) locale: Locale,
+        pub(crate) preferred_audio_locale: Option<Locale>,
+
+        pub(crate) bucket: String,
+
+        pub(crate) signature: String,
+        pub(crate) policy: String,
+        pub(crate) key_pair_id: String,
+        /// The account id is wrapped in a [`Result`] since [`Executor::auth_anonymously`] /
+        /// [`CrunchyrollBuilder::login_anonymously`] doesn't return an account id and to prevent
+        /// writing error messages multiple times in functions which require the account id to be
+        /// set they can just get the id or return the fix set error message.
+        pub(crate) account_id: Result<String>,
+        /// The device the session was created for. It's sent along when refreshing the session.
+        pub(crate) device_identifier: Option<DeviceIdentifier>,
+    }
+
+    #[cfg(feature = "experimental-stabilizations")]
+    /// Contains which fixes should be used to make the api more reliable as Crunchyroll does weird
+    /// stuff / delivers incorrect results.
+    #[derive(Clone, Debug)]
+    pub(crate) struct ExecutorFixes {
+        pub(crate) locale_name_parsing: bool,
+        pub(crate) season_number: bool,
+    }
+
+    /// Internal struct to execute all request with.
+    #[derive(Debug)]
+    pub struct Executor {
+        pub(crate) client: Client,
+
+        /// Must be a [`RwLock`] because `Executor` is always passed inside `Arc` which does not
+        /// allow direct changes to the struct.
+        pub(crate) config: RwLock<ExecutorConfig>,
+        pub(crate) details: ExecutorDetails,
+
+        pub(crate) hooks: Hooks,
+        pub(crate) block_mitigation: Option<BlockMitigation>,
+        pub(crate) clock: Arc<dyn Clock>,
+        pub(crate) token_store: Option<Arc<dyn TokenStore>>,
+        #[cfg(feature = "tower")]
+        pub(crate) middleware: Option<tokio::sync::Mutex<crate::internal::tower::Middleware>>,
+        #[cfg(feature = "experimental-stabilizations")]
+        pub(crate) fixes: ExecutorFixes,
+    }
+
+    impl Executor {
+        /// Requests a resource outside the Crunchyroll api, e.g. an image or a news article. Only
+        /// the client (and with it its proxy / tls configuration) is used, the request is neither
+        /// authenticated nor does it go through the hooks or middleware of the session.
+        pub(crate) async fn request_external(&self, url: &str) -> Result<Vec<u8>> {
+            let req = self.client.get(url).build()?;
+            #[cfg(feature = "testing")]
+            let resp = match &self.hooks.transport {
+                Some(transport) => transport.respond(req)?,
+                None => self.client.execute(req).await?,
+            };
+            #[cfg(not(feature = "testing"))]
+            let resp = self.client.execute(req).await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                return Err(Error::Request {
+                    message: format!("failed to request {url}: {status}"),
+                    status: Some(status),
+                    url: url.to_string(),
+                    code: None,
+                    request_id: request_id(resp.headers()),
+                });
+            }
+            Ok(resp.bytes().await?.to_vec())
+        }
+
+        pub(crate) fn get<U: IntoUrl>(self: &Arc<Self>, url: U) -> ExecutorRequestBuilder {
+            ExecutorRequestBuilder::new(self.clone(), self.client.get(url))
+        }
+
+        pub(crate) fn post<U: IntoUrl>(self: &Arc<Self>, url: U) -> ExecutorRequestBuilder {
+            ExecutorRequestBuilder::new(self.clone(), self.client.post(url))
+        }
+
+        pub(crate) fn put<U: IntoUrl>(self: &Arc<Self>, url: U) -> ExecutorRequestBuilder {
+            ExecutorRequestBuilder::new(self.clone(), self.client.put(url))
+        }
+
+        pub(crate) fn patch<U: IntoUrl>(self: &Arc<Self>, url: U) -> ExecutorRequestBuilder {
+            ExecutorRequestBuilder::new(self.clone(), self.client.patch(url))
+        }
+
+        pub(crate) fn delete<U: IntoUrl>(self: &Arc<Self>, url: U) -> ExecutorRequestBuilder {
+            ExecutorRequestBuilder::new(self.clone(), self.client.delete(url))
+        }
+
+        #[cfg_attr(
+            feature = "tracing",
+            tracing::instrument(
+                level = "debug",
+                skip_all,
+                fields(response = std::any::type_name::<T>())
+            )
+        )]
+        pub(crate) async fn request<T: Request + DeserializeOwned>(
+            self: &Arc<Self>,
+            mut req: RequestBuilder,
+        ) -> Result<T> {
+            req = self.auth_req(req).await?;
+            req = req.header(header::CONTENT_TYPE, "application/json");
+
+            let mut resp: T = request(
+                &self.client,
+                &self.hooks,
+                self.clock.as_ref(),
+                self.block_mitigation.as_ref(),
+                req,
+                #[cfg(feature = "tower")]
+                self.middleware.as_ref(),
+            )
+            .await?;
+
+            resp.__set_executor(self.clone()).await;
+
+            Ok(resp)
+        }
+
+        pub(crate) async fn auth_req(
+            self: &Arc<Self>,
+            mut req: RequestBuilder,
+        ) -> Result<RequestBuilder> {
+            let mut config = self.config.write().await;
+            let mut rotated_token = None;
+            if config.session_expire <= self.clock.now() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("session expired, refreshing it");
+                let login_response = match &config.session_token {
+                    SessionToken::RefreshToken(refresh_token) => {
+                        Executor::auth_with_refresh_token(
+                            &self.auth_context(),
+                            refresh_token.as_str(),
+                        )
+                        .await?
+                    }
+                    SessionToken::EtpRt(etp_rt) => {
+                        Executor::auth_with_etp_rt(&self.auth_context(), etp_rt.as_str()).await?
+                    }
+                    SessionToken::Anonymous => {
+                        Executor::auth_anonymously(&self.auth_context()).await?
+                    }
+                };
+
+                let mut new_config = config.clone();
+                new_config.token_type = login_response.token_type;
+                new_config.access_token = login_response.access_token;
+                new_config.session_token = match new_config.session_token {
+                    SessionToken::RefreshToken(_) => {
+                        SessionToken::RefreshToken(login_response.refresh_token.unwrap())
+                    }
+                    SessionToken::EtpRt(_) => {
+                        SessionToken::EtpRt(login_response.refresh_token.unwrap())
+                    }
+                    SessionToken::Anonymous => SessionToken::Anonymous,
+                };
+                new_config.session_expire = self
+                    .clock
+                    .now()
+                    .add(Duration::try_seconds(login_response.expires_in as i64).unwrap());
+
+                let rotated = !matches!(new_config.session_token, SessionToken::Anonymous);
+                *config = new_config;
+                if rotated {
+                    if let Some(on_token_rotation) = &self.hooks.on_token_rotation {
+                        on_token_rotation(&config.session_token)
+                    }
+                    rotated_token = Some(config.session_token.clone())
+                }
+            }
+
+            req = req.header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", config.access_token),
+            );
+            drop(config);
+
+            if let (Some(token), Some(token_store)) = (rotated_token, &self.token_store) {
+                // the session is already rotated at this point, failing the request wouldn't undo
+                // that
+                if let Err(e) = token_store.set(&token).await {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "failed to store rotated session token");
+                    if let Some(on_token_store_error) = &self.hooks.on_token_store_error {
+                        on_token_store_error(&e)
+                    }
+                }
+            }
+            Ok(req)
+        }
+
+        pub(crate) async fn jwt_claim<T: DeserializeOwned>(
+            &self,
+            claim: &str,
+        ) -> Result<Option<T>> {
+            let executor_config = self.config.read().await;
+
+            let mut claims = jwt_claims(executor_config.access_token.as_str())?;
+            if let Some(claim) = claims.remove(claim) {
+                Ok(serde_json::from_value(claim)?)
+            } else {
+                Ok(None)
+            }
+        }
+
+        pub(crate) async fn premium(&self) -> bool {
+            self.jwt_claim::<Vec<String>>("benefits")
+                .await
+                .unwrap()
+                .unwrap_or_default()
+                .contains(&"cr_premium".to_string())
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_anonymously(ctx: &AuthContext<'_>) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let mut builder = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+            if !ctx.hooks.do_not_track {
+                builder = builder.header("ETP-Anonymous-ID", uuid::Uuid::new_v4().to_string())
+            }
+            let req = builder
+                .body(
+                    serde_urlencoded::to_string([
+                        ("grant_type", "client_id"),
+                        ("scope", "offline_access"),
+                    ])
+                    .unwrap(),
+                )
+                .build()?;
+            ctx.execute(req).await
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_credentials(
+            ctx: &AuthContext<'_>,
+            email: &str,
+            password: &str,
+        ) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let mut body = vec![
+                ("username", email),
+                ("password", password),
+                ("grant_type", "password"),
+                ("scope", "offline_access"),
+            ];
+            if let Some(device_identifier) = ctx.device_identifier {
+                body.extend(device_identifier.form_fields())
+            }
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn signup(
+            ctx: &AuthContext<'_>,
+            anonymous_response: &AuthResponse,
+            email: &str,
+            password: &str,
+            locale: &Locale,
+            marketing_opt_in: bool,
+        ) -> Result<()> {
+            let endpoint = "https://www.crunchyroll.com/accounts/v1/signup";
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!(
+                        "{} {}",
+                        anonymous_response.token_type, anonymous_response.access_token
+                    ),
+                )
+                .json(&serde_json::json!({
+                    "email": email,
+                    "password": password,
+                    "preferred_communication_language": locale,
+                    "email_marketing_opt_in": marketing_opt_in,
+                }))
+                .build()?;
+            ctx.execute::<serde_json::Map<String, serde_json::Value>>(req)
+                .await?;
+            Ok(())
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_refresh_token(
+            ctx: &AuthContext<'_>,
+            refresh_token: &str,
+        ) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let mut body = vec![
+                ("refresh_token", refresh_token),
+                ("grant_type", "refresh_token"),
+                ("scope", "offline_access mp"),
+            ];
+            if let Some(device_identifier) = ctx.device_identifier {
+                body.extend(device_identifier.form_fields())
+            }
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_refresh_token_profile_id(
+            ctx: &AuthContext<'_>,
+            refresh_token: &str,
+            profile_id: &str,
+        ) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let body = vec![
+                ("refresh_token", refresh_token),
+                ("grant_type", "refresh_token_profile_id"),
+                ("scope", "offline_access"),
+                ("profile_id", profile_id),
+            ];
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_etp_rt(ctx: &AuthContext<'_>, etp_rt: &str) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let body = vec![("grant_type", "etp_rt_cookie"), ("scope", "offline_access")];
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", BasicAuthToken::WEB),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header(header::COOKIE, format!("etp_rt={etp_rt}"))
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+    #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+    struct DeviceCodeResponse {
+        device_code: String,
+        user_code: String,
+        expires_in: i64,
+        interval: Option<u64>,
+    }
+
+    impl Executor {
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn request_device_code(ctx: &AuthContext<'_>) -> Result<DeviceCodeResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/device/code";
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string([("scope", "offline_access")]).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_device_code(
+            ctx: &AuthContext<'_>,
+            device_code: &str,
+        ) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/device/token";
+            let mut body = vec![
+                ("device_code", device_code),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("scope", "offline_access"),
+            ];
+            if let Some(device_identifier) = ctx.device_identifier {
+                body.extend(device_identifier.form_fields())
+            }
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+    }
+
+    impl Executor {
+        #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+        async fn auth_with_external_token(
+            ctx: &AuthContext<'_>,
+            provider: &ExternalLoginProvider,
+            id_token: &str,
+        ) -> Result<AuthResponse> {
+            let endpoint = "https://www.crunchyroll.com/auth/v1/token";
+            let provider = provider.to_string();
+            let mut body = vec![
+                (
+                    "grant_type",
+                    "urn:ietf:params:oauth:grant-type:token-exchange",
+                ),
+                ("subject_token", id_token),
+                (
+                    "subject_token_type",
+                    "urn:ietf:params:oauth:token-type:id_token",
+                ),
+                ("provider", provider.as_str()),
+                ("scope", "offline_access"),
+            ];
+            if let Some(device_identifier) = ctx.device_identifier {
+                body.extend(device_identifier.form_fields())
+            }
+            let req = ctx
+                .client
+                .post(endpoint)
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Basic {}", ctx.basic_auth_token),
+                )
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(serde_urlencoded::to_string(body).unwrap())
+                .build()?;
+            ctx.execute(req).await
+        }
+    }
+
+    /// Everything the authentication requests need, borrowed from a [`CrunchyrollBuilder`] when
+    /// logging in or from an [`Executor`] when its session is refreshed.
+    pub(crate) struct AuthContext<'a> {
+        client: &'a Client,
+        hooks: &'a Hooks,
+        clock: &'a dyn Clock,
+        block_mitigation: Option<&'a BlockMitigation>,
+        basic_auth_token: &'a BasicAuthToken,
+        device_identifier: &'a Option<DeviceIdentifier>,
+        #[cfg(feature = "tower")]
+        middleware: Option<&'a tokio::sync::Mutex<crate::internal::tower::Middleware>>,
+    }
+
+    impl AuthContext<'_> {
+        async fn execute<T: DeserializeOwned>(&self, req: reqwest::Request) -> Result<T> {
+            execute_checked(
+                self.client,
+                req,
+                self.hooks,
+                self.clock,
+                self.block_mitigation,
+                #[cfg(feature = "tower")]
+                self.middleware,
+            )
+            .await
+        }
+    }
+
+    impl Executor {
+        fn auth_context(&self) -> AuthContext<'_> {
+            AuthContext {
+                client: &self.client,
+                hooks: &self.hooks,
+                clock: self.clock.as_ref(),
+                block_mitigation: self.block_mitigation.as_ref(),
+                basic_auth_token: &self.details.basic_auth_token,
+                device_identifier: &self.details.device_identifier,
+                #[cfg(feature = "tower")]
+                middleware: self.middleware.as_ref(),
+            }
+        }
+    }
+
+    impl Default for Executor {
+        fn default() -> Self {
+            Self {
+                client: Client::new(),
+                config: RwLock::new(ExecutorConfig {
+                    token_type: "".to_string(),
+                    access_token: "".to_string(),
+                    session_token: SessionToken::RefreshToken("".into()),
+                    session_expire: Default::default(),
+                }),
+                details: ExecutorDetails {
+                    basic_auth_token: BasicAuthToken::default(),
+                    locale: Default::default(),
+                    preferred_audio_locale: None,
+                    bucket: "".to_string(),
+                    signature: "".to_string(),
+                    policy: "".to_string(),
+                    key_pair_id: "".to_string(),
+                    account_id: Ok("".to_string()),
+                    device_identifier: None,
+                },
+                hooks: Hooks::default(),
+                block_mitigation: None,
+                clock: Arc::new(SystemClock),
+                token_store: None,
+                #[cfg(feature = "tower")]
+                middleware: None,
+                #[cfg(feature = "experimental-stabilizations")]
+                fixes: ExecutorFixes {
+                    locale_name_parsing: false,
+                    season_number: false,
+                },
+            }
+        }
+    }
+
+    pub(crate) struct ExecutorRequestBuilder {
+        executor: Arc<Executor>,
+        builder: RequestBuilder,
+    }
+
+    impl ExecutorRequestBuilder {
+        pub(crate) fn new(executor: Arc<Executor>, builder: RequestBuilder) -> Self {
+            Self { executor, builder }
+        }
+
+        pub(crate) fn query<T: Serialize + ?Sized>(mut self, query: &T) -> ExecutorRequestBuilder {
+            self.builder = self.builder.query(query);
+
+            self
+        }
+
+        pub(crate) fn apply_locale_query(self) -> ExecutorRequestBuilder {
+            let locale = self.executor.details.locale.clone();
+            self.query(&[("locale", locale)])
+        }
+
+        pub(crate) fn apply_preferred_audio_locale_query(self) -> ExecutorRequestBuilder {
+            if let Some(locale) = self.executor.details.preferred_audio_locale.clone() {
+                self.query(&[("preferred_audio_language", locale)])
+            } else {
+                self
+            }
+        }
+
+        /// Only request the given fields of the response items. Fields which aren't requested
+        /// have their default value after deserialization. Does nothing if `fields` is empty.
+        pub(crate) fn fields<S: AsRef<str>>(self, fields: &[S]) -> ExecutorRequestBuilder {
+            if fields.is_empty() {
+                return self;
+            }
+            let fields = fields
+                .iter()
+                .map(|f| f.as_ref())
+                .collect::<Vec<&str>>()
+                .join(",");
+            self.query(&[("fields", fields)])
+        }
+
+        /// Adds the signed bucket query parameters which are required by the legacy cms.
+        #[cfg(feature = "legacy-cms")]
+        pub(crate) fn apply_media_query(self) -> ExecutorRequestBuilder {
+            let details = &self.executor.details;
+            let query = [
+                ("Policy", details.policy.clone()),
+                ("Signature", details.signature.clone()),
+                ("Key-Pair-Id", details.key_pair_id.clone()),
+            ];
+            self.query(&query)
+        }
+
+        pub(crate) fn json<T: Serialize + ?Sized>(mut self, json: &T) -> ExecutorRequestBuilder {
+            self.builder = self.builder.json(json);
+
+            self
+        }
+
+        pub(crate) fn header<V: AsRef<str>>(
+            mut self,
+            name: header::HeaderName,
+            value: V,
+        ) -> ExecutorRequestBuilder {
+            self.builder = self.builder.header(name, value.as_ref());
+
+            self
+        }
+
+        pub(crate) async fn request<T: Request + DeserializeOwned>(self) -> Result<T> {
+            self.executor.request(self.builder).await
+        }
+
+        pub(crate) async fn request_raw(self, auth: bool) -> Result<Vec<u8>> {
+            Ok(self.request_response(auth).await?.bytes().await?.to_vec())
+        }
+
+        /// Like [`ExecutorRequestBuilder::request_raw`] but returns the response itself, e.g. to
+        /// read its headers. Only a rate limited response (status `429`) is turned into an error
+        /// (and retried if a [`RateLimitRetry`] is set), every other status must be checked by the
+        /// caller.
+        pub(crate) async fn request_response(mut self, auth: bool) -> Result<reqwest::Response> {
+            if auth {
+                self.builder = self.executor.auth_req(self.builder).await?;
+            }
+
+            let mut req = self.builder.build()?;
+            let mut rate_limit_attempt = 0;
+            loop {
+                let retry_req = req.try_clone();
+                let resp = execute(
+                    &self.executor.client,
+                    req,
+                    &self.executor.hooks,
+                    #[cfg(feature = "tower")]
+                    self.executor.middleware.as_ref(),
+                )
+                .await?;
+                if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+                    return Ok(resp);
+                }
+
+                let url = resp.url().to_string();
+                let error = rate_limited(url.clone(), resp.headers(), self.executor.clock.now());
+                let (Some(rate_limit_retry), Error::RateLimited { retry_after, .. }) =
+                    (&self.executor.hooks.rate_limit_retry, &error)
+                else {
+                    return Err(error);
+                };
+                rate_limit_attempt += 1;
+                let (Some(delay), Some(retry_req)) = (
+                    rate_limit_retry.retry_delay(rate_limit_attempt, *retry_after),
+                    retry_req,
+                ) else {
+                    return Err(error);
+                };
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    attempt = rate_limit_attempt,
+                    url,
+                    delay = ?delay,
+                    "request got rate limited, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                req = retry_req;
+            }
+        }
+    }
+
+    /// Options for [`CrunchyrollBuilder::register`].
+    #[derive(Clone, Debug, Default)]
+    pub struct RegisterOptions {
+        locale: Option<Locale>,
+        marketing_opt_in: bool,
+    }
+
+    impl RegisterOptions {
+        pub fn new() -> RegisterOptions {
+            Self::default()
+        }
+
+        /// Language in which Crunchyroll communicates with the account, e.g. in emails. Default is
+        /// the locale set via [`CrunchyrollBuilder::locale`].
+        pub fn locale(mut self, locale: Locale) -> RegisterOptions {
+            self.locale = Some(locale);
+            self
+        }
+
+        /// If the account should receive marketing emails. Default is `false`.
+        pub fn marketing_opt_in(mut self, marketing_opt_in: bool) -> RegisterOptions {
+            self.marketing_opt_in = marketing_opt_in;
+            self
+        }
+    }
+
+    /// A pending login via an activation code, like Crunchyroll tv apps do it. Obtained via
+    /// [`CrunchyrollBuilder::login_with_device_code`]. Show [`DeviceCodeLogin::user_code`] to the
+    /// user, who has to enter it at [`DeviceCodeLogin::activation_url`], and call
+    /// [`DeviceCodeLogin::wait`] to get the session once the code was entered.
+    pub struct DeviceCodeLogin {
+        builder: CrunchyrollBuilder,
+        device_code: String,
+
+        /// The code the user has to enter.
+        pub user_code: String,
+        /// Url of the page where the user has to enter the code.
+        pub activation_url: String,
+        /// Time until the code can be entered.
+        pub expires_at: DateTime<Utc>,
+        /// How long is waited between two checks if the code was entered.
+        pub interval: std::time::Duration,
+    }
+
+    impl std::fmt::Debug for DeviceCodeLogin {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("DeviceCodeLogin")
+                .field("user_code", &self.user_code)
+                .field("activation_url", &self.activation_url)
+                .field("expires_at", &self.expires_at)
+                .field("interval", &self.interval)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl DeviceCodeLogin {
+        /// Waits until the user entered [`DeviceCodeLogin::user_code`] and returns the logged in
+        /// session. Returns [`Error::Authentication`] if the code expired before it was entered or
+        /// the user denied the login. Every other error of the token request is returned as is.
+        pub async fn wait(mut self) -> Result<Crunchyroll> {
+            loop {
+                let result = Executor::auth_with_device_code(
+                    &self.builder.auth_context(),
+                    &self.device_code,
+                )
+                .await;
+                match result {
+                    Ok(login_response) => {
+                        let session_token = SessionToken::RefreshToken(
+                            login_response.refresh_token.clone().unwrap_or_default(),
+                        );
+                        return self.builder.post_login(login_response, session_token).await;
+                    }
+                    // crunchyroll answers with an error as long as the code wasn't entered
+                    Err(Error::Request {
+                        code: Some(ApiErrorCode::AuthorizationPending),
+                        ..
+                    }) => (),
+                    // like described in rfc 8628, the polling interval must be increased by 5
+                    // seconds if the server asks to slow down
+                    Err(Error::Request {
+                        code: Some(ApiErrorCode::SlowDown),
+                        ..
+                    }) => self.interval += std::time::Duration::from_secs(5),
+                    Err(Error::Request {
+                        code: Some(ApiErrorCode::ExpiredToken),
+                        ..
+                    }) => {
+                        return Err(Error::Authentication {
+                            message: format!(
+                                "device code {} expired before it was entered",
+                                self.user_code
+                            ),
+                        })
+                    }
+                    Err(Error::Request {
+                        code: Some(ApiErrorCode::AccessDenied),
+                        ..
+                    }) => {
+                        return Err(Error::Authentication {
+                            message: format!("login via device code {} was denied", self.user_code),
+                        })
+                    }
+                    Err(e) => return Err(e),
+                }
+
+                if self.builder.clock.now() + self.interval >= self.expires_at {
+                    return Err(Error::Authentication {
+                        message: format!(
+                            "device code {} expired before it was entered",
+                            self.user_code
+                        ),
+                    });
+                }
+                tokio::time::sleep(self.interval).await
+            }
+        }
+    }
+
+    /// A builder to construct a new [`Crunchyroll`] instance. To create it, call
+    /// [`Crunchyroll::builder`].
+    pub struct CrunchyrollBuilder {
+        client: Client,
+        basic_auth_token: BasicAuthToken,
+        auto_basic_auth_token: bool,
+        locale: Locale,
+        preferred_audio_locale: Option<Locale>,
+        device_identifier: Option<DeviceIdentifier>,
+
+        hooks: Hooks,
+        block_mitigation: Option<BlockMitigation>,
+        clock: Arc<dyn Clock>,
+        token_store: Option<Arc<dyn TokenStore>>,
+        proxy: Option<ProxyConfig>,
+        #[cfg(feature = "tower")]
+        middleware: Option<tokio::sync::Mutex<crate::internal::tower::Middleware>>,
+        #[cfg(feature = "experimental-stabilizations")]
+        fixes: ExecutorFixes,
+    }
+
+    impl Default for CrunchyrollBuilder {
+        fn default() -> Self {
+            Self {
+                client: CrunchyrollBuilder::predefined_client_builder()
+                    .build()
+                    .unwrap(),
+                basic_auth_token: BasicAuthToken::default(),
+                auto_basic_auth_token: false,
+                locale: Locale::en_US,
+                preferred_audio_locale: None,
+                device_identifier: None,
+                hooks: Hooks::default(),
+                block_mitigation: None,
+                clock: Arc::new(SystemClock),
+                token_store: None,
+                proxy: None,
+                #[cfg(feature = "tower")]
+                middleware: None,
+                #[cfg(feature = "experimental-stabilizations")]
+                fixes: ExecutorFixes {
+                    locale_name_parsing: false,
+                    season_number: false,
+                },
+            }
+        }
+    }
+
+    impl CrunchyrollBuilder {
+        /// Return a [`ClientBuilder`] which has all required configurations necessary to send
+        /// successful requests to Crunchyroll, applied (most of the time; sometimes Crunchyroll has
+        /// fluctuations that requests doesn't work for a specific amount of time and after that
+        /// amount everything goes back to normal and works as it should). You can use this builder
+        /// to configure the behavior of the download client. Use [`CrunchyrollBuilder::client`] or
+        /// to set your built client.
+        pub fn predefined_client_builder() -> ClientBuilder {
+            let tls_config = rustls::ClientConfig::builder_with_provider(
+                rustls::crypto::CryptoProvider {
+                    cipher_suites: rustls::crypto::ring::DEFAULT_CIPHER_SUITES.to_vec(),
+                    kx_groups: vec![rustls::crypto::ring::kx_group::X25519],
+                    ..rustls::crypto::ring::default_provider()
+                }
+                .into(),
+            )
+            .with_protocol_versions(&[&rustls::version::TLS12, &rustls::version::TLS13])
+            .unwrap()
+            .with_root_certificates(rustls::RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+            })
+            .with_no_client_auth();
+
+            Client::builder()
+                .https_only(true)
+                .cookie_store(true)
+                .user_agent("Crunchyroll/1.8.0 Nintendo Switch/12.3.12.0 UE4/4.27")
+                .use_preconfigured_tls(tls_config)
+        }
+
+        /// Set a custom client that will be used in all api requests.
+        /// It is recommended to use the client builder from
+        /// [`CrunchyrollBuilder::predefined_client_builder`] as base as it has some configurations
+        /// which may be needed to make successful requests to Crunchyroll.
+        pub fn client(mut self, client: Client) -> CrunchyrollBuilder {
+            self.client = client;
+            self
+        }
+
+        /// Set the basic auth token which is used to get a session token. Use this if Crunchyroll
+        /// rotated the token and [`BasicAuthToken::NINTENDO_SWITCH`] (the default) stopped working.
+        /// Has no effect on [`CrunchyrollBuilder::login_with_etp_rt`].
+        pub fn basic_auth_token(mut self, basic_auth_token: BasicAuthToken) -> CrunchyrollBuilder {
+            self.basic_auth_token = basic_auth_token;
+            self
+        }
+
+        /// Fetch the current basic auth token of the Crunchyroll web app when logging in instead of
+        /// using a hardcoded one. This makes the login survive token rotations but adds a
+        /// dependency on the structure of the crunchyroll.com index page, which might change at
+        /// any time too. Overwrites the token set via [`CrunchyrollBuilder::basic_auth_token`].
+        pub fn auto_basic_auth_token(mut self, enable: bool) -> CrunchyrollBuilder {
+            self.auto_basic_auth_token = enable;
+            self
+        }
+
+        /// Set in which languages all results which have human readable text in it should be
+        /// returned.
+        pub fn locale(mut self, locale: Locale) -> CrunchyrollBuilder {
+            self.locale = locale;
+            self
+        }
+
+        /// Set the audio language of media (like episodes) which should be returned when querying
+        /// by any other method than the direct media id. For example, if the preferred audio locale
+        /// were set to [`Locale::en_US`], the seasons queried with [`crate::Series::seasons`] would
+        /// likely have [`Locale::en_US`] as their audio locale. This might not always work on all
+        /// endpoints as Crunchyroll does Crunchyroll things (e.g. it seems to have no effect when
+        /// changing the locale and using [`Crunchyroll::query`]).
+        pub fn preferred_audio_locale(
+            mut self,
+            preferred_audio_locale: Locale,
+        ) -> CrunchyrollBuilder {
+            self.preferred_audio_locale = Some(preferred_audio_locale);
+            self
+        }
+
+        /// Set an identifier for the session which will be opened. `device_id` is usually a random
+        /// UUID, `device_type` a description of the device which issues the session, e.g. `Chrome
+        /// on Windows` or `iPhone 15`.
+        /// Gets only used if the login method is [`CrunchyrollBuilder::login_with_credentials`].
+        pub fn device_identifier(
+            mut self,
+            device_identifier: DeviceIdentifier,
+        ) -> CrunchyrollBuilder {
+            self.device_identifier = Some(device_identifier);
+            self
+        }
+
+        /// Set a callback which is called before every request, including requests which are made
+        /// internally like session refreshes or stream segment downloads. It receives the request
+        /// method and url.
+        pub fn on_request<F>(mut self, callback: F) -> CrunchyrollBuilder
+        where
+            F: Fn(&Method, &Url) + Send + Sync + 'static,
+        {
+            self.hooks.on_request = Some(Arc::new(callback));
+            self
+        }
+
+        /// Set a callback which is called after every request, including requests which are made
+        /// internally like session refreshes or stream segment downloads. It receives the request
+        /// method, url, the response status and how long the request took. The status is [`None`]
+        /// if the request failed before any response was received. Errors carry the request id of
+        /// the response, for successful requests it's part of the `tracing` event of the request
+        /// (if the `tracing` feature is enabled).
+        pub fn on_response<F>(mut self, callback: F) -> CrunchyrollBuilder
+        where
+            F: Fn(&Method, &Url, Option<StatusCode>, std::time::Duration) + Send + Sync + 'static,
+        {
+            self.hooks.on_response = Some(Arc::new(callback));
+            self
+        }
+
+        /// Set a callback which is called every time the refresh token or etp-rt cookie of the
+        /// session is rotated while refreshing the session. It receives the new token, which
+        /// replaces the previous one: store it if you want to log in with it later. Use
+        /// [`CrunchyrollBuilder::token_store`] if the token should be persisted automatically.
+        pub fn on_token_rotation<F>(mut self, callback: F) -> CrunchyrollBuilder
+        where
+            F: Fn(&SessionToken) + Send + Sync + 'static,
+        {
+            self.hooks.on_token_rotation = Some(Arc::new(callback));
+            self
+        }
+
+        /// Set a callback which is called if the [`CrunchyrollBuilder::token_store`] fails to store
+        /// a token which was rotated while refreshing the session. The request which triggered the
+        /// refresh still succeeds, so this is the only place where such an error shows up.
+        pub fn on_token_store_error<F>(mut self, callback: F) -> CrunchyrollBuilder
+        where
+            F: Fn(&Error) + Send + Sync + 'static,
+        {
+            self.hooks.on_token_store_error = Some(Arc::new(callback));
+            self
+        }
+
+        /// Opt out of tracking-style behavior which is mimicked from the official clients. If
+        /// enabled, the `ETP-Anonymous-ID` header (a random id which is sent when logging in
+        /// anonymously) is omitted and every api request is sent with the `DNT: 1` and `Sec-GPC: 1`
+        /// headers (requests to media CDNs don't get them). Headers and parameters which are required for the api to work, like the
+        /// [`CrunchyrollBuilder::device_identifier`] or cookies, are still sent. Default is `false`.
+        pub fn do_not_track(mut self, do_not_track: bool) -> CrunchyrollBuilder {
+            self.hooks.do_not_track = do_not_track;
+            self
+        }
+
+        /// Send the `Accept-Language` header with the given locale on every request. Official
+        /// clients send it as hint which language / region the user prefers. This doesn't change
+        /// the region of the session (see [`Crunchyroll::region`]) or the language of api results,
+        /// which is set via [`CrunchyrollBuilder::locale`].
+        pub fn accept_language(mut self, locale: Locale) -> CrunchyrollBuilder {
+            self.hooks.accept_language = Some(locale.to_string());
+            self
+        }
+
+        /// Set the [`Clock`] which is used to check if the session has expired (see [`Clock`] for
+        /// what else it's used for). Default is [`SystemClock`].
+        pub fn clock(mut self, clock: impl Clock + 'static) -> CrunchyrollBuilder {
+            self.clock = Arc::new(clock);
+            self
+        }
+
+        /// Set a [`TokenStore`] which persists the session token after login and whenever it gets
+        /// rotated. Use [`CrunchyrollBuilder::login_with_token_store`] to log in with the stored
+        /// token.
+        pub fn token_store(mut self, token_store: impl TokenStore + 'static) -> CrunchyrollBuilder {
+            self.token_store = Some(Arc::new(token_store));
+            self
+        }
+
+        /// Set a rate limiter which delays api requests if they're sent too fast. Share the same
+        /// [`RateLimiter`] between multiple instances to limit them together.
+        pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> CrunchyrollBuilder {
+            self.hooks.rate_limiter = Some(rate_limiter);
+            self
+        }
+
+        /// Retry requests which got rate limited by Crunchyroll instead of returning
+        /// [`Error::RateLimited`]. The request is retried after the time the `Retry-After` header
+        /// of the response specifies. Combine it with [`CrunchyrollBuilder::rate_limiter`] to avoid
+        /// getting rate limited in the first place.
+        pub fn rate_limit_retry(mut self, rate_limit_retry: RateLimitRetry) -> CrunchyrollBuilder {
+            self.hooks.rate_limit_retry = Some(rate_limit_retry);
+            self
+        }
+
+        /// Set a proxy which is used for the requests specified by [`ProxyConfig::scope`]. A new
+        /// client based on [`CrunchyrollBuilder::predefined_client_builder`] is created with the
+        /// proxy when logging in, which replaces the client set via [`CrunchyrollBuilder::client`].
+        /// If you need a custom client, configure the proxy on it yourself.
+        pub fn proxy(mut self, proxy: ProxyConfig) -> CrunchyrollBuilder {
+            self.proxy = Some(proxy);
+            self
+        }
+
+        /// Overwrite the base urls the requests are sent to. By default, the official Crunchyroll
+        /// hosts are used.
+        pub fn endpoints(mut self, endpoints: Endpoints) -> CrunchyrollBuilder {
+            self.hooks.endpoints = Some(endpoints);
+            self
+        }
+
+        /// Tolerate unexpected `null` values in api responses instead of failing the whole request.
+        /// Fields which are `null` but can't be are set to their default value and every
+        /// occurrence is recorded as [`crate::error::ParseWarning`] (see
+        /// [`Crunchyroll::parse_warnings`]). Responses which still can't be parsed fail like
+        /// before. Default is `false`.
+        pub fn lenient_parsing(mut self, lenient_parsing: bool) -> CrunchyrollBuilder {
+            self.hooks.lenient_parsing = lenient_parsing;
+            self
+        }
+
+        /// Set a [`crate::media::ThroughputObserver`] which is notified about the download speed of
+        /// stream segments, e.g. to show it in a progress bar.
+        pub fn throughput_observer(
+            mut self,
+            observer: impl crate::media::ThroughputObserver + 'static,
+        ) -> CrunchyrollBuilder {
+            self.hooks.throughput_observer = Some(Arc::new(observer));
+            self
+        }
+
+        /// Answer every request with the canned responses of the given
+        /// [`crate::testing::MockTransport`] instead of sending it over the network. Useful to test
+        /// code which takes a [`Crunchyroll`] instance.
+        #[cfg(feature = "testing")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+        pub fn with_transport(
+            mut self,
+            transport: crate::testing::MockTransport,
+        ) -> CrunchyrollBuilder {
+            self.hooks.transport = Some(transport);
+            self
+        }
+
+        /// Record the (anonymized) json responses of all requests with the given
+        /// [`crate::testing::FixtureRecorder`], to replay them later via
+        /// [`crate::testing::MockTransport::with_fixtures`].
+        #[cfg(feature = "testing")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+        pub fn record_fixtures(
+            mut self,
+            recorder: crate::testing::FixtureRecorder,
+        ) -> CrunchyrollBuilder {
+            self.hooks.recorder = Some(recorder);
+            self
+        }
+
+        /// Set how to react if a request gets blocked by the Cloudflare bot protection
+        /// ([`Error::Block`]). By default, no mitigation is applied and the error is returned
+        /// directly.
+        pub fn block_mitigation(mut self, block_mitigation: BlockMitigation) -> CrunchyrollBuilder {
+            self.block_mitigation = Some(block_mitigation);
+            self
+        }
+
+        /// Adds a [tower](https://docs.rs/tower/latest/tower/) middleware which is called on every
+        /// request.
+        #[cfg(feature = "tower")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+        pub fn middleware<F, S>(mut self, service: S) -> CrunchyrollBuilder
+        where
+            F: std::future::Future<Output = Result<reqwest::Response, Error>> + Send + 'static,
+            S: tower_service::Service<
+                    reqwest::Request,
+                    Response = reqwest::Response,
+                    Error = Error,
+                    Future = F,
+                > + Send
+                + 'static,
+        {
+            self.middleware = Some(tokio::sync::Mutex::new(
+                crate::internal::tower::Middleware::new(service),
+            ));
+            self
+        }
+
+        /// Set season and episode locales by parsing the season name and check if it contains
+        /// any language name.
+        /// Under special circumstances, this can slow down some methods as additional request must
+        /// be made. Currently, this applies to [`crate::Series`]. Whenever a request
+        /// is made which returns [`crate::Series`], internally [`crate::Series::seasons`] is called
+        /// for every series.
+        /// See <https://github.com/crunchy-labs/crunchyroll-rs/issues/3> for more information.
+        #[cfg(feature = "experimental-stabilizations")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "experimental-stabilizations")))]
+        pub fn stabilization_locales(mut self, enable: bool) -> CrunchyrollBuilder {
+            self.fixes.locale_name_parsing = enable;
+            self
+        }
+
+        /// Set the season number of seasons by parsing a string which is delivered via the api too
+        /// and looks to be more reliable than the actual integer season number Crunchyroll provides.
+        #[cfg(feature = "experimental-stabilizations")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "experimental-stabilizations")))]
+        pub fn stabilization_season_number(mut self, enable: bool) -> CrunchyrollBuilder {
+            self.fixes.season_number = enable;
+            self
+        }
+
+        /// Login without an account. This is just like if you would visit crunchyroll.com without
+        /// an account. Some functions won't work if logged in with this method.
+        pub async fn login_anonymously(mut self) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response = Executor::auth_anonymously(&self.auth_context()).await?;
+            let session_token = SessionToken::Anonymous;
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Logs in with credentials (email and password) and returns a new `Crunchyroll` instance.
+        pub async fn login_with_credentials<S: AsRef<str>>(
+            mut self,
+            email: S,
+            password: S,
+        ) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response = Executor::auth_with_credentials(
+                &self.auth_context(),
+                email.as_ref(),
+                password.as_ref(),
+            )
+            .await?;
+            let session_token =
+                SessionToken::RefreshToken(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Creates a new account with the given credentials and returns a `Crunchyroll` instance
+        /// which is logged in with it. If the email is already used by another account, the
+        /// returned [`Error::Request`] has the code
+        /// [`crate::error::ApiErrorCode::EmailAlreadyExists`], if the password doesn't meet the
+        /// requirements of Crunchyroll, it has the code
+        /// [`crate::error::ApiErrorCode::WeakPassword`].
+        pub async fn register<S: AsRef<str>>(
+            mut self,
+            email: S,
+            password: S,
+            options: RegisterOptions,
+        ) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let anonymous_response = Executor::auth_anonymously(&self.auth_context()).await?;
+            Executor::signup(
+                &self.auth_context(),
+                &anonymous_response,
+                email.as_ref(),
+                password.as_ref(),
+                options.locale.as_ref().unwrap_or(&self.locale),
+                options.marketing_opt_in,
+            )
+            .await?;
+
+            let login_response = Executor::auth_with_credentials(
+                &self.auth_context(),
+                email.as_ref(),
+                password.as_ref(),
+            )
+            .await?;
+            let session_token =
+                SessionToken::RefreshToken(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Logs in with the token of the [`TokenStore`] set via [`CrunchyrollBuilder::token_store`].
+        /// Returns [`Error::Authentication`] if no store is set or it doesn't contain a token.
+        pub async fn login_with_token_store(self) -> Result<Crunchyroll> {
+            let Some(token_store) = &self.token_store else {
+                return Err(Error::Authentication {
+                    message: "no token store is set".to_string(),
+                });
+            };
+            match token_store.get().await? {
+                Some(SessionToken::RefreshToken(refresh_token)) => {
+                    self.login_with_refresh_token(refresh_token).await
+                }
+                Some(SessionToken::EtpRt(etp_rt)) => self.login_with_etp_rt(etp_rt).await,
+                Some(SessionToken::Anonymous) => self.login_anonymously().await,
+                None => Err(Error::Authentication {
+                    message: "the token store doesn't contain a token".to_string(),
+                }),
+            }
+        }
+
+        /// Logs in with a refresh token. This token is obtained when logging in with
+        /// [`CrunchyrollBuilder::login_with_credentials`].
+        /// Note: Even though the tokens used in [`CrunchyrollBuilder::login_with_refresh_token`] and
+        /// [`CrunchyrollBuilder::login_with_etp_rt`] are having the same syntax, Crunchyroll
+        /// internal they're different. I had issues when I tried to log in with the refresh token
+        /// on [`CrunchyrollBuilder::login_with_etp_rt`] and vice versa.
+        pub async fn login_with_refresh_token<S: AsRef<str>>(
+            mut self,
+            refresh_token: S,
+        ) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response =
+                Executor::auth_with_refresh_token(&self.auth_context(), refresh_token.as_ref())
+                    .await?;
+            let session_token =
+                SessionToken::RefreshToken(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Just like [`CrunchyrollBuilder::login_with_refresh_token`] but with the addition that
+        /// the id of a [`crate::profile::Profile`] is given too. The resulting [`Crunchyroll`]
+        /// session will settings that are specific to the given [`crate::profile::Profile`] id.
+        ///
+        /// *Note*: When using this login method, some endpoints aren't available / will return an
+        /// error. Idk why, but these endpoints can only be used if the authentication is anything
+        /// other than [`CrunchyrollBuilder::login_with_refresh_token_profile_id`].
+        pub async fn login_with_refresh_token_profile_id<S: AsRef<str>>(
+            mut self,
+            refresh_token: S,
+            profile_id: S,
+        ) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response = Executor::auth_with_refresh_token_profile_id(
+                &self.auth_context(),
+                refresh_token.as_ref(),
+                profile_id.as_ref(),
+            )
+            .await?;
+            let session_token =
+                SessionToken::RefreshToken(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Logs in with an etp rt cookie and returns a new `Crunchyroll` instance.
+        /// This cookie can be extracted if you copy the `etp_rt` cookie from your browser.
+        /// Note: Even though the tokens used in [`CrunchyrollBuilder::login_with_etp_rt`] and
+        /// [`CrunchyrollBuilder::login_with_refresh_token`] are having the same syntax, Crunchyroll
+        /// internal they're different. I had issues when I tried to log in with the `etp_rt`
+        /// cookie on [`CrunchyrollBuilder::login_with_refresh_token`] and vice versa.
+        pub async fn login_with_etp_rt<S: AsRef<str>>(mut self, etp_rt: S) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response =
+                Executor::auth_with_etp_rt(&self.auth_context(), etp_rt.as_ref()).await?;
+            let session_token = SessionToken::EtpRt(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Logs in with an account which was created via Google or Apple and therefore has no
+        /// password. `id_token` is the OpenID Connect id token which the provider issued for the
+        /// account, obtaining it (e.g. via the sign-in SDK of the provider) is up to the caller. If
+        /// the provider account isn't linked to a Crunchyroll account, the returned
+        /// [`Error::Request`] has the code
+        /// [`crate::error::ApiErrorCode::ExternalAccountNotLinked`].
+        ///
+        /// The token exchange grant, the `provider` parameter and the error code mimic what the
+        /// official apps seem to send but weren't verified against the api, so this login may
+        /// fail although the id token is valid.
+        pub async fn login_with_external_token<S: AsRef<str>>(
+            mut self,
+            provider: ExternalLoginProvider,
+            id_token: S,
+        ) -> Result<Crunchyroll> {
+            self.pre_login().await?;
+
+            let login_response = Executor::auth_with_external_token(
+                &self.auth_context(),
+                &provider,
+                id_token.as_ref(),
+            )
+            .await?;
+            let session_token =
+                SessionToken::RefreshToken(login_response.refresh_token.clone().unwrap());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        /// Starts a login via an activation code, like Crunchyroll tv apps do it. The user enters
+        /// the code of the returned [`DeviceCodeLogin`] on the Crunchyroll website, so no
+        /// credentials have to be handled. The basic auth token (see
+        /// [`CrunchyrollBuilder::basic_auth_token`]) must belong to an app which supports this
+        /// flow, the default one does.
+        ///
+        /// ```no_run
+        /// use crunchyroll_rs::Crunchyroll;
+        ///
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), crunchyroll_rs::error::Error> {
+        /// let login = Crunchyroll::builder().login_with_device_code().await?;
+        /// println!("Enter {} at {}", login.user_code, login.activation_url);
+        /// let crunchy = login.wait().await?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub async fn login_with_device_code(mut self) -> Result<DeviceCodeLogin> {
+            self.pre_login().await?;
+
+            let device_code = Executor::request_device_code(&self.auth_context()).await?;
+
+            Ok(DeviceCodeLogin {
+                device_code: device_code.device_code,
+                user_code: device_code.user_code,
+                activation_url: "https://www.crunchyroll.com/activate".to_string(),
+                expires_at: self
+                    .clock
+                    .now()
+                    .add(Duration::try_seconds(device_code.expires_in).unwrap_or_default()),
+                interval: std::time::Duration::from_secs(device_code.interval.unwrap_or(5)),
+                builder: self,
+            })
+        }
+
+        /// Builds a session from tokens which were obtained elsewhere, e.g. by a browser automation
+        /// step or another client, without authenticating again. `access_token` must be the jwt
+        /// which is sent as bearer token, `expires_at` the time it expires. After that, the session
+        /// is refreshed with `refresh_token` like every other session. If the tokens were issued
+        /// for a specific device, set it via [`CrunchyrollBuilder::device_identifier`], it's then
+        /// sent along when refreshing the session.
+        pub async fn login_with_tokens<S: AsRef<str>>(
+            mut self,
+            access_token: S,
+            refresh_token: S,
+            expires_at: DateTime<Utc>,
+        ) -> Result<Crunchyroll> {
+            let claims = jwt_claims(access_token.as_ref())?;
+            let claim = |name: &str| claims.get(name).and_then(|c| c.as_str()).map(String::from);
+
+            self.pre_login().await?;
+
+            let login_response = AuthResponse {
+                access_token: access_token.as_ref().to_string(),
+                refresh_token: Some(refresh_token.as_ref().to_string()),
+                expires_in: (expires_at - self.clock.now()).num_seconds() as i32,
+                token_type: "Bearer".to_string(),
+                scope: String::new(),
+                country: claim("country").unwrap_or_default(),
+                account_id: claim("sub"),
+                profile_id: claim("profile_id"),
+            };
+            let session_token = SessionToken::RefreshToken(refresh_token.as_ref().to_string());
+
+            self.post_login(login_response, session_token).await
+        }
+
+        fn auth_context(&self) -> AuthContext<'_> {
+            AuthContext {
+                client: &self.client,
+                hooks: &self.hooks,
+                clock: self.clock.as_ref(),
+                block_mitigation: self.block_mitigation.as_ref(),
+                basic_auth_token: &self.basic_auth_token,
+                device_identifier: &self.device_identifier,
+                #[cfg(feature = "tower")]
+                middleware: self.middleware.as_ref(),
+            }
+        }
+
+        async fn pre_login(&mut self) -> Result<()> {
+            if let Some(proxy) = &self.proxy {
+                self.client = CrunchyrollBuilder::predefined_client_builder()
+                    .proxy(proxy.to_proxy()?)
+                    .build()?;
+            }
+
+            // Request the index page to set cookies which are required to bypass the cloudflare bot
+            // check
+            let index = execute(
+                &self.client,
+                self.client.get("https://www.crunchyroll.com").build()?,
+                &self.hooks,
+                #[cfg(feature = "tower")]
+                None,
+            )
+            .await?;
+            if self.auto_basic_auth_token {
+                self.basic_auth_token = BasicAuthToken::from_app_config(&index.text().await?)?;
+            }
+            Ok(())
+        }
+
+        async fn post_login(
+            self,
+            login_response: AuthResponse,
+            session_token: SessionToken,
+        ) -> Result<Crunchyroll> {
+            let index_endpoint = "https://www.crunchyroll.com/index/v2";
+            #[derive(Deserialize, smart_default::SmartDefault)]
+            #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+            #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+            #[allow(dead_code)]
+            struct IndexRespCms {
+                bucket: String,
+                #[default(DateTime::<Utc>::from(std::time::SystemTime::UNIX_EPOCH))]
+                expires: DateTime<Utc>,
+                key_pair_id: String,
+                policy: String,
+                signature: String,
+            }
+            #[derive(Default, Deserialize, Request)]
+            #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+            #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+            #[allow(dead_code)]
+            struct IndexResp {
+                cms_web: IndexRespCms,
+                default_marketing_opt_in: bool,
+                service_available: bool,
+
+                #[cfg(feature = "__test_strict")]
+                cms: crate::StrictValue,
+                #[cfg(feature = "__test_strict")]
+                cms_beta: crate::StrictValue,
+            }
+
+            let index_req = self.client.get(index_endpoint).header(
+                header::AUTHORIZATION,
+                format!(
+                    "{} {}",
+                    login_response.token_type, login_response.access_token
+                ),
+            );
+            let index: IndexResp = request(
+                &self.client,
+                &self.hooks,
+                self.clock.as_ref(),
+                self.block_mitigation.as_ref(),
+                index_req,
+                #[cfg(feature = "tower")]
+                self.middleware.as_ref(),
+            )
+            .await?;
+
+            if let Some(token_store) = &self.token_store {
+                token_store.set(&session_token).await?
+            }
+
+            let crunchy = Crunchyroll {
+                executor: Arc::new(Executor {
+                    client: self.client,
+
+                    config: RwLock::new(ExecutorConfig {
+                        token_type: login_response.token_type,
+                        access_token: login_response.access_token,
+                        session_token,
+                        session_expire: self
+                            .clock
+                            .now()
+                            .add(Duration::try_seconds(login_response.expires_in as i64).unwrap()),
+                    }),
+                    details: ExecutorDetails {
+                        basic_auth_token: self.basic_auth_token,
+                        locale: self.locale,
+                        preferred_audio_locale: self.preferred_audio_locale,
+
+                        // '/' is trimmed so that urls which require it must be in .../{bucket}/... like format.
+                        // this just looks cleaner
+                        bucket: index
+                            .cms_web
+                            .bucket
+                            .strip_prefix('/')
+                            .unwrap_or(index.cms_web.bucket.as_str())
+                            .to_string(),
+
+                        signature: index.cms_web.signature,
+                        policy: index.cms_web.policy,
+                        key_pair_id: index.cms_web.key_pair_id,
+                        account_id: login_response.account_id.ok_or_else(|| {
+                            Error::Authentication {
+                                message: "Login with a user account to use this function"
+                                    .to_string(),
+                            }
+                        }),
+                        device_identifier: self.device_identifier,
+                    },
+                    hooks: self.hooks,
+                    block_mitigation: self.block_mitigation,
+                    clock: self.clock,
+                    token_store: self.token_store,
+                    #[cfg(feature = "tower")]
+                    middleware: self.middleware,
+                    #[cfg(feature = "experimental-stabilizations")]
+                    fixes: self.fixes,
+                }),
+            };
+
+            Ok(crunchy)
+        }
+    }
+
+    /// Returns the claims of a jwt access token.
+    fn jwt_claims(token: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let key = jsonwebtoken::DecodingKey::from_rsa_components("", "").unwrap();
+        let mut validation = jsonwebtoken::Validation::default();
+        // the jwt might be expired when calling this function. but there is no really need to
+        // refresh it if this case happens. sure, it might be that something has changed when
+        // re-requesting the token but the possibility of this is tiny
+        validation.validate_exp = false;
+        // we just want the jwt claims, no need to check the signature. no safety critical
+        // processes rely on the jwt internally
+        validation.insecure_disable_signature_validation();
+        jsonwebtoken::decode::<serde_json::Map<String, serde_json::Value>>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| Error::Authentication {
+                message: format!("invalid access token: {e}"),
+            })
+    }
+
+    /// Make a request from the provided builder.
+    async fn request<T: Request + DeserializeOwned>(
+        client: &Client,
+        hooks: &Hooks,
+        clock: &dyn Clock,
+        block_mitigation: Option<&BlockMitigation>,
+        req: RequestBuilder,
+        #[cfg(feature = "tower")] middleware: Option<
+            &tokio::sync::Mutex<crate::internal::tower::Middleware>,
+        >,
+    ) -> Result<T> {
+        let built_req = req.build()?;
+        #[cfg(feature = "__test_strict")]
+        let url = built_req.url().to_string();
+
+        #[cfg(not(feature = "__test_strict"))]
+        {
+            execute_checked(
+                client,
+                built_req,
+                hooks,
+                clock,
+                block_mitigation,
+                #[cfg(feature = "tower")]
+                middleware,
+            )
+            .await
+        }
+        #[cfg(feature = "__test_strict")]
+        {
+            let result: serde_json::Map<String, serde_json::Value> = execute_checked(
+                client,
+                built_req,
+                hooks,
+                clock,
+                block_mitigation,
+                #[cfg(feature = "tower")]
+                middleware,
+            )
+            .await?;
+
+            let cleaned = clean_request(result);
+            let value = serde_json::Value::deserialize(serde::de::value::MapDeserializer::new(
+                cleaned.into_iter(),
+            ))?;
+            serde_json::from_value(value.clone()).map_err(|e| Error::Decode {
+                message: format!("{} at {}:{}", e, e.line(), e.column()),
+                content: value.to_string().into_bytes(),
+                url,
+                request_id: None,
+            })
+        }
+    }
+
+    /// Send a request, either via the middleware if set or via the client, and call the request
+    /// hooks.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(method = %req.method(), url = %req.url())
+        )
+    )]
+    async fn execute(
+        client: &Client,
+        mut req: reqwest::Request,
+        hooks: &Hooks,
+        #[cfg(feature = "tower")] middleware: Option<
+            &tokio::sync::Mutex<crate::internal::tower::Middleware>,
+        >,
+    ) -> Result<reqwest::Response> {
+        // checked before the endpoints are rewritten, as they might point to other hosts
+        let is_api = is_api_url(req.url());
+        if let Some(rate_limiter) = &hooks.rate_limiter {
+            if is_api {
+                rate_limiter.acquire().await
+            }
+        }
+        if let Some(endpoints) = &hooks.endpoints {
+            if let Some(url) = endpoints.rewrite(req.url())? {
+                *req.url_mut() = url
+            }
+        }
+        if let Some(accept_language) = &hooks.accept_language {
+            if let Ok(value) = header::HeaderValue::from_str(accept_language) {
+                req.headers_mut().insert(header::ACCEPT_LANGUAGE, value);
+            }
+        }
+        if hooks.do_not_track && is_api {
+            let headers = req.headers_mut();
+            headers.insert(header::DNT, header::HeaderValue::from_static("1"));
+            headers.insert("Sec-GPC", header::HeaderValue::from_static("1"));
+        }
+
+        let method = req.method().clone();
+        let url = req.url().clone();
+        if let Some(on_request) = &hooks.on_request {
+            on_request(&method, &url)
+        }
+
+        let start = std::time::Instant::now();
+        #[cfg(feature = "testing")]
+        let result = if let Some(transport) = &hooks.transport {
+            transport.respond(req)
+        } else {
+            send(
+                client,
+                req,
+                #[cfg(feature = "tower")]
+                middleware,
+            )
+            .await
+        };
+        #[cfg(feature = "testing")]
+        let result = match (result, &hooks.recorder) {
+            (Ok(resp), Some(recorder)) => recorder.record(&method, &url, resp).await,
+            (result, _) => result,
+        };
+        #[cfg(not(feature = "testing"))]
+        let result = send(
+            client,
+            req,
+            #[cfg(feature = "tower")]
+            middleware,
+        )
+        .await;
+
+        let status = match &result {
+            Ok(resp) => Some(resp.status()),
+            Err(Error::Request { status, .. }) => *status,
+            Err(_) => None,
+        };
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(resp) => tracing::debug!(
+                status = status.map(|s| s.as_u16()),
+                request_id = request_id(resp.headers()),
+                duration = ?start.elapsed(),
+                "request finished"
+            ),
+            Err(e) => tracing::warn!(
+                status = status.map(|s| s.as_u16()),
+                duration = ?start.elapsed(),
+                error = %e,
+                "request failed"
+            ),
+        }
+        if let Some(on_response) = &hooks.on_response {
+            on_response(&method, &url, status, start.elapsed())
+        }
+
+        result
+    }
+
+    /// Send a request via the middleware if set, otherwise via the client.
+    async fn send(
+        client: &Client,
+        req: reqwest::Request,
+        #[cfg(feature = "tower")] middleware: Option<
+            &tokio::sync::Mutex<crate::internal::tower::Middleware>,
+        >,
+    ) -> Result<reqwest::Response> {
+        #[cfg(feature = "tower")]
+        if let Some(middleware) = middleware {
+            use std::ops::DerefMut;
+            return middleware.lock().await.deref_mut().call(req).await;
+        }
+        client.execute(req).await.map_err(Error::from)
+    }
+
+    /// Like [`execute`] but also checks the response via [`check_request`]. If the request got
+    /// blocked and a [`BlockMitigation`] is set, the mitigation is applied and the request retried.
+    /// If it got rate limited and a [`RateLimitRetry`] is set, the request is retried after the
+    /// requested delay.
+    async fn execute_checked<T: DeserializeOwned>(
+        client: &Client,
+        mut req: reqwest::Request,
+        hooks: &Hooks,
+        clock: &dyn Clock,
+        block_mitigation: Option<&BlockMitigation>,
+        #[cfg(feature = "tower")] middleware: Option<
+            &tokio::sync::Mutex<crate::internal::tower::Middleware>,
+        >,
+    ) -> Result<T> {
+        let url = req.url().to_string();
+        let mut attempt = 0;
+        let mut rate_limit_attempt = 0;
+        loop {
+            if let Some(user_agent) = block_mitigation.and_then(|bm| bm.current_user_agent()) {
+                let user_agent =
+                    header::HeaderValue::from_str(&user_agent).map_err(|e| Error::Input {
+                        message: format!("Invalid user agent '{user_agent}': {e}"),
+                    })?;
+                req.headers_mut().insert(header::USER_AGENT, user_agent);
+            }
+            let retry_req = req.try_clone();
+
+            let resp = execute(
+                client,
+                req,
+                hooks,
+                #[cfg(feature = "tower")]
+                middleware,
+            )
+            .await?;
+            let result = check_request(
+                url.clone(),
+                resp,
+                hooks.lenient_parsing.then_some(&hooks.parse_warnings),
+                clock.now(),
+            )
+            .await;
+
+            let rate_limit_delay = match (&hooks.rate_limit_retry, &result) {
+                (Some(rate_limit_retry), Err(Error::RateLimited { retry_after, .. })) => {
+                    rate_limit_attempt += 1;
+                    Some(rate_limit_retry.retry_delay(rate_limit_attempt, *retry_after))
+                }
+                _ => None,
+            };
+            if let Some(delay) = rate_limit_delay {
+                let (Some(delay), Some(retry_req)) = (delay, retry_req) else {
+                    return result;
+                };
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    attempt = rate_limit_attempt,
+                    url,
+                    delay = ?delay,
+                    "request got rate limited, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                req = retry_req;
+                continue;
+            }
+
+            let (Some(block_mitigation), Some(retry_req), Err(Error::Block { .. })) =
+                (block_mitigation, retry_req, &result)
+            else {
+                return result;
+            };
+            if attempt >= block_mitigation.max_retries {
+                return result;
+            }
+            attempt += 1;
+
+            let user_agent = block_mitigation.rotate_user_agent();
+            if block_mitigation.refresh_cookies {
+                // the cookies are bound to the user agent, so the one of the retry must be used
+                let mut refresh_req = client.get("https://www.crunchyroll.com");
+                if let Some(user_agent) = &user_agent {
+                    refresh_req = refresh_req.header(header::USER_AGENT, user_agent)
+                }
+                let refreshed = match refresh_req.build() {
+                    Ok(refresh_req) => {
+                        execute(
+                            client,
+                            refresh_req,
+                            hooks,
+                            #[cfg(feature = "tower")]
+                            None,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e.into()),
+                };
+                // the block is what the caller has to handle, not the failed refresh
+                if !refreshed.is_ok_and(|resp| resp.status().is_success()) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(url, "refreshing the cookies failed, not retrying");
+                    return result;
+                }
+            }
+            let delay = block_mitigation.delay();
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                attempt,
+                url,
+                delay = ?delay,
+                "request got blocked, retrying"
+            );
+            if let Some(on_attempt) = &block_mitigation.on_attempt {
+                on_attempt(&BlockMitigationEvent {
+                    attempt,
+                    url: url.clone(),
+                    user_agent,
+                    refreshed_cookies: block_mitigation.refresh_cookies,
+                    delay,
+                })
+            }
+            tokio::time::sleep(delay).await;
+
+            req = retry_req;
+        }
+    }
+
+    /// Removes all fields which are starting and ending with `__` from a map (which is usually the
+    /// response of a request). Some fields can be excluded from this process by providing the field
+    /// names in `not_clean_fields`.
+    #[cfg(feature = "__test_strict")]
+    fn clean_request(
+        mut map: serde_json::Map<String, serde_json::Value>,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        for (key, value) in map.clone() {
+            if key.starts_with("__") && key.ends_with("__") {
+                map.remove(key.as_str());
+            } else if let Some(object) = value.as_object() {
+                map.insert(
+                    key,
+                    serde_json::to_value(clean_request(object.clone())).unwrap(),
+                );
+            } else if let Some(array) = value.as_array() {
+                map.insert(
+                    key,
+                    serde_json::to_value(clean_request_array(array.clone())).unwrap(),
+                );
+            }
+        }
+        map
+    }
+
+    #[cfg(feature = "__test_strict")]
+    fn clean_request_array(mut arr: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+        for (i, item) in arr.clone().iter().enumerate() {
+            if let Some(object) = item.as_object() {
+                arr[i] = serde_json::to_value(clean_request(object.clone())).unwrap();
+            } else if let Some(array) = item.as_array() {
+                arr[i] = serde_json::to_value(clean_request_array(array.clone())).unwrap();
+            }
+        }
+        arr
+    }
+}
+
+pub(crate) use auth::Executor;
+pub use auth::{
+    BasicAuthToken, Clock, CrunchyrollBuilder, DeviceCodeLogin, Endpoints, RegisterOptions,
+    SessionToken, SystemClock,
+};
+pub use block_mitigation::{BlockMitigation, BlockMitigationEvent};
+pub use proxy::{ProxyConfig, ProxyScope};
+pub use rate_limit::{RateLimitRetry, RateLimiter};
+pub use token_store::TokenStore;
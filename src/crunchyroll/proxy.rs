@@ -0,0 +1,88 @@
+//! Proxy configuration of the http client.
+
+use crate::error::Error;
+use crate::Result;
+use reqwest::Url;
+
+/// Which requests should be sent through a proxy configured via [`ProxyConfig`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProxyScope {
+    /// Only requests to the Crunchyroll api, including authentication and playback endpoints.
+    Api,
+    /// Only requests to the media CDNs, e.g. stream segments or subtitles.
+    Media,
+    /// All requests.
+    #[default]
+    All,
+}
+
+/// If `url` points to the Crunchyroll api (including authentication and playback endpoints)
+/// and not to a media CDN.
+pub(crate) fn is_api_url(url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    host == "crunchyroll.com"
+        || host == "www.crunchyroll.com"
+        || host == "beta-api.crunchyroll.com"
+        || host == "sso.crunchyroll.com"
+        || host.ends_with(".crunchyrollsvc.com")
+}
+
+impl ProxyScope {
+    pub(crate) fn matches(&self, url: &Url) -> bool {
+        let is_api = is_api_url(url);
+        match self {
+            ProxyScope::Api => is_api,
+            ProxyScope::Media => !is_api,
+            ProxyScope::All => true,
+        }
+    }
+}
+
+/// Proxy configuration. Set it via [`crate::crunchyroll::CrunchyrollBuilder::proxy`].
+#[derive(Clone, Debug)]
+pub struct ProxyConfig {
+    url: String,
+    scope: ProxyScope,
+    basic_auth: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    /// Creates a new proxy configuration. `url` is the url of the http(s) proxy, e.g.
+    /// `http://127.0.0.1:8080`.
+    pub fn new(url: impl Into<String>) -> ProxyConfig {
+        Self {
+            url: url.into(),
+            scope: ProxyScope::default(),
+            basic_auth: None,
+        }
+    }
+
+    /// Set which requests should be sent through the proxy. Default is [`ProxyScope::All`].
+    pub fn scope(mut self, scope: ProxyScope) -> ProxyConfig {
+        self.scope = scope;
+        self
+    }
+
+    /// Set credentials to authenticate against the proxy.
+    pub fn basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> ProxyConfig {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    pub(crate) fn to_proxy(&self) -> Result<reqwest::Proxy> {
+        let proxy_url = Url::parse(&self.url).map_err(|e| Error::Input {
+            message: format!("invalid proxy url '{}': {e}", self.url),
+        })?;
+        let scope = self.scope.clone();
+        let mut proxy =
+            reqwest::Proxy::custom(move |url| scope.matches(url).then(|| proxy_url.clone()));
+        if let Some((username, password)) = &self.basic_auth {
+            proxy = proxy.basic_auth(username, password)
+        }
+        Ok(proxy)
+    }
+}
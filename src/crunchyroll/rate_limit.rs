@@ -0,0 +1,123 @@
+//! Client side limiting and retrying of requests to the Crunchyroll api.
+
+use std::sync::Arc;
+
+/// Limits how many requests are sent to the Crunchyroll api. Downloads from the media CDNs
+/// (e.g. stream segments) aren't limited. It can be shared between multiple [`crate::Crunchyroll`]
+/// instances (clones of it share the same limit), see [`crate::crunchyroll::CrunchyrollBuilder::rate_limiter`].
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    min_interval: std::time::Duration,
+    /// Time slot of the last request which was let through or is waiting for its slot.
+    last_request: Arc<std::sync::Mutex<Option<tokio::time::Instant>>>,
+}
+
+impl RateLimiter {
+    /// Create a new limiter which waits at least `min_interval` between two requests.
+    pub fn new(min_interval: std::time::Duration) -> RateLimiter {
+        Self {
+            min_interval,
+            last_request: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Create a new limiter which allows at most `requests` requests per second.
+    pub fn per_second(requests: u32) -> RateLimiter {
+        Self::new(std::time::Duration::from_secs(1) / requests.max(1))
+    }
+
+    pub(crate) async fn acquire(&self) {
+        // the slot is reserved before sleeping, so concurrent requests are queued up without
+        // waiting for the lock while another one sleeps
+        let slot = {
+            let mut last_request = self.last_request.lock().unwrap();
+            let now = tokio::time::Instant::now();
+            let slot = last_request.map_or(now, |last| (last + self.min_interval).max(now));
+            *last_request = Some(slot);
+            slot
+        };
+        tokio::time::sleep_until(slot).await
+    }
+}
+
+/// Policy how requests which got rate limited by Crunchyroll ([`crate::error::Error::RateLimited`]) are
+/// retried. Set it via [`crate::crunchyroll::CrunchyrollBuilder::rate_limit_retry`]. Without it, rate limited
+/// requests return the error immediately.
+#[derive(Clone, Debug)]
+pub struct RateLimitRetry {
+    max_retries: u32,
+    delay: std::time::Duration,
+    max_delay: std::time::Duration,
+}
+
+impl Default for RateLimitRetry {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            delay: std::time::Duration::from_secs(1),
+            max_delay: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+impl RateLimitRetry {
+    /// How often a rate limited request is retried. Default is 3.
+    pub fn max_retries(mut self, max_retries: u32) -> RateLimitRetry {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait before retrying if the response doesn't contain a `Retry-After`
+    /// header. The delay is doubled on every retry. Default is 1 second.
+    pub fn delay(mut self, delay: std::time::Duration) -> RateLimitRetry {
+        self.delay = delay;
+        self
+    }
+
+    /// Maximal time to wait before a retry. If Crunchyroll requests to wait longer, the
+    /// [`crate::error::Error::RateLimited`] is returned instead. Default is 60 seconds.
+    pub fn max_delay(mut self, max_delay: std::time::Duration) -> RateLimitRetry {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns how long to wait before the `attempt`th retry, or [`None`] if the request
+    /// shouldn't be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        retry_after: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+        let delay = retry_after.unwrap_or(self.delay * 2u32.saturating_pow(attempt - 1));
+        (delay <= self.max_delay).then_some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use futures_util::FutureExt;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn rate_limiter_slots() {
+        let limiter = RateLimiter::new(Duration::from_millis(100));
+        let start = tokio::time::Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // a request which waits for its slot but isn't polled anymore doesn't block the others
+        let mut stalled = Box::pin(limiter.acquire());
+        assert!((&mut stalled).now_or_never().is_none());
+        let others = async { tokio::join!(limiter.acquire(), limiter.acquire()) };
+        tokio::time::timeout(Duration::from_secs(1), others)
+            .await
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(1000), "{elapsed:?}")
+    }
+}
@@ -0,0 +1,20 @@
+//! Persistence of the session token.
+
+use crate::crunchyroll::SessionToken;
+use crate::Result;
+
+/// Storage for the [`SessionToken`] of a session. Set it via [`crate::crunchyroll::CrunchyrollBuilder::token_store`]
+/// to persist the token (e.g. in a file, the system keyring or a database) after login and
+/// every time it gets rotated while refreshing the session. Crunchyroll invalidates a refresh
+/// token once it was used, so a token which was stored only at login time may become unusable.
+#[async_trait::async_trait]
+pub trait TokenStore: std::fmt::Debug + Send + Sync {
+    /// Return the stored token, or [`None`] if no token is stored.
+    async fn get(&self) -> Result<Option<SessionToken>>;
+
+    /// Store `token`, replacing the previously stored one. An error while storing the token at
+    /// login fails the login. If the token is rotated while refreshing the session, the error
+    /// doesn't fail the request which triggered the refresh but is passed to
+    /// [`crate::crunchyroll::CrunchyrollBuilder::on_token_store_error`].
+    async fn set(&self, token: &SessionToken) -> Result<()>;
+}
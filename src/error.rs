@@ -1,13 +1,44 @@
 //! Library specific errors.
 
+use crate::macros::enum_values;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
 use reqwest::{Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_json::{Map, Value};
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub(crate) type Result<T, E = Error> = core::result::Result<T, E>;
 
+enum_values! {
+    /// Error codes which the api returns in its error responses. Get it via the `code` field of
+    /// [`Error::Request`]. Codes which aren't listed here are available as
+    /// [`ApiErrorCode::Custom`].
+    pub enum ApiErrorCode {
+        InvalidCredentials = "auth.obtain_access_token.invalid_credentials"
+        ForcePasswordReset = "auth.obtain_access_token.force_password_reset"
+        InvalidGrant = "invalid_grant"
+        UnauthorizedClient = "unauthorized_client"
+        SubscriptionRequired = "subscription.required"
+        TooManyActiveStreams = "TOO_MANY_ACTIVE_STREAMS"
+        Unauthorized = "unauthorized"
+        Forbidden = "forbidden"
+        NotFound = "not_found"
+        BadRequest = "bad_request"
+        EmailAlreadyExists = "accounts.create_account.email_already_exists"
+        WeakPassword = "accounts.create_account.weak_password"
+        ExternalAccountNotLinked = "auth.obtain_access_token.external_account_not_linked"
+        AuthorizationPending = "authorization_pending"
+        SlowDown = "slow_down"
+        ExpiredToken = "expired_token"
+        AccessDenied = "access_denied"
+        GeoRestricted = "content.geo_restricted"
+    }
+}
+
 /// Crate specific error types.
 #[derive(Clone, Debug)]
 pub enum Error {
@@ -22,6 +53,13 @@ pub enum Error {
         status: Option<StatusCode>,
         /// The url which caused the error.
         url: String,
+        /// The error code of the api response. [`None`] if the error didn't originate from an api
+        /// error response, e.g. if the connection failed.
+        code: Option<ApiErrorCode>,
+        /// Id which Crunchyroll assigned to the request, taken from the `x-request-id` (or
+        /// `x-correlation-id`) header of the response. Include it when reporting issues. [`None`]
+        /// if there was no response or it didn't contain such a header.
+        request_id: Option<String>,
     },
     /// While decoding the api response body something went wrong.
     Decode {
@@ -32,6 +70,8 @@ pub enum Error {
         /// The url which caused the error. Might be empty if the error got triggered by the
         /// [`From<serde_json::Error>`] implementation for this enum.
         url: String,
+        /// Id of the request, see the `request_id` field of [`Error::Request`].
+        request_id: Option<String>,
     },
 
     /// Something went wrong while logging in.
@@ -48,6 +88,71 @@ pub enum Error {
         body: String,
         /// The url which caused the error.
         url: String,
+        /// Id of the request, see the `request_id` field of [`Error::Request`].
+        request_id: Option<String>,
+    },
+
+    /// The requested content isn't available in the region of the session (see
+    /// [`crate::Crunchyroll::region`]). Only returned for api errors with the code
+    /// [`ApiErrorCode::GeoRestricted`], geo restrictions which Crunchyroll reports differently (the
+    /// code couldn't be verified against every endpoint) are still returned as [`Error::Request`].
+    GeoRestricted {
+        message: String,
+        status: StatusCode,
+        /// Countries (ISO 3166-1 alpha-2 codes) the content is available in. Empty if the api
+        /// response doesn't contain them.
+        available_in: Vec<String>,
+        /// The url which caused the error.
+        url: String,
+        /// Id of the request, see the `request_id` field of [`Error::Request`].
+        request_id: Option<String>,
+    },
+
+    /// Crunchyroll rate limited the request (status `429`). Set
+    /// [`crate::crunchyroll::CrunchyrollBuilder::rate_limit_retry`] to automatically retry rate
+    /// limited requests.
+    RateLimited {
+        message: String,
+        /// How long to wait before sending the next request, parsed from the `Retry-After` header.
+        /// [`None`] if the response doesn't contain the header.
+        retry_after: Option<Duration>,
+        /// The url which caused the error.
+        url: String,
+        /// Id of the request, see the `request_id` field of [`Error::Request`].
+        request_id: Option<String>,
+    },
+
+    /// The operation was cancelled via [`crate::common::Cancellation::cancel`].
+    Cancelled { message: String },
+
+    /// None of the platforms which [`crate::media::Stream::from_id_auto`] tried yields a stream
+    /// that matches the requirements.
+    NoMatchingStream {
+        message: String,
+        /// The tried platforms. If the stream or stream data of a platform couldn't be requested,
+        /// or the stream couldn't be invalidated afterward, the error is appended in parentheses.
+        tried: Vec<String>,
+    },
+
+    /// The operation didn't finish in the configured time, e.g. a page request of a
+    /// [`crate::common::Pagination`] with [`crate::common::Pagination::page_timeout`] set.
+    Timeout {
+        message: String,
+        /// The time after which the operation was aborted.
+        timeout: Duration,
+    },
+
+    /// A downloaded stream segment is incomplete or otherwise damaged, even after retrying.
+    CorruptSegment {
+        message: String,
+        /// The length which the server announced via the `Content-Length` header.
+        expected_length: Option<u64>,
+        /// The length of the data which was actually received.
+        received_length: u64,
+        /// How many times the segment was requested.
+        attempts: u32,
+        /// The url of the segment.
+        url: String,
     },
 }
 
@@ -55,19 +160,29 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Internal { message } => write!(f, "{message}"),
-            Error::Request { message, url, .. } => {
+            Error::Request {
+                message,
+                url,
+                request_id,
+                ..
+            } => {
                 // the url can be 'n/a' when the error got triggered by the [`From<reqwest::Error>`]
                 // implementation for this error struct
                 if url != "n/a" {
-                    write!(f, "{message} ({url})")
+                    write!(f, "{message} ({url})")?
                 } else {
-                    write!(f, "{message}")
+                    write!(f, "{message}")?
                 }
+                if let Some(request_id) = request_id {
+                    write!(f, " [request id: {request_id}]")?
+                }
+                Ok(())
             }
             Error::Decode {
                 message,
                 content,
                 url,
+                request_id,
             } => {
                 let mut msg = message.clone();
                 // the url is 'n/a' when the error got triggered by the [`From<serde_json::Error>`]
@@ -75,6 +190,9 @@ impl Display for Error {
                 if url != "n/a" {
                     msg.push_str(&format!(" ({url})"))
                 }
+                if let Some(request_id) = request_id {
+                    msg.push_str(&format!(" [request id: {request_id}]"))
+                }
                 if content.is_empty() {
                     write!(f, "{}", msg)
                 } else {
@@ -83,7 +201,64 @@ impl Display for Error {
             }
             Error::Authentication { message } => write!(f, "{message}"),
             Error::Input { message } => write!(f, "{message}"),
-            Error::Block { message, body, url } => write!(f, "{message} ({url}): {body}"),
+            Error::Block {
+                message,
+                body,
+                url,
+                request_id,
+            } => {
+                write!(f, "{message} ({url})")?;
+                if let Some(request_id) = request_id {
+                    write!(f, " [request id: {request_id}]")?
+                }
+                write!(f, ": {body}")
+            }
+            Error::Cancelled { message } => write!(f, "{message}"),
+            Error::Timeout { message, .. } => write!(f, "{message}"),
+            Error::NoMatchingStream { message, tried } => {
+                write!(f, "{message} (tried {})", tried.join(", "))
+            }
+            Error::RateLimited {
+                message,
+                url,
+                request_id,
+                ..
+            } => {
+                write!(f, "{message} ({url})")?;
+                if let Some(request_id) = request_id {
+                    write!(f, " [request id: {request_id}]")?
+                }
+                Ok(())
+            }
+            Error::GeoRestricted {
+                message,
+                available_in,
+                url,
+                request_id,
+                ..
+            } => {
+                write!(f, "{message} ({url})")?;
+                if let Some(request_id) = request_id {
+                    write!(f, " [request id: {request_id}]")?
+                }
+                if !available_in.is_empty() {
+                    write!(f, ", available in: {}", available_in.join(", "))?
+                }
+                Ok(())
+            }
+            Error::CorruptSegment {
+                message,
+                expected_length,
+                received_length,
+                attempts,
+                url,
+            } => {
+                write!(
+                    f,
+                    "{message} ({url}): received {received_length} bytes, expected {}, after {attempts} attempt(s)",
+                    expected_length.map_or("unknown".to_string(), |l| l.to_string())
+                )
+            }
         }
     }
 }
@@ -96,6 +271,7 @@ impl From<serde_json::Error> for Error {
             message: err.to_string(),
             content: vec![],
             url: "n/a".to_string(),
+            request_id: None,
         }
     }
 }
@@ -113,12 +289,15 @@ impl From<reqwest::Error> for Error {
                 message: err.to_string(),
                 status: err.status(),
                 url: err.url().map_or("n/a".to_string(), |url| url.to_string()),
+                code: None,
+                request_id: None,
             }
         } else if err.is_decode() {
             Error::Decode {
                 message: err.to_string(),
                 content: vec![],
                 url: err.url().map_or("n/a".to_string(), |url| url.to_string()),
+                request_id: None,
             }
         } else if err.is_builder() {
             Error::Internal {
@@ -132,6 +311,36 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+/// Headers which contain the id Crunchyroll assigned to a request, in the order they're preferred.
+/// Ids of the CDN edge servers (like `cf-ray` or `x-amz-cf-id`) don't identify the request at
+/// Crunchyroll and are therefore not used.
+const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "x-correlation-id"];
+
+/// Returns the id of the request a response belongs to, if the response contains one.
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    REQUEST_ID_HEADERS.iter().find_map(|name| {
+        headers
+            .get(*name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    })
+}
+
+impl Error {
+    /// Sets the request id of errors which originate from a response.
+    fn with_request_id(mut self, id: Option<String>) -> Self {
+        if let Error::Request { request_id, .. }
+        | Error::Decode { request_id, .. }
+        | Error::Block { request_id, .. }
+        | Error::RateLimited { request_id, .. }
+        | Error::GeoRestricted { request_id, .. } = &mut self
+        {
+            *request_id = id
+        }
+        self
+    }
+}
+
 pub(crate) fn is_request_error(value: Value, url: &str, status: &StatusCode) -> Result<()> {
     #[derive(Debug, Deserialize)]
     #[serde(untagged)]
@@ -161,9 +370,11 @@ pub(crate) fn is_request_error(value: Value, url: &str, status: &StatusCode) ->
         other: Map<String, Value>,
     }
 
-    let error_msg = match serde_json::from_value::<ErrorTypes>(value) {
+    let available_in = geo_available_in(&value);
+    let (code, error_msg) = match serde_json::from_value::<ErrorTypes>(value) {
         Ok(ErrorTypes::MessageTypeError { message, r#type }) => {
-            format!("{} - {}", r#type, message)
+            let msg = format!("{} - {}", r#type, message);
+            (r#type, msg)
         }
         Ok(ErrorTypes::CodeError {
             code,
@@ -173,7 +384,7 @@ pub(crate) fn is_request_error(value: Value, url: &str, status: &StatusCode) ->
             let mut msg = if let Some(message) = message {
                 format!("{message} - {code}")
             } else {
-                code
+                code.clone()
             };
             if !context.is_empty() {
                 let details: Vec<String> = context
@@ -182,27 +393,184 @@ pub(crate) fn is_request_error(value: Value, url: &str, status: &StatusCode) ->
                     .collect();
                 msg += &format!(" ({})", details.join(", "))
             }
-            msg
+            (code, msg)
         }
         Ok(ErrorTypes::GenericError { error, other }) => {
-            let mut msg = error;
+            let mut msg = error.clone();
             if !other.is_empty() {
                 msg += &format!(" ({})", serde_json::to_string(&other).unwrap())
             }
-            msg
+            (error, msg)
         }
         Err(_) => return Ok(()),
     };
+    let code = ApiErrorCode::from(code);
+    if code == ApiErrorCode::GeoRestricted {
+        return Err(Error::GeoRestricted {
+            message: error_msg,
+            status: *status,
+            available_in,
+            url: url.to_string(),
+            request_id: None,
+        });
+    }
     Err(Error::Request {
         message: error_msg,
         status: Some(*status),
         url: url.to_string(),
+        code: Some(code),
+        request_id: None,
     })
 }
 
-pub(crate) async fn check_request<T: DeserializeOwned>(url: String, resp: Response) -> Result<T> {
+/// Returns the countries a geo restricted content is available in, taken from the `available_in`
+/// field of the api error or its context.
+fn geo_available_in(value: &Value) -> Vec<String> {
+    let Value::Object(map) = value else {
+        return vec![];
+    };
+    let mut objects = vec![map];
+    if let Some(Value::Array(context)) = map.get("context") {
+        objects.extend(context.iter().filter_map(|c| c.as_object()))
+    }
+
+    objects
+        .iter()
+        .filter_map(|object| object.get("available_in")?.as_array())
+        .flatten()
+        .filter_map(|country| country.as_str().map(|c| c.to_string()))
+        .collect()
+}
+
+/// Anomaly in an api response which was tolerated because lenient parsing is enabled (see
+/// [`crate::crunchyroll::CrunchyrollBuilder::lenient_parsing`]).
+#[derive(Clone, Debug)]
+pub struct ParseWarning {
+    /// Url of the request whose response contained the anomaly.
+    pub url: String,
+    /// The error which would have been returned without lenient parsing.
+    pub message: String,
+    /// Json paths (e.g. `data.0.title`) of all `null` values which were replaced with their
+    /// default.
+    pub fields: Vec<String>,
+}
+
+/// Maximal number of warnings a [`ParseWarnings`] log keeps.
+const MAX_PARSE_WARNINGS: usize = 100;
+
+/// Log of the [`ParseWarning`]s of a [`crate::Crunchyroll`] session. Get it via
+/// [`crate::Crunchyroll::parse_warnings`]. Only the latest 100 warnings are kept, so the log
+/// doesn't grow indefinitely in long-running sessions.
+#[derive(Clone, Debug, Default)]
+pub struct ParseWarnings {
+    warnings: Arc<Mutex<Vec<ParseWarning>>>,
+}
+
+impl ParseWarnings {
+    /// All warnings which were recorded, in the order they occurred.
+    pub fn all(&self) -> Vec<ParseWarning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    /// Return all recorded warnings and clear the log.
+    pub fn take(&self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings.lock().unwrap())
+    }
+
+    /// Check if no warnings were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.lock().unwrap().is_empty()
+    }
+
+    fn push(&self, warning: ParseWarning) {
+        let mut warnings = self.warnings.lock().unwrap();
+        if warnings.len() >= MAX_PARSE_WARNINGS {
+            warnings.remove(0);
+        }
+        warnings.push(warning)
+    }
+}
+
+/// Remove all `null` object fields from `value`, so that the fields fall back to their default when
+/// deserialized. `null` array elements are kept. Returns the paths of the removed fields.
+fn strip_nulls(value: &mut Value, path: &str, removed: &mut Vec<String>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, v| {
+                if v.is_null() {
+                    removed.push(join(key));
+                    false
+                } else {
+                    true
+                }
+            });
+            for (key, v) in object.iter_mut() {
+                strip_nulls(v, &join(key), removed)
+            }
+        }
+        // removing array elements would shift the following ones and change the length of the
+        // array, so only the objects inside arrays are cleaned up
+        Value::Array(array) => {
+            for (i, v) in array.iter_mut().enumerate() {
+                strip_nulls(v, &join(&i.to_string()), removed)
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Parses the value of a `Retry-After` header, which is either an amount of seconds or a http date
+/// (which is compared to `now`).
+fn parse_retry_after(retry_after: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(secs) = retry_after.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(retry_after.trim()).ok()?;
+    // a date in the past means that the request can be retried immediately
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Creates the [`Error::RateLimited`] of a response with status `429`.
+pub(crate) fn rate_limited(url: String, headers: &HeaderMap, now: DateTime<Utc>) -> Error {
+    let retry_after = headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|retry_after| retry_after.to_str().ok())
+        .and_then(|retry_after| parse_retry_after(retry_after, now));
+
+    Error::RateLimited {
+        message: format!(
+            "Rate limit detected. {}",
+            retry_after.map_or("Try again later".to_string(), |retry_after| format!(
+                "Try again in {} seconds",
+                retry_after.as_secs()
+            ))
+        ),
+        retry_after,
+        url,
+        request_id: request_id(headers),
+    }
+}
+
+pub(crate) async fn check_request<T: DeserializeOwned>(
+    url: String,
+    resp: Response,
+    parse_warnings: Option<&ParseWarnings>,
+    now: DateTime<Utc>,
+) -> Result<T> {
     let content_length = resp.content_length().unwrap_or(0);
     let status = resp.status();
+    let request_id = request_id(resp.headers());
     let _raw = match resp.status().as_u16() {
         403 => {
             let raw = resp.bytes().await?;
@@ -215,6 +583,7 @@ pub(crate) async fn check_request<T: DeserializeOwned>(url: String, resp: Respon
                     message: "Triggered Cloudflare bot protection".to_string(),
                     body: String::from_utf8_lossy(raw.as_ref()).to_string(),
                     url,
+                    request_id,
                 });
             }
             raw
@@ -224,29 +593,11 @@ pub(crate) async fn check_request<T: DeserializeOwned>(url: String, resp: Respon
                 message: "The requested resource is not present".to_string(),
                 status: Some(resp.status()),
                 url,
+                code: None,
+                request_id,
             })
         }
-        429 => {
-            let retry_secs =
-                if let Some(retry_after) = resp.headers().get(reqwest::header::RETRY_AFTER) {
-                    retry_after.to_str().map_or(None, |retry_after_secs| {
-                        retry_after_secs.parse::<u32>().ok()
-                    })
-                } else {
-                    None
-                };
-
-            return Err(Error::Request {
-                message: format!(
-                    "Rate limit detected. {}",
-                    retry_secs.map_or("Try again later".to_string(), |secs| format!(
-                        "Try again in {secs} seconds"
-                    ))
-                ),
-                status: Some(resp.status()),
-                url,
-            });
-        }
+        429 => return Err(rate_limited(url, resp.headers(), now)),
         _ => resp.bytes().await?,
     };
     let mut raw: &[u8] = _raw.as_ref();
@@ -260,11 +611,91 @@ pub(crate) async fn check_request<T: DeserializeOwned>(url: String, resp: Respon
         message: format!("{} at {}:{}", e, e.line(), e.column()),
         content: raw.to_vec(),
         url: url.clone(),
+        request_id: request_id.clone(),
     })?;
-    is_request_error(value.clone(), &url, &status)?;
-    serde_json::from_value::<T>(value).map_err(|e| Error::Decode {
-        message: format!("{} at {}:{}", e, e.line(), e.column()),
+    is_request_error(value.clone(), &url, &status)
+        .map_err(|e| e.with_request_id(request_id.clone()))?;
+    let message = match serde_json::from_value::<T>(value.clone()) {
+        Ok(t) => return Ok(t),
+        Err(e) => format!("{} at {}:{}", e, e.line(), e.column()),
+    };
+    let error = || Error::Decode {
+        message: message.clone(),
         content: raw.to_vec(),
+        url: url.clone(),
+        request_id: request_id.clone(),
+    };
+    let Some(parse_warnings) = parse_warnings else {
+        return Err(error());
+    };
+
+    let mut value = value;
+    let mut fields = vec![];
+    strip_nulls(&mut value, "", &mut fields);
+    if fields.is_empty() {
+        return Err(error());
+    }
+    let t = serde_json::from_value::<T>(value).map_err(|_| error())?;
+    #[cfg(feature = "tracing")]
+    tracing::warn!(url, fields = ?fields, "tolerated unexpected null values in response");
+    parse_warnings.push(ParseWarning {
         url,
-    })
+        message,
+        fields,
+    });
+    Ok(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn strip_nulls_keeps_array_elements() {
+        let mut value = json!({
+            "title": null,
+            "data": [
+                { "id": "GY8VEQ95Y", "description": null },
+                null,
+                [null, { "slug": null }]
+            ]
+        });
+        let mut removed = vec![];
+        strip_nulls(&mut value, "", &mut removed);
+
+        assert_eq!(
+            value,
+            json!({
+                "data": [
+                    { "id": "GY8VEQ95Y" },
+                    null,
+                    [null, {}]
+                ]
+            })
+        );
+        assert_eq!(
+            removed,
+            vec![
+                "title".to_string(),
+                "data.0.description".to_string(),
+                "data.2.1.slug".to_string()
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_warnings_capped() {
+        let parse_warnings = ParseWarnings::default();
+        for i in 0..MAX_PARSE_WARNINGS + 5 {
+            parse_warnings.push(ParseWarning {
+                url: i.to_string(),
+                message: String::new(),
+                fields: vec![],
+            })
+        }
+        let warnings = parse_warnings.all();
+        assert_eq!(warnings.len(), MAX_PARSE_WARNINGS);
+        assert_eq!(warnings[0].url, "5")
+    }
 }
@@ -1,13 +1,16 @@
 //! Feeds like home feed or news feed.
 
 use crate::common::{Pagination, PaginationBulkResultMeta, V2BulkResult, V2TypeBulkResult};
-use crate::media::MediaType;
+use crate::crunchyroll::Executor;
+use crate::media::util::request_media;
+use crate::media::{MediaType, RelatedMedia};
 use crate::search::{BrowseOptions, BrowseSortType};
-use crate::{Crunchyroll, MediaCollection, Request, Series};
+use crate::{Concert, Crunchyroll, Episode, MediaCollection, MusicVideo, Request, Result, Series};
 use chrono::{DateTime, Utc};
 use futures_util::FutureExt;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::Arc;
 
 /// Images for a [`FeedCarousel`].
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
@@ -22,6 +25,7 @@ pub struct FeedCarouselImages {
 /// The carousel / sliding images showed at first when visiting crunchyroll.com
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
+#[request(executor(panel))]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct FeedCarousel {
@@ -70,16 +74,82 @@ pub struct FeedBanner {
 }
 
 /// A feed containing multiple ids to different series.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
 pub struct SeriesFeed {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
     pub title: String,
 
     pub description: String,
 
-    /// Ids to series. Use [`Series::from_id`] to get the series.
+    /// Ids to series. Use [`SeriesFeed::series`] to get the series.
+    pub ids: Vec<String>,
+}
+
+impl SeriesFeed {
+    /// Requests all series of [`SeriesFeed::ids`] in a single request.
+    pub async fn series(&self) -> Result<Vec<Series>> {
+        let objects = Crunchyroll {
+            executor: self.executor.clone(),
+        }
+        .objects_from_ids(&self.ids)
+        .await?;
+        Ok(objects
+            .into_iter()
+            .filter_map(|o| Series::try_from(o).ok())
+            .collect())
+    }
+}
+
+/// A feed containing multiple ids to music videos.
+#[derive(Clone, Debug, Default, Serialize, Request)]
+pub struct MusicVideoFeed {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
+    /// Ids to music videos. Use [`MusicVideoFeed::music_videos`] to get the music videos.
+    pub ids: Vec<String>,
+}
+
+impl MusicVideoFeed {
+    /// Requests all music videos of [`MusicVideoFeed::ids`] in a single request.
+    pub async fn music_videos(&self) -> Result<Vec<MusicVideo>> {
+        if self.ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/music/music_videos/{}",
+            self.ids.join(",")
+        );
+        request_media(self.executor.clone(), endpoint).await
+    }
+}
+
+/// A feed containing multiple ids to concerts.
+#[derive(Clone, Debug, Default, Serialize, Request)]
+pub struct ConcertFeed {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
+    /// Ids to concerts. Use [`ConcertFeed::concerts`] to get the concerts.
     pub ids: Vec<String>,
 }
 
+impl ConcertFeed {
+    /// Requests all concerts of [`ConcertFeed::ids`] in a single request.
+    pub async fn concerts(&self) -> Result<Vec<Concert>> {
+        if self.ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/music/concerts/{}",
+            self.ids.join(",")
+        );
+        request_media(self.executor.clone(), endpoint).await
+    }
+}
+
 /// A feed containing an id to a series or episode, depending on what you've watched in the past.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct SimilarFeed {
@@ -91,17 +161,36 @@ pub struct SimilarFeed {
     pub similar_id: String,
 }
 
-/// A feed containing information about a game with a link to it.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+/// A feed containing information about a game with a link to it. Also used for the games of the
+/// Crunchyroll Game Vault (see [`Crunchyroll::games`]).
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct GameFeed {
+    /// Id of the game. Only set for games of the Game Vault.
+    #[serde(default)]
+    pub id: String,
     pub title: String,
     pub description: String,
     pub images: FeedBannerImages,
+    /// Link to the game, for Game Vault games this is the store page (e.g. Google Play or the App
+    /// Store) of the game.
     pub link: String,
 }
 
+/// A feed containing ids to games of the Crunchyroll Game Vault. The ids correspond to
+/// [`GameFeed::id`] of the games returned by [`Crunchyroll::games`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct GameVaultFeed {
+    pub title: String,
+
+    pub description: String,
+
+    pub ids: Vec<String>,
+}
+
 /// Items which can be shown on the home feed.
-#[derive(Clone, Debug, Serialize, Request)]
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize)]
 pub enum HomeFeed {
     /// The feed at the top of the Crunchyroll website.
     CarouselFeed(Vec<FeedCarousel>),
@@ -118,12 +207,12 @@ pub enum HomeFeed {
     /// A feed containing a title with description and multiple series (ids) matching to title and
     /// description.
     SeriesFeed(SeriesFeed),
-    /// A feed containing ids to music videos. Use [`crate::MusicVideo::from_id`] to get usable
+    /// A feed containing ids to music videos. Use [`MusicVideoFeed::music_videos`] to get usable
     /// structs from it.
-    MusicVideoFeed(Vec<String>),
-    /// A feed containing ids to concerts. Use [`crate::Concert::from_id`] to get usable structs from
+    MusicVideoFeed(MusicVideoFeed),
+    /// A feed containing ids to concerts. Use [`ConcertFeed::concerts`] to get usable structs from
     /// it.
-    ConcertFeed(Vec<String>),
+    ConcertFeed(ConcertFeed),
     /// A feed containing ids to artists. Use [`crate::media::Artist::from_id`] to get usable structs
     /// from it.
     ArtistFeed(Vec<String>),
@@ -137,6 +226,8 @@ pub enum HomeFeed {
     /// [`Series::similar`] to get similar series.
     SimilarTo(SimilarFeed),
     Game(GameFeed),
+    /// A feed containing ids to games of the Crunchyroll Game Vault.
+    GameVault(GameVaultFeed),
     /// Crunchyroll may update their feed / add new items. This field catches everything which is
     /// unknown / not implemented in the library.
     Unknown(serde_json::Map<String, serde_json::Value>),
@@ -192,7 +283,7 @@ impl<'de> Deserialize<'de> for HomeFeed {
                             .ok_or_else(|| type_error("link", "string"))?
                             .to_string();
                         let query: Vec<(String, String)> =
-                            serde_urlencoded::from_str(link.split('?').last().unwrap())
+                            serde_urlencoded::from_str(link.split('?').next_back().unwrap())
                                 .map_err(|e| Error::custom(e.to_string()))?;
 
                         let mut browse_options = BrowseOptions::default();
@@ -253,28 +344,43 @@ impl<'de> Deserialize<'de> for HomeFeed {
                             .as_array()
                             .ok_or_else(|| type_error("ids", "string list"))?
                             .iter()
-                            .map(|v| v.to_string())
-                            .collect();
-                        Ok(Self::ConcertFeed(ids))
+                            .map(|v| v.as_str().map(|id| id.to_string()))
+                            .collect::<Option<Vec<String>>>()
+                            .ok_or_else(|| type_error("ids", "string list"))?;
+                        Ok(Self::ConcertFeed(ConcertFeed {
+                            executor: Default::default(),
+                            ids,
+                        }))
                     }
                     "music_video" => {
                         let ids: Vec<String> = get_value("ids")?
                             .as_array()
                             .ok_or_else(|| type_error("ids", "string list"))?
                             .iter()
-                            .map(|v| v.to_string())
-                            .collect();
-                        Ok(Self::MusicVideoFeed(ids))
+                            .map(|v| v.as_str().map(|id| id.to_string()))
+                            .collect::<Option<Vec<String>>>()
+                            .ok_or_else(|| type_error("ids", "string list"))?;
+                        Ok(Self::MusicVideoFeed(MusicVideoFeed {
+                            executor: Default::default(),
+                            ids,
+                        }))
                     }
                     "artist" => {
                         let ids: Vec<String> = get_value("ids")?
                             .as_array()
                             .ok_or_else(|| type_error("ids", "string list"))?
                             .iter()
-                            .map(|v| v.to_string())
-                            .collect();
+                            .map(|v| v.as_str().map(|id| id.to_string()))
+                            .collect::<Option<Vec<String>>>()
+                            .ok_or_else(|| type_error("ids", "string list"))?;
                         Ok(Self::ArtistFeed(ids))
                     }
+                    "games" | "game_vault" => Ok(Self::GameVault(
+                        serde_json::from_value(
+                            serde_json::to_value(as_map).map_err(map_serde_error)?,
+                        )
+                        .map_err(map_serde_error)?,
+                    )),
                     #[cfg(feature = "__test_strict")]
                     _ => Err(Error::custom(format!(
                         "cannot parse home feed response type '{response_type}'"
@@ -301,6 +407,26 @@ impl<'de> Deserialize<'de> for HomeFeed {
     }
 }
 
+#[async_trait::async_trait]
+impl Request for HomeFeed {
+    async fn __set_executor(&mut self, executor: Arc<Executor>) {
+        match self {
+            HomeFeed::CarouselFeed(carousel_feed) => {
+                for carousel in carousel_feed {
+                    carousel.__set_executor(executor.clone()).await
+                }
+            }
+            HomeFeed::Series(series) => series.__set_executor(executor).await,
+            HomeFeed::SeriesFeed(series_feed) => series_feed.__set_executor(executor).await,
+            HomeFeed::MusicVideoFeed(music_video_feed) => {
+                music_video_feed.__set_executor(executor).await
+            }
+            HomeFeed::ConcertFeed(concert_feed) => concert_feed.__set_executor(executor).await,
+            _ => (),
+        }
+    }
+}
+
 /// Top news and latest news.
 pub struct NewsFeedResult {
     pub top_news: Pagination<NewsFeed>,
@@ -312,6 +438,9 @@ pub struct NewsFeedResult {
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct NewsFeed {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
     pub title: String,
     pub description: String,
 
@@ -325,6 +454,245 @@ pub struct NewsFeed {
     pub news_link: String,
 }
 
+impl NewsFeed {
+    /// Requests the article [`NewsFeed::news_link`] points to and extracts its content. The article
+    /// is requested without any session information, like every other website visitor would.
+    pub async fn article(&self) -> Result<NewsArticle> {
+        let raw = self.executor.request_external(&self.news_link).await?;
+        Ok(NewsArticle::parse(
+            &String::from_utf8_lossy(&raw),
+            &self.news_link,
+        ))
+    }
+}
+
+/// Content of a news article, get it via [`NewsFeed::article`]. It's extracted from the html page
+/// of the article, so formatting like links or emphasis is dropped.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NewsArticle {
+    pub title: String,
+    /// Text of every paragraph of the article body.
+    pub paragraphs: Vec<String>,
+    /// Urls of all images of the article body.
+    pub images: Vec<String>,
+
+    /// Url of the article.
+    pub url: String,
+}
+
+impl NewsArticle {
+    fn parse(html: &str, url: &str) -> Self {
+        // only the article element contains the actual content, the rest of the page are
+        // navigation, related articles, etc.
+        let body = html
+            .find("<article")
+            .and_then(|start| {
+                html[start..]
+                    .rfind("</article>")
+                    .map(|end| &html[start..start + end])
+            })
+            .unwrap_or(html);
+
+        let title = html_meta(html, "og:title")
+            .or_else(|| html_elements(body, "h1").into_iter().next())
+            .map(|t| html_text(&t))
+            .unwrap_or_default();
+        let paragraphs = html_elements(body, "p")
+            .iter()
+            .map(|p| html_text(p))
+            .filter(|p| !p.is_empty())
+            .collect();
+        let images = html_tags(body, "img")
+            .iter()
+            .filter_map(|img| html_attribute(img, "src"))
+            .map(|src| html_decode(&src))
+            .collect();
+
+        Self {
+            title,
+            paragraphs,
+            images,
+            url: url.to_string(),
+        }
+    }
+}
+
+/// Returns the position of the `>` which ends the tag `tag` starts with. A `>` inside a quoted
+/// attribute value doesn't end the tag.
+fn html_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Returns the position of the first tag named `name` in `html` and the length of its name
+/// including the leading `<` (or `</` if `closing` is set).
+fn html_find_tag(html: &str, name: &str, closing: bool) -> Option<(usize, usize)> {
+    let open = if closing {
+        format!("</{name}")
+    } else {
+        format!("<{name}")
+    };
+    let mut offset = 0;
+    while let Some(start) = html[offset..].find(&open) {
+        let start = offset + start;
+        let boundary = html[start + open.len()..].chars().next();
+        if boundary.is_some_and(|c| c.is_whitespace() || c == '>' || c == '/') {
+            return Some((start, open.len()));
+        }
+        offset = start + open.len()
+    }
+    None
+}
+
+/// Returns the opening tags (e.g. `<img src="...">`) of all elements with the given name.
+fn html_tags<'a>(html: &'a str, name: &str) -> Vec<&'a str> {
+    let mut tags = vec![];
+    let mut rest = html;
+    while let Some((start, _)) = html_find_tag(rest, name, false) {
+        let tag = &rest[start..];
+        // an unterminated tag at the end of a malformed document is ignored
+        let Some(end) = html_tag_end(tag) else { break };
+        tags.push(&tag[..=end]);
+        rest = &tag[end + 1..]
+    }
+    tags
+}
+
+/// Returns the inner html of all elements with the given name. Elements which are nested into
+/// another element with the same name are part of the inner html of the outer element and
+/// returned separately too. Elements without a closing tag are ignored.
+fn html_elements(html: &str, name: &str) -> Vec<String> {
+    let mut elements = vec![];
+    for tag in html_tags(html, name) {
+        // tags are sub slices of html, so their position can be calculated via the pointers
+        let content_start = tag.as_ptr() as usize - html.as_ptr() as usize + tag.len();
+        if tag.ends_with("/>") {
+            continue;
+        }
+
+        let content = &html[content_start..];
+        let mut depth = 0;
+        let mut offset = 0;
+        let end = loop {
+            let next_open = html_find_tag(&content[offset..], name, false);
+            let Some((close, close_len)) = html_find_tag(&content[offset..], name, true) else {
+                break None;
+            };
+            match next_open {
+                Some((open, open_len)) if open < close => {
+                    depth += 1;
+                    offset += open + open_len
+                }
+                _ if depth == 0 => break Some(offset + close),
+                _ => {
+                    depth -= 1;
+                    offset += close + close_len
+                }
+            }
+        };
+        if let Some(end) = end {
+            elements.push(content[..end].to_string())
+        }
+    }
+    elements
+}
+
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    loop {
+        let start = rest.find(name)?;
+        let before = rest[..start].chars().next_back();
+        let after = rest[start + name.len()..].trim_start();
+        rest = &rest[start + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = value[1..].find(quote)?;
+        return Some(value[1..1 + end].to_string());
+    }
+}
+
+fn html_meta(html: &str, property: &str) -> Option<String> {
+    html_tags(html, "meta")
+        .into_iter()
+        .find(|tag| html_attribute(tag, "property").is_some_and(|p| p == property))
+        .and_then(|tag| html_attribute(tag, "content"))
+}
+
+/// Strips all tags from the given html and decodes its entities.
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    html_decode(&text)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn html_decode(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let replacement = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "rsquo" | "lsquo" => Some('\''),
+            "rdquo" | "ldquo" => Some('"'),
+            "hellip" => Some('…'),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, replacement) {
+            (Some(entity), Some(replacement)) => {
+                decoded.push(replacement);
+                rest = &rest[entity.len() + 2..]
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..]
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
 impl Crunchyroll {
     /// Returns the home feed (shown when visiting the Crunchyroll index page).
     pub fn home_feed(&self) -> Pagination<HomeFeed> {
@@ -353,6 +721,34 @@ impl Crunchyroll {
         )
     }
 
+    /// Returns the music landing feed (shown when visiting the Crunchyroll music page). Music
+    /// related items are returned as [`HomeFeed::MusicVideoFeed`], [`HomeFeed::ConcertFeed`] and
+    /// [`HomeFeed::ArtistFeed`].
+    pub fn music_landing(&self) -> Pagination<HomeFeed> {
+        Pagination::new(
+            |options| {
+                async move {
+                    let endpoint = format!(
+                        "https://www.crunchyroll.com/content/v2/discover/{}/music_feed",
+                        options.executor.details.account_id.clone()?
+                    );
+                    let result = options
+                        .executor
+                        .get(endpoint)
+                        .query(&[("n", options.page_size), ("start", options.start)])
+                        .apply_locale_query()
+                        .request::<V2BulkResult<HomeFeed, PaginationBulkResultMeta>>()
+                        .await?;
+                    Ok(result.into())
+                }
+                .boxed()
+            },
+            self.executor.clone(),
+            None,
+            None,
+        )
+    }
+
     /// Returns Crunchyroll news.
     pub fn news_feed(&self) -> NewsFeedResult {
         NewsFeedResult {
@@ -417,6 +813,35 @@ impl Crunchyroll {
         }
     }
 
+    /// Returns the episodes to continue watching ("Continue Watching" on the website), including
+    /// their playhead and if they are fully watched.
+    pub fn up_next(&self) -> Pagination<RelatedMedia<Episode>> {
+        Pagination::new(
+            |options| {
+                async move {
+                    let endpoint = format!(
+                        "https://www.crunchyroll.com/content/v2/discover/{}/up_next",
+                        options.executor.details.account_id.clone()?
+                    );
+                    let result: V2BulkResult<RelatedMedia<Episode>, PaginationBulkResultMeta> =
+                        options
+                            .executor
+                            .get(endpoint)
+                            .query(&[("n", options.page_size), ("start", options.start)])
+                            .apply_locale_query()
+                            .apply_preferred_audio_locale_query()
+                            .request()
+                            .await?;
+                    Ok(result.into())
+                }
+                .boxed()
+            },
+            self.executor.clone(),
+            None,
+            None,
+        )
+    }
+
     /// Returns recommended series or movies to watch.
     pub fn recommendations(&self) -> Pagination<MediaCollection> {
         Pagination::new(
@@ -444,3 +869,94 @@ impl Crunchyroll {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn news_article_parse() {
+        let article = NewsArticle::parse(
+            include_str!("../tests/fixtures/news_article.html"),
+            "https://www.crunchyroll.com/news/frieren",
+        );
+
+        assert_eq!(article.title, "Frieren & Fern Return in Season 2");
+        // empty paragraphs and the ones outside the article are dropped
+        assert_eq!(
+            article.paragraphs,
+            vec![
+                "The second season of Frieren premieres in January 2026.",
+                "Staff: Keiichirō Saitō – director",
+                "Nested paragraph",
+            ]
+        );
+        assert_eq!(
+            article.images,
+            vec![
+                "https://example.com/key-visual.jpg?w=1200&h=675",
+                "https://example.com/single-quoted.jpg",
+            ]
+        );
+        assert_eq!(article.url, "https://www.crunchyroll.com/news/frieren")
+    }
+
+    #[test]
+    fn news_article_parse_without_article() {
+        // the title falls back to the first heading if the page has no og:title
+        let article = NewsArticle::parse("<h1>Title</h1><p>Text</p>", "");
+
+        assert_eq!(article.title, "Title");
+        assert_eq!(article.paragraphs, vec!["Text"])
+    }
+
+    #[test]
+    fn html_tags_names() {
+        let html = r#"<p>a</p><pre>b</pre><p class="x">c</p><picture></picture>"#;
+
+        assert_eq!(html_tags(html, "p"), vec!["<p>", r#"<p class="x">"#])
+    }
+
+    #[test]
+    fn html_elements_nested() {
+        let html = "<div>outer <div>inner</div> rest</div><div>second</div>";
+
+        assert_eq!(
+            html_elements(html, "div"),
+            vec!["outer <div>inner</div> rest", "inner", "second"]
+        )
+    }
+
+    #[test]
+    fn html_elements_malformed() {
+        // unclosed elements and unterminated tags are skipped
+        assert_eq!(html_elements("<p>unclosed", "p"), Vec::<String>::new());
+        assert_eq!(
+            html_elements("<p>closed</p><p unterminated", "p"),
+            vec!["closed"]
+        );
+        assert_eq!(
+            html_elements("<p>text<p>nested unclosed</p>", "p"),
+            vec!["nested unclosed"]
+        );
+        assert!(html_tags("<img src=\"a.jpg", "img").is_empty())
+    }
+
+    #[test]
+    fn html_attribute_values() {
+        let tag = r#"<img data-src="lazy.jpg" alt="a > b"  src = 'image.jpg'>"#;
+
+        assert_eq!(html_attribute(tag, "src").as_deref(), Some("image.jpg"));
+        assert_eq!(html_attribute(tag, "alt").as_deref(), Some("a > b"));
+        assert_eq!(html_attribute(tag, "title"), None);
+        assert_eq!(html_attribute(r#"<img src=unquoted>"#, "src"), None)
+    }
+
+    #[test]
+    fn html_text_decode() {
+        assert_eq!(
+            html_text("<b>Tom&amp;Jerry</b>\n  &#8220;quoted&#x201D; &unknown; &amp"),
+            "Tom&Jerry “quoted” &unknown; &amp"
+        )
+    }
+}
@@ -0,0 +1,24 @@
+//! Games of the Crunchyroll Game Vault.
+
+use crate::common::V2BulkResult;
+use crate::feed::GameFeed;
+use crate::{Crunchyroll, Result};
+
+impl Crunchyroll {
+    /// Returns all games which are available in the Game Vault. Game Vault games are included in
+    /// premium memberships and are played via the dedicated apps, [`GameFeed::link`] links to the
+    /// store page of the game.
+    ///
+    /// The `/content/v2/discover/games` endpoint this uses is undocumented and wasn't verified
+    /// against every region, so it may change or be unavailable without notice.
+    pub async fn games(&self) -> Result<Vec<GameFeed>> {
+        let endpoint = "https://www.crunchyroll.com/content/v2/discover/games";
+        Ok(self
+            .executor
+            .get(endpoint)
+            .apply_locale_query()
+            .request::<V2BulkResult<GameFeed>>()
+            .await?
+            .data)
+    }
+}
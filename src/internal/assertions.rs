@@ -0,0 +1,96 @@
+//! Compile time checks that the public types can be shared / moved between threads. Adding a field
+//! which isn't `Send` or `Sync` to one of the types breaks the build here instead of in the code of
+//! multi-threaded consumers.
+
+use crate::account::{Account, Subscription};
+use crate::categories::CategoryInformation;
+use crate::common::{Cancellation, Image, Pagination};
+use crate::crunchyroll::{
+    BasicAuthToken, BlockMitigation, CrunchyrollBuilder, DeviceCodeLogin, Endpoints, ProxyConfig,
+    RateLimitRetry, RateLimiter, SessionToken,
+};
+use crate::devices::Device;
+use crate::error::{ApiErrorCode, Error, ParseWarnings};
+use crate::feed::{HomeFeed, NewsFeed, NewsFeedResult};
+use crate::list::{Crunchylist, Crunchylists, WatchHistoryEntry, WatchlistEntry};
+use crate::media::{
+    Artist, EpisodeDownloader, ExtraVideo, MediaFilter, MediaStream, PlayheadInformation,
+    RollingThroughput, SkipEvents, Stream, StreamData, StreamSegment, StreamSession,
+    StreamSessionGuard, Subtitle, WatchProgressTracker,
+};
+use crate::pool::CrunchyrollPool;
+use crate::profile::Profiles;
+use crate::search::QueryResults;
+use crate::{
+    Concert, Crunchyroll, Episode, Locale, MediaCollection, Movie, MovieListing, MusicVideo,
+    Season, Series,
+};
+
+const fn assert_send<T: Send>() {}
+const fn assert_send_sync<T: Send + Sync>() {}
+
+const _: () = {
+    assert_send_sync::<Crunchyroll>();
+    assert_send_sync::<CrunchyrollBuilder>();
+    assert_send_sync::<CrunchyrollPool<String>>();
+    assert_send_sync::<BasicAuthToken>();
+    assert_send_sync::<BlockMitigation>();
+    assert_send_sync::<DeviceCodeLogin>();
+    assert_send_sync::<Endpoints>();
+    assert_send_sync::<ProxyConfig>();
+    assert_send_sync::<RateLimiter>();
+    assert_send_sync::<RateLimitRetry>();
+    assert_send_sync::<SessionToken>();
+    assert_send_sync::<Locale>();
+
+    assert_send_sync::<Error>();
+    assert_send_sync::<ApiErrorCode>();
+    assert_send_sync::<ParseWarnings>();
+
+    assert_send_sync::<Series>();
+    assert_send_sync::<Season>();
+    assert_send_sync::<Episode>();
+    assert_send_sync::<ExtraVideo>();
+    assert_send_sync::<MovieListing>();
+    assert_send_sync::<Movie>();
+    assert_send_sync::<MusicVideo>();
+    assert_send_sync::<Concert>();
+    assert_send_sync::<Artist>();
+    assert_send_sync::<MediaCollection>();
+    assert_send_sync::<PlayheadInformation>();
+    assert_send_sync::<SkipEvents>();
+    assert_send_sync::<MediaFilter>();
+    assert_send_sync::<Image>();
+
+    assert_send_sync::<Stream>();
+    assert_send_sync::<StreamData>();
+    assert_send_sync::<StreamSession>();
+    assert_send_sync::<StreamSessionGuard>();
+    assert_send_sync::<MediaStream>();
+    assert_send_sync::<StreamSegment>();
+    assert_send_sync::<Subtitle>();
+    assert_send_sync::<WatchProgressTracker>();
+    assert_send_sync::<EpisodeDownloader>();
+    assert_send_sync::<RollingThroughput>();
+
+    assert_send_sync::<Account>();
+    assert_send_sync::<Subscription>();
+    assert_send_sync::<Profiles>();
+    assert_send_sync::<Device>();
+    assert_send_sync::<CategoryInformation>();
+    assert_send_sync::<HomeFeed>();
+    assert_send_sync::<NewsFeed>();
+    assert_send_sync::<Crunchylists>();
+    assert_send_sync::<Crunchylist>();
+    assert_send_sync::<WatchlistEntry>();
+    assert_send_sync::<WatchHistoryEntry>();
+    assert_send_sync::<Cancellation>();
+    #[cfg(feature = "testing")]
+    assert_send_sync::<crate::testing::MockTransport>();
+
+    // paginations are streams which are polled via a mutable reference, so they only have to be
+    // movable between threads
+    assert_send::<Pagination<Series>>();
+    assert_send::<QueryResults>();
+    assert_send::<NewsFeedResult>();
+};
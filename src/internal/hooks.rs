@@ -0,0 +1,45 @@
+use crate::crunchyroll::{Endpoints, RateLimitRetry, RateLimiter, SessionToken};
+use crate::error::{Error, ParseWarnings};
+use crate::media::ThroughputObserver;
+use reqwest::{Method, StatusCode, Url};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) type OnRequestFn = Arc<dyn Fn(&Method, &Url) + Send + Sync>;
+pub(crate) type OnResponseFn =
+    Arc<dyn Fn(&Method, &Url, Option<StatusCode>, Duration) + Send + Sync>;
+pub(crate) type OnTokenRotationFn = Arc<dyn Fn(&SessionToken) + Send + Sync>;
+pub(crate) type OnTokenStoreErrorFn = Arc<dyn Fn(&Error) + Send + Sync>;
+
+/// Callbacks and settings which are applied to every request the library makes.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_request: Option<OnRequestFn>,
+    pub(crate) on_response: Option<OnResponseFn>,
+    pub(crate) on_token_rotation: Option<OnTokenRotationFn>,
+    pub(crate) on_token_store_error: Option<OnTokenStoreErrorFn>,
+    /// Suppress tracking-style headers which official clients send but which aren't required to
+    /// use the api, and send do-not-track headers instead.
+    pub(crate) do_not_track: bool,
+    /// Value of the `Accept-Language` header which is sent on every request.
+    pub(crate) accept_language: Option<String>,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) rate_limit_retry: Option<RateLimitRetry>,
+    pub(crate) endpoints: Option<Endpoints>,
+    /// Tolerate unexpected `null` values in responses and record them in `parse_warnings` instead
+    /// of failing.
+    pub(crate) lenient_parsing: bool,
+    pub(crate) parse_warnings: ParseWarnings,
+    pub(crate) throughput_observer: Option<Arc<dyn ThroughputObserver>>,
+    #[cfg(feature = "testing")]
+    pub(crate) transport: Option<crate::testing::MockTransport>,
+    #[cfg(feature = "testing")]
+    pub(crate) recorder: Option<crate::testing::FixtureRecorder>,
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<hooks>")
+    }
+}
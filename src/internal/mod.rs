@@ -1,3 +1,5 @@
+mod assertions;
+pub(crate) mod hooks;
 pub(crate) mod serde;
 pub(crate) mod strict;
 #[cfg(feature = "tower")]
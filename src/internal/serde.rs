@@ -1,11 +1,12 @@
 use crate::account::Wallpaper;
 use crate::common::Image;
 use crate::error::Error;
-use crate::{Request, Result};
+use crate::{Locale, Request, Result};
 use chrono::Duration;
 use serde::de::{DeserializeOwned, Error as SerdeError};
 use serde::{Deserialize, Deserializer};
 use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::str::FromStr;
 
 #[derive(Request)]
@@ -154,10 +155,77 @@ where
     }
 }
 
+/// Checks if a raw locale is one of the sentinel values Crunchyroll uses when a locale is unknown or
+/// not set. Known sentinels are an empty string (`""`) and a lone colon (`":"`); whitespace around
+/// them is ignored.
+pub(crate) fn is_locale_sentinel(raw: &str) -> bool {
+    let trimmed = raw.trim();
+    trimmed.is_empty() || trimmed == ":"
+}
+
+/// Deserializes a locale and normalizes sentinel values (see [`is_locale_sentinel`]) to
+/// [`Locale::default`], the unknown locale (see [`Locale::is_unknown`]).
+pub(crate) fn deserialize_locale<'de, D>(deserializer: D) -> Result<Locale, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(deserialize_maybe_empty_locale(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes a locale to `None` if it's null or a sentinel value (see [`is_locale_sentinel`]).
+pub(crate) fn deserialize_maybe_empty_locale<'de, D>(
+    deserializer: D,
+) -> Result<Option<Locale>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Deserialize::deserialize(deserializer)?;
+    Ok(value
+        .filter(|raw| !is_locale_sentinel(raw))
+        .map(Locale::from))
+}
+
+/// Deserializes a list of locales and removes all sentinel values (see [`is_locale_sentinel`]).
+pub(crate) fn deserialize_locales<'de, D>(deserializer: D) -> Result<Vec<Locale>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<Vec<String>> = Deserialize::deserialize(deserializer)?;
+    Ok(value
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|raw| !is_locale_sentinel(raw))
+        .map(Locale::from)
+        .collect())
+}
+
+/// Deserializes a map with locales as keys and removes all entries whose key is a sentinel value
+/// (see [`is_locale_sentinel`]).
+pub(crate) fn deserialize_locale_map<'de, D, T>(
+    deserializer: D,
+) -> Result<HashMap<Locale, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let value: Option<HashMap<String, T>> = Deserialize::deserialize(deserializer)?;
+    Ok(value
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(raw, _)| !is_locale_sentinel(raw))
+        .map(|(raw, v)| (Locale::from(raw), v))
+        .collect())
+}
+
 pub(crate) fn deserialize_thumbnail_image<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<Vec<Image>, D::Error> {
-    let as_map = serde_json::Map::deserialize(deserializer)?;
+    let value = Value::deserialize(deserializer)?;
+    // an already flattened list, if the images were exported via serialization
+    if value.is_array() {
+        return serde_json::from_value(value).map_err(|e| serde::de::Error::custom(e.to_string()));
+    }
+    let as_map = Map::deserialize(value).map_err(|e| serde::de::Error::custom(e.to_string()))?;
 
     if let Some(thumbnail) = as_map.get("thumbnail") {
         Ok(serde_json::from_value::<Vec<Vec<Image>>>(thumbnail.clone())
@@ -178,7 +246,7 @@ pub(crate) fn deserialize_streams_link<'de, D: Deserializer<'de>>(
     Ok(as_string
         .trim_end_matches("/streams")
         .split('/')
-        .last()
+        .next_back()
         .ok_or_else(|| serde::de::Error::custom("cannot extract stream id"))?
         .to_string())
 }
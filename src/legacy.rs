@@ -0,0 +1,61 @@
+//! Access to the legacy cms at `beta-api.crunchyroll.com`.
+//!
+//! Crunchyroll moved its cms to `www.crunchyroll.com/content/v2` but some regions / accounts still
+//! rely on the old cms, which requires a signed bucket (`media_query`) to access media. Its
+//! responses are converted into the format of the current cms, so the same structs are used for
+//! both.
+
+use crate::{Crunchyroll, Episode, Movie, MovieListing, Request, Result, Season, Series};
+use serde_json::{Map, Value};
+
+macro_rules! impl_legacy_media {
+    ($($fn_name:ident, $media:ident, $path:literal);*) => {
+        impl Crunchyroll {
+            $(
+                #[doc = concat!("Requests a [`", stringify!($media), "`] by its id from the legacy cms.")]
+                pub async fn $fn_name(&self, id: impl AsRef<str>) -> Result<$media> {
+                    let endpoint = format!(
+                        "https://beta-api.crunchyroll.com/cms/v2/{}/{}/{}",
+                        self.executor.details.bucket,
+                        $path,
+                        id.as_ref()
+                    );
+                    let legacy: Map<String, Value> = self
+                        .executor
+                        .get(endpoint)
+                        .apply_media_query()
+                        .apply_locale_query()
+                        .request()
+                        .await?;
+                    let mut media: $media = serde_json::from_value(Value::Object(legacy_to_v2(legacy)))?;
+                    media.__set_executor(self.executor.clone()).await;
+                    Ok(media)
+                }
+            )*
+        }
+    }
+}
+
+impl_legacy_media! {
+    legacy_series, Series, "series";
+    legacy_season, Season, "seasons";
+    legacy_episode, Episode, "episodes";
+    legacy_movie_listing, MovieListing, "movie_listings";
+    legacy_movie, Movie, "movies"
+}
+
+/// Converts a legacy cms object into the format of the current cms. Both contain the same media
+/// fields, but the legacy cms has links (e.g. to the streams of an episode) in `__links__` and
+/// additional metadata fields like `__class__` or `__href__`.
+fn legacy_to_v2(mut legacy: Map<String, Value>) -> Map<String, Value> {
+    let streams_link = legacy
+        .get("__links__")
+        .and_then(|links| links.get("streams"))
+        .and_then(|streams| streams.get("href"))
+        .cloned();
+    if let Some(streams_link) = streams_link {
+        legacy.entry("streams_link").or_insert(streams_link);
+    }
+    legacy.retain(|key, _| !(key.starts_with("__") && key.ends_with("__")));
+    legacy
+}
@@ -122,6 +122,19 @@
 //! - **tower**: Enables the usage of a [tower](https://docs.rs/tower) compatible middleware.
 //! - **experimental-stabilizations**: Provides some functions to maybe fix broken api results. See
 //!   [Bugs](#bugs) for more information.
+//! - **legacy-cms**: Enables access to the legacy cms at `beta-api.crunchyroll.com`, which some
+//!   regions / accounts still require.
+//! - **dash-downloader**: Enables [`media::Stream::to_dash_downloader`] to download streams with
+//!   [dash-mpd](https://docs.rs/dash-mpd).
+//! - **tracing**: Emits [tracing](https://docs.rs/tracing) spans and events for requests, login /
+//!   session refreshes, stream data parsing and pagination.
+//! - **testing**: Enables [`testing::MockTransport`] and [`testing::fixtures`] to test code which
+//!   uses this crate without network access, and [`testing::FixtureRecorder`] to record api
+//!   responses for it.
+//! - **raw-json**: Keeps the raw json of [`Series`], [`Season`], [`Episode`], [`MovieListing`] and
+//!   [`Movie`] (available via e.g. [`Series::raw_json`]), to include it in bug reports. Only these
+//!   five types are covered, no other struct keeps its raw json. To get the raw responses of other
+//!   requests, record them with [`testing::FixtureRecorder`] (requires the **testing** feature).
 //!
 //! # Implementation
 //! To ensure at least all existing parts of the library are working as expected, a special feature
@@ -140,13 +153,21 @@ pub mod crunchyroll;
 pub mod devices;
 pub mod error;
 pub mod feed;
+pub mod games;
+#[cfg(feature = "legacy-cms")]
+#[cfg_attr(docsrs, doc(cfg(feature = "legacy-cms")))]
+pub mod legacy;
 pub mod list;
 pub mod media;
 #[cfg(feature = "parse")]
 #[cfg_attr(docsrs, doc(cfg(feature = "parse")))]
 pub mod parse;
+pub mod pool;
 pub mod profile;
 pub mod search;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
 
 // internal
 mod internal;
@@ -26,6 +26,12 @@ pub struct WatchHistoryEntry {
     pub panel: MediaCollection,
 }
 
+impl AsRef<MediaCollection> for WatchHistoryEntry {
+    fn as_ref(&self) -> &MediaCollection {
+        &self.panel
+    }
+}
+
 impl Crunchyroll {
     /// Get the history which episodes / movies you've watched.
     pub fn watch_history(&self) -> Pagination<WatchHistoryEntry> {
@@ -1,5 +1,6 @@
 use crate::common::V2BulkResult;
 use crate::error::Error;
+use crate::media::MediaType;
 use crate::{
     enum_values, options, Crunchyroll, EmptyJsonProxy, Executor, MediaCollection, Request, Result,
 };
@@ -30,6 +31,12 @@ pub struct WatchlistEntry {
     pub panel: MediaCollection,
 }
 
+impl AsRef<MediaCollection> for WatchlistEntry {
+    fn as_ref(&self) -> &MediaCollection {
+        &self.panel
+    }
+}
+
 impl WatchlistEntry {
     /// Mark this entry as favorite on your watchlist. The argument this function takes, says if the
     /// entry should be marked (`true`) or unmarked (`false`) as favorite.
@@ -155,21 +162,184 @@ impl Crunchyroll {
             .await?
             .data)
     }
+
+    /// Returns the entries of your watchlist which are marked as favorite. Unlike filtering the
+    /// result of [`Crunchyroll::watchlist`], the favorites are selected by Crunchyroll, so the
+    /// sorting and order of `options` apply to the favorites only.
+    pub async fn favorites(&self, options: WatchlistOptions) -> Result<Vec<WatchlistEntry>> {
+        self.watchlist(options.only_favorites(true)).await
+    }
+}
+
+/// A watchlist entry as exported by [`Crunchyroll::export_watchlist`]. Besides the data which is
+/// required to restore the entry via [`Crunchyroll::import_watchlist`], it contains the title and
+/// type of the media to keep exports human-readable.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WatchlistExportEntry {
+    /// Id of the series or movie listing.
+    pub id: String,
+    pub title: String,
+    pub media_type: MediaType,
+    pub is_favorite: bool,
+}
+
+/// How [`Crunchyroll::import_watchlist`] handles entries which are already in the watchlist.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum WatchlistImportConflict {
+    /// Keep the existing entry as it is.
+    #[default]
+    Skip,
+    /// Overwrite the favorite state of the existing entry with the one of the imported entry.
+    Overwrite,
+}
+
+/// Result of [`Crunchyroll::import_watchlist`]. Contains the ids of the imported entries, grouped by
+/// what happened to them.
+#[derive(Clone, Debug, Default)]
+pub struct WatchlistImportSummary {
+    /// Entries which weren't in the watchlist and got added.
+    pub added: Vec<String>,
+    /// Entries which were already in the watchlist and whose favorite state got overwritten.
+    pub updated: Vec<String>,
+    /// Entries which were already in the watchlist and stayed untouched.
+    pub skipped: Vec<String>,
+}
+
+/// Number of entries which are requested per page when exporting a watchlist.
+const EXPORT_PAGE_SIZE: usize = 100;
+/// Maximal number of ids which are checked in a single request when importing a watchlist.
+const IMPORT_BATCH_SIZE: usize = 50;
+
+impl Crunchyroll {
+    /// Export your watchlist, e.g. to back it up or to migrate it to another account with
+    /// [`Crunchyroll::import_watchlist`]. The result can be serialized with serde. Entries which are
+    /// neither a series nor a movie listing can't be imported and are skipped.
+    pub async fn export_watchlist(&self) -> Result<Vec<WatchlistExportEntry>> {
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/discover/{}/watchlist",
+            self.executor.details.account_id.clone()?
+        );
+        // the default options only return entries which aren't favorites
+        let options = WatchlistOptions {
+            only_favorites: None,
+            ..Default::default()
+        };
+
+        let mut entries = vec![];
+        loop {
+            let page = self
+                .executor
+                .get(&endpoint)
+                .query(&options.clone().into_query())
+                .query(&[("n", EXPORT_PAGE_SIZE), ("start", entries.len())])
+                .apply_locale_query()
+                .request::<V2BulkResult<WatchlistEntry>>()
+                .await?;
+            let page_len = page.data.len();
+            entries.extend(page.data);
+            if page_len < EXPORT_PAGE_SIZE || entries.len() >= page.total as usize {
+                break;
+            }
+        }
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| {
+                let (id, title, media_type) = match entry.panel {
+                    MediaCollection::Series(series) => (series.id, series.title, MediaType::Series),
+                    MediaCollection::MovieListing(movie_listing) => {
+                        (movie_listing.id, movie_listing.title, MediaType::Movie)
+                    }
+                    _ => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("skipping watchlist entry which is neither a series nor a movie listing");
+                        return None;
+                    }
+                };
+                Some(WatchlistExportEntry {
+                    id,
+                    title,
+                    media_type,
+                    is_favorite: entry.is_favorite,
+                })
+            })
+            .collect())
+    }
+
+    /// Import entries which were exported by [`Crunchyroll::export_watchlist`] into your watchlist.
+    /// `on_conflict` specifies how entries which are already in the watchlist are handled.
+    /// Existing entries are looked up in batches. Large watchlists may hit Crunchyroll's rate
+    /// limit, set [`crate::crunchyroll::CrunchyrollBuilder::rate_limit_retry`] to retry rate
+    /// limited requests and [`crate::crunchyroll::CrunchyrollBuilder::rate_limiter`] to space out
+    /// the requests.
+    pub async fn import_watchlist(
+        &self,
+        entries: Vec<WatchlistExportEntry>,
+        on_conflict: WatchlistImportConflict,
+    ) -> Result<WatchlistImportSummary> {
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/{}/watchlist",
+            self.executor.details.account_id.clone()?
+        );
+        let mut summary = WatchlistImportSummary::default();
+
+        for batch in entries.chunks(IMPORT_BATCH_SIZE) {
+            let ids = batch
+                .iter()
+                .map(|entry| entry.id.as_str())
+                .collect::<Vec<&str>>()
+                .join(",");
+            let existing = self
+                .executor
+                .get(&endpoint)
+                .query(&[("content_ids", &ids)])
+                .apply_locale_query()
+                .request::<V2BulkResult<SimpleWatchlistEntry>>()
+                .await?
+                .data;
+
+            for entry in batch {
+                match existing.iter().find(|e| e.id == entry.id) {
+                    None => {
+                        add_to_watchlist(&self.executor, &entry.id).await?;
+                        if entry.is_favorite {
+                            mark_favorite_watchlist(&self.executor, entry.id.clone(), true).await?
+                        }
+                        summary.added.push(entry.id.clone())
+                    }
+                    Some(existing)
+                        if on_conflict == WatchlistImportConflict::Overwrite
+                            && existing.is_favorite != entry.is_favorite =>
+                    {
+                        mark_favorite_watchlist(
+                            &self.executor,
+                            entry.id.clone(),
+                            entry.is_favorite,
+                        )
+                        .await?;
+                        summary.updated.push(entry.id.clone())
+                    }
+                    Some(_) => summary.skipped.push(entry.id.clone()),
+                }
+            }
+        }
+
+        Ok(summary)
+    }
 }
 
 macro_rules! add_to_watchlist {
-    ($(#[doc = $add:literal] #[doc = $as:literal] $s:path);*) => {
+    ($(#[doc = $add:literal] #[doc = $favorite:literal] #[doc = $as:literal] $s:path);*) => {
         $(
             impl $s {
                 #[doc = $add]
                 pub async fn add_to_watchlist(&self) -> Result<()> {
-                    let endpoint = format!("https://www.crunchyroll.com/content/v2/{}/watchlist", self.executor.details.account_id.clone()?);
-                    let _: EmptyJsonProxy = self.executor.post(endpoint)
-                        .json(&json!({"content_id": &self.id}))
-                        .apply_locale_query()
-                        .request()
-                        .await?;
-                    Ok(())
+                    add_to_watchlist(&self.executor, &self.id).await
+                }
+
+                #[doc = $favorite]
+                pub async fn mark_favorite(&self, favorite: bool) -> Result<()> {
+                    mark_favorite_watchlist(&self.executor, self.id.clone(), favorite).await
                 }
 
                 #[doc = $as]
@@ -192,13 +362,29 @@ macro_rules! add_to_watchlist {
 
 add_to_watchlist! {
     #[doc = "Add this series to your watchlist."]
+    #[doc = "Mark (`true`) or unmark (`false`) this series as favorite on your watchlist. The series must be on your watchlist (see [`crate::media::Series::add_to_watchlist`])."]
     #[doc = "Check and convert this series to a watchlist entry (to check if this series was watched before)."]
     crate::media::Series;
     #[doc = "Add this movie to your watchlist."]
+    #[doc = "Mark (`true`) or unmark (`false`) this movie as favorite on your watchlist. The movie must be on your watchlist (see [`crate::media::MovieListing::add_to_watchlist`])."]
     #[doc = "Check and convert this movie to a watchlist entry (to check if this movie was watched before)."]
     crate::media::MovieListing
 }
 
+async fn add_to_watchlist(executor: &Arc<Executor>, id: &str) -> Result<()> {
+    let endpoint = format!(
+        "https://www.crunchyroll.com/content/v2/{}/watchlist",
+        executor.details.account_id.clone()?
+    );
+    executor
+        .post(endpoint)
+        .json(&json!({ "content_id": id }))
+        .apply_locale_query()
+        .request::<EmptyJsonProxy>()
+        .await?;
+    Ok(())
+}
+
 async fn mark_favorite_watchlist(
     executor: &Arc<Executor>,
     id: String,
@@ -1,9 +1,11 @@
-use crate::common::{Image, Request};
+use crate::common::{Image, Pagination, RawJson, Request};
 use crate::crunchyroll::Executor;
-use crate::media::anime::util::{fix_empty_episode_versions, fix_empty_season_versions};
+use crate::media::anime::util::{
+    fix_empty_episode_versions, fix_empty_season_versions, similar_to,
+};
 use crate::media::util::request_media;
 use crate::media::Media;
-use crate::{Crunchyroll, Locale, Result, Season, Series};
+use crate::{Crunchyroll, Locale, MediaCollection, Result, Season, Series};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -22,6 +24,8 @@ pub struct EpisodeVersion {
     #[serde(rename = "season_guid")]
     pub season_id: String,
 
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
 
     pub is_premium_only: bool,
@@ -53,6 +57,8 @@ impl EpisodeVersion {
 pub struct Episode {
     #[serde(skip)]
     pub(crate) executor: Arc<Executor>,
+    #[serde(skip)]
+    pub(crate) raw_json: RawJson,
 
     pub id: String,
     #[serde(alias = "streams_link")]
@@ -100,14 +106,21 @@ pub struct Episode {
     pub season_number: u32,
     pub season_display_number: String,
 
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
     /// Only populated if [`Episode`] got generated via [`Season::episodes`].
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub recent_audio_locale: Option<Locale>,
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub subtitle_locales: Vec<Locale>,
 
     /// Descriptors about the episode content, e.g. 'Violence' or 'Sexualized Imagery'.
     #[serde(default)]
-    pub content_descriptors: Vec<String>,
+    pub content_descriptors: Vec<crate::media::ContentDescriptor>,
 
     #[serde(alias = "duration_ms")]
     #[serde(deserialize_with = "crate::internal::serde::deserialize_millis_to_duration")]
@@ -140,6 +153,9 @@ pub struct Episode {
 
     pub is_mature: bool,
     pub maturity_ratings: Vec<String>,
+    /// Region specific maturity rating. Use [`Episode::is_age_restricted`] to check if the media is
+    /// suitable for a specific age.
+    pub extended_maturity_rating: crate::media::ExtendedMaturityRating,
     pub mature_blocked: bool,
 
     pub available_offline: bool,
@@ -173,8 +189,6 @@ pub struct Episode {
     #[serde(rename = "type")]
     _type: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
-    extended_maturity_rating: crate::StrictValue,
-    #[cfg(feature = "__test_strict")]
     tenant_categories: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
     available_date: crate::StrictValue,
@@ -192,7 +206,45 @@ pub struct Episode {
     recent_variant: Option<crate::StrictValue>,
 }
 
+/// Normalized episode metadata to populate a Discord rich presence or an os media session with.
+/// Obtained via [`Episode::presence_metadata`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PresenceMetadata {
+    pub title: String,
+    pub series_title: String,
+    pub season_title: String,
+    /// The displayed episode number, e.g. `5` or `5.5`. Might be empty for specials.
+    pub episode_number: String,
+    /// Url of the largest thumbnail of the episode.
+    pub image_url: Option<String>,
+    /// Url which opens the episode on the Crunchyroll website / in the Crunchyroll app.
+    pub url: String,
+}
+
 impl Episode {
+    /// Returns metadata of this episode which can be used to populate a Discord rich presence or an
+    /// os media session. Crunchyroll's own Discord integration is triggered by
+    /// [`Episode::set_playhead`], this is for apps which build their own presence.
+    pub fn presence_metadata(&self) -> PresenceMetadata {
+        let url = self.web_url();
+
+        PresenceMetadata {
+            title: self.title.clone(),
+            series_title: self.series_title.clone(),
+            season_title: self.season_title.clone(),
+            episode_number: self.episode.clone(),
+            image_url: crate::media::shared::largest(&self.images).map(|i| i.source.clone()),
+            url,
+        }
+    }
+
+    /// Checks if a viewer of the given age is too young to watch this episode. See
+    /// [`crate::media::ExtendedMaturityRating::min_age`] for how the age is determined.
+    pub fn is_age_restricted(&self, age: u8) -> bool {
+        self.extended_maturity_rating
+            .is_age_restricted(age, self.is_mature)
+    }
+
     /// Returns the series the episode belongs to.
     pub async fn series(&self) -> Result<Series> {
         let endpoint = format!(
@@ -204,6 +256,12 @@ impl Episode {
             .remove(0))
     }
 
+    /// Series or movie listings which are similar to the series this episode belongs to. Like
+    /// [`Series::similar`], but without requesting the series first.
+    pub fn similar(&self) -> Pagination<MediaCollection> {
+        similar_to(self.executor.clone(), self.series_id.clone())
+    }
+
     /// Returns the season the episode belongs to.
     pub async fn season(&self) -> Result<Season> {
         let endpoint = format!(
@@ -0,0 +1,97 @@
+use crate::common::{Request, V2BulkResult};
+use crate::crunchyroll::Executor;
+use crate::media::{Stream, StreamPlatform, ThumbnailImages};
+use crate::{enum_values, Crunchyroll, Locale, Result};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+enum_values! {
+    /// Type of an [`ExtraVideo`].
+    pub enum ExtraVideoType {
+        Trailer = "trailer"
+        Pv = "pv"
+        Teaser = "teaser"
+        Interview = "interview"
+        Special = "special"
+    }
+}
+
+/// Bonus video (e.g. a trailer, PV or interview) of a [`crate::Series`] or [`crate::Season`]. Get
+/// them via [`crate::Series::extras`] or [`crate::Season::extras`].
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, Serialize, smart_default::SmartDefault, Request)]
+#[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
+pub struct ExtraVideo {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
+    pub id: String,
+    #[serde(alias = "streams_link")]
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_streams_link")]
+    pub stream_id: String,
+
+    pub title: String,
+    pub slug_title: String,
+    pub description: String,
+
+    #[serde(alias = "extra_video_type")]
+    pub video_type: ExtraVideoType,
+
+    pub series_id: String,
+    /// Empty if the video belongs to the whole series.
+    pub season_id: String,
+
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
+    pub audio_locale: Locale,
+
+    #[serde(alias = "duration_ms")]
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_millis_to_duration")]
+    #[serde(serialize_with = "crate::internal::serde::serialize_duration_to_millis")]
+    #[default(Duration::try_milliseconds(0).unwrap())]
+    pub duration: Duration,
+
+    pub images: ThumbnailImages,
+
+    pub is_premium_only: bool,
+}
+
+impl ExtraVideo {
+    /// Streams for this video.
+    pub async fn stream(&self) -> Result<Stream> {
+        let id = if self.stream_id.is_empty() {
+            &self.id
+        } else {
+            &self.stream_id
+        };
+        Stream::from_id(
+            &Crunchyroll {
+                executor: self.executor.clone(),
+            },
+            id,
+            StreamPlatform::WebChrome,
+            None,
+        )
+        .await
+    }
+
+    /// Check if the video can be watched.
+    pub async fn available(&self) -> bool {
+        self.executor.premium().await || !self.is_premium_only
+    }
+}
+
+/// Requests the extra videos of the series or season which are listed at `endpoint`.
+pub(crate) async fn extra_videos(
+    executor: Arc<Executor>,
+    endpoint: String,
+) -> Result<Vec<ExtraVideo>> {
+    Ok(executor
+        .get(endpoint)
+        .apply_locale_query()
+        .request::<V2BulkResult<ExtraVideo>>()
+        .await?
+        .data)
+}
@@ -1,4 +1,4 @@
-use crate::common::{PaginationBulkResultMeta, Request};
+use crate::common::Request;
 use crate::macros::enum_values;
 use crate::media::Media;
 use crate::{Episode, MediaCollection, Movie, MovieListing, Result, Season, Series};
@@ -66,7 +66,7 @@ impl<'de> Deserialize<'de> for SkipEvents {
             let Some(obj) = as_map.get(object) else {
                 continue;
             };
-            if obj.as_object().map_or(false, |o| o.is_empty())
+            if obj.as_object().is_some_and(|o| o.is_empty())
                 // crunchyroll sometimes has a skip events, but it's lacking start or end times.
                 // this is just abstracted away since an event without a start or end doesn't make
                 // sense to be wrapped in e.g. an Option
@@ -107,7 +107,8 @@ pub struct RelatedMedia<T: Request + DeserializeOwned> {
     pub shortcut: Option<bool>,
 }
 
-/// Information about the playhead of an [`Episode`] or [`Movie`].
+/// Information about the playhead of an [`Episode`], [`Movie`], [`crate::MusicVideo`] or
+/// [`crate::Concert`].
 #[allow(dead_code)]
 #[derive(Clone, Debug, Deserialize, Serialize, smart_default::SmartDefault, Request)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
@@ -124,6 +125,17 @@ pub struct PlayheadInformation {
     pub last_modified: DateTime<Utc>,
 }
 
+/// Result of a playhead sync, e.g. via [`Episode::sync_playhead`].
+#[derive(Clone, Debug)]
+pub enum PlayheadSync {
+    /// The local playhead was newer than the remote one (or no remote playhead existed) and got
+    /// pushed.
+    Local,
+    /// The remote playhead is at least as new as the local one and was kept. Contains the remote
+    /// playhead, use it to update your local state.
+    Remote(PlayheadInformation),
+}
+
 enum_values! {
     /// Starts a rating can have. Crunchyroll does not use simple numbers which would be much easier
     /// to work with but own names for every star.
@@ -186,6 +198,8 @@ macro_rules! impl_manual_media_deserialize {
                     D: Deserializer<'de>,
                 {
                     let mut as_map = serde_json::Map::deserialize(deserializer)?;
+                    #[cfg(feature = "raw-json")]
+                    let raw_json = $crate::common::RawJson::new(serde_json::Value::Object(as_map.clone()));
 
                     if let Some(mut metadata) = as_map.remove($metadata) {
                         if let Some(object) = metadata.as_object_mut() {
@@ -195,12 +209,29 @@ macro_rules! impl_manual_media_deserialize {
                         }
                     }
 
-                    $media::deserialize(
+                    #[allow(unused_mut)]
+                    let mut media = $media::deserialize(
                         serde_json::to_value(as_map)
                             .map_err(|e| Error::custom(e.to_string()))?
                             .into_deserializer(),
                     )
-                    .map_err(|e| Error::custom(e.to_string()))
+                    .map_err(|e| Error::custom(e.to_string()))?;
+                    #[cfg(feature = "raw-json")]
+                    {
+                        media.raw_json = raw_json;
+                    }
+                    Ok(media)
+                }
+            }
+
+            #[cfg(feature = "raw-json")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "raw-json")))]
+            impl $media {
+                /// The raw json this struct was deserialized from. Useful for bug reports, as it
+                /// shows exactly what the api returned. `None` if the struct wasn't deserialized
+                /// from an api response.
+                pub fn raw_json(&self) -> Option<std::sync::Arc<serde_json::Value>> {
+                    self.raw_json.get()
                 }
             }
         )*
@@ -276,22 +307,7 @@ macro_rules! impl_media_video_collection {
             impl $media_video {
                 /// Similar series or movie listing to the current item.
                 pub fn similar(&self) -> $crate::common::Pagination<MediaCollection> {
-                    use futures_util::FutureExt;
-
-                    $crate::common::Pagination::new(|options| {
-                        async move {
-                            let endpoint = format!("https://www.crunchyroll.com/content/v2/discover/{}/similar_to/{}", options.executor.details.account_id.clone()?, options.extra.get("id").unwrap());
-                            let result: $crate::common::V2BulkResult<MediaCollection, PaginationBulkResultMeta> = options
-                                .executor
-                                .get(endpoint)
-                                .query(&[("n", options.page_size), ("start", options.start)])
-                                .apply_locale_query()
-                                .request()
-                                .await?;
-                            Ok(result.into())
-                        }
-                        .boxed()
-                    }, self.executor.clone(), None, Some(vec![("id", self.id.clone())]))
+                    $crate::media::anime::util::similar_to(self.executor.clone(), self.id.clone())
                 }
 
                 pub async fn rating(&self) -> Result<Rating> {
@@ -333,6 +349,17 @@ macro_rules! impl_media_video {
                     $crate::media::Stream::from_id(&$crate::Crunchyroll { executor: self.executor.clone() }, &self.id, $crate::media::StreamPlatform::WebChrome, None).await
                 }
 
+                /// Subtitles of this episode / movie. Crunchyroll has no endpoint which lists
+                /// subtitles without creating a playback session, so a stream is requested and
+                /// invalidated again right away. It counts against the stream limit only for the
+                /// duration of this call. Use [`Self::stream`] if you need closed captions too.
+                pub async fn subtitles(&self) -> Result<std::collections::HashMap<$crate::Locale, $crate::media::Subtitle>> {
+                    let stream = self.stream().await?;
+                    let subtitles = stream.subtitles.clone();
+                    stream.invalidate().await?;
+                    Ok(subtitles)
+                }
+
                 /// Check if the episode / movie can be watched.
                 pub async fn available(&self) -> bool {
                     self.executor.premium().await || !self.is_premium_only
@@ -392,17 +419,17 @@ macro_rules! impl_media_video {
                     }
                 }
 
+                /// The url of this episode / movie on crunchyroll.com, e.g. to share it. It can
+                /// be parsed back via [`crate::parse_url`].
+                pub fn web_url(&self) -> String {
+                    $crate::media::util::web_url("watch", &self.id, &self.slug_title)
+                }
+
                 /// Get playhead information.
                 pub async fn playhead(&self) -> Result<Option<PlayheadInformation>> {
-                    let endpoint = format!("https://www.crunchyroll.com/content/v2/{}/playheads", self.executor.details.account_id.clone()?);
-                    Ok(self.executor.get(endpoint)
-                        .query(&[("content_ids", &self.id)])
-                        .apply_locale_query()
-                        .request::<$crate::common::V2BulkResult<PlayheadInformation>>()
+                    Ok($crate::media::playback::playheads(&self.executor, &[&self.id])
                         .await?
-                        .data
-                        .get(0)
-                        .cloned())
+                        .remove(&self.id))
                 }
 
                 /// Set the playhead (current playback position) for this episode / movie. Used unit
@@ -410,10 +437,40 @@ macro_rules! impl_media_video {
                 /// integration so if you update the playhead and have Crunchyroll connected to
                 /// Discord, this episode / movie will be shown as your Discord status.
                 pub async fn set_playhead(&self, position: u32) -> Result<()> {
-                    let endpoint = format!("https://www.crunchyroll.com/content/v2/{}/playheads", self.executor.details.account_id.clone()?);
-                    self.executor.post(endpoint)
-                        .apply_locale_query()
-                        .json(&serde_json::json!({"content_id": &self.id, "playhead": position}))
+                    $crate::media::playback::set_playhead(&self.executor, &self.id, position, false).await
+                }
+
+                /// Sync the playhead with the remote one. The local playhead (`local_position` in
+                /// seconds, last updated at `local_timestamp`) is only pushed via
+                /// [`Self::set_playhead`] if it's newer than the remote playhead. Use this instead
+                /// of [`Self::set_playhead`] if the playhead might have been updated by another
+                /// device in the meantime, to not overwrite newer progress.
+                pub async fn sync_playhead(&self, local_position: u32, local_timestamp: DateTime<Utc>) -> Result<PlayheadSync> {
+                    if let Some(remote) = self.playhead().await? {
+                        if remote.last_modified >= local_timestamp {
+                            return Ok(PlayheadSync::Remote(remote))
+                        }
+                    }
+                    self.set_playhead(local_position).await?;
+                    Ok(PlayheadSync::Local)
+                }
+
+                /// Mark this episode / movie as fully watched. This sets the playhead to the end of
+                /// the episode / movie, so, like [`Self::set_playhead`], it's shown as your Discord
+                /// status if you have Crunchyroll connected to Discord.
+                pub async fn mark_watched(&self) -> Result<()> {
+                    let duration = self.duration.num_seconds().max(0) as u32;
+                    $crate::media::playback::set_playhead(&self.executor, &self.id, duration, true).await
+                }
+
+                /// Mark this episode / movie as not watched. This removes its playhead, so it starts
+                /// from the beginning the next time it is played. The playhead is removed by
+                /// sending a `DELETE` request to the playhead of this episode / movie; this
+                /// endpoint isn't publicly documented, the `episode_mark_watched_unwatched` test
+                /// checks that it still removes the playhead.
+                pub async fn mark_unwatched(&self) -> Result<()> {
+                    let endpoint = format!("https://www.crunchyroll.com/content/v2/{}/playheads/{}", self.executor.details.account_id.clone()?, &self.id);
+                    self.executor.delete(endpoint)
                         .request::<$crate::EmptyJsonProxy>()
                         .await?;
                     Ok(())
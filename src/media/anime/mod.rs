@@ -1,4 +1,5 @@
 mod episode;
+mod extra;
 mod r#impl;
 mod movie;
 mod movie_listing;
@@ -7,6 +8,7 @@ mod series;
 pub(crate) mod util;
 
 pub use episode::*;
+pub use extra::*;
 pub use movie::*;
 pub use movie_listing::*;
 pub use r#impl::*;
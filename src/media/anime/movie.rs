@@ -1,7 +1,9 @@
+use crate::common::{Pagination, RawJson};
 use crate::crunchyroll::Executor;
+use crate::media::anime::util::similar_to;
 use crate::media::util::request_media;
 use crate::media::{Media, ThumbnailImages};
-use crate::{Crunchyroll, MovieListing, Result};
+use crate::{Crunchyroll, MediaCollection, MovieListing, Result};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -15,6 +17,8 @@ use std::sync::Arc;
 pub struct Movie {
     #[serde(skip)]
     pub(crate) executor: Arc<Executor>,
+    #[serde(skip)]
+    pub(crate) raw_json: RawJson,
 
     pub id: String,
     #[serde(alias = "streams_link")]
@@ -43,7 +47,7 @@ pub struct Movie {
 
     /// Descriptors about the movie content, e.g. 'Violence' or 'Sexualized Imagery'.
     #[serde(default)]
-    pub content_descriptors: Vec<String>,
+    pub content_descriptors: Vec<crate::media::ContentDescriptor>,
 
     #[default(DateTime::< Utc >::from(std::time::SystemTime::UNIX_EPOCH))]
     pub free_available_date: DateTime<Utc>,
@@ -58,6 +62,9 @@ pub struct Movie {
 
     pub maturity_ratings: Vec<String>,
     pub is_mature: bool,
+    /// Region specific maturity rating. Use [`Movie::is_age_restricted`] to check if the media is
+    /// suitable for a specific age.
+    pub extended_maturity_rating: crate::media::ExtendedMaturityRating,
     pub mature_blocked: bool,
 
     pub available_offline: bool,
@@ -80,8 +87,6 @@ pub struct Movie {
     #[cfg(feature = "__test_strict")]
     promo_description: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
-    extended_maturity_rating: crate::StrictValue,
-    #[cfg(feature = "__test_strict")]
     available_date: crate::StrictValue,
     #[cfg(feature = "__test_strict")]
     availability_starts: Option<crate::StrictValue>,
@@ -92,6 +97,13 @@ pub struct Movie {
 }
 
 impl Movie {
+    /// Checks if a viewer of the given age is too young to watch this movie. See
+    /// [`crate::media::ExtendedMaturityRating::min_age`] for how the age is determined.
+    pub fn is_age_restricted(&self, age: u8) -> bool {
+        self.extended_maturity_rating
+            .is_age_restricted(age, self.is_mature)
+    }
+
     /// Returns the parent movie listing of this movie.
     pub async fn movie_listing(&self) -> Result<MovieListing> {
         let endpoint = format!(
@@ -102,6 +114,12 @@ impl Movie {
             .await?
             .remove(0))
     }
+
+    /// Series or movie listings which are similar to the movie listing this movie belongs to. Like
+    /// [`MovieListing::similar`], but without requesting the movie listing first.
+    pub fn similar(&self) -> Pagination<MediaCollection> {
+        similar_to(self.executor.clone(), self.movie_listing_id.clone())
+    }
 }
 
 #[async_trait::async_trait]
@@ -1,13 +1,53 @@
 use crate::categories::Category;
-use crate::common::Request;
+use crate::common::{RawJson, Request};
 use crate::crunchyroll::Executor;
-use crate::media::util::request_media;
+use crate::media::util::{request_media, request_media_with_locales};
 use crate::media::{Media, PosterImages};
 use crate::{Crunchyroll, Locale, Movie, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Options for [`MovieListing::movies_with`].
+#[derive(Clone, Debug)]
+pub struct MoviesOptions {
+    locale: Option<Locale>,
+    preferred_audio_locale: Option<Locale>,
+    include_mature: bool,
+}
+
+impl Default for MoviesOptions {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            preferred_audio_locale: None,
+            include_mature: true,
+        }
+    }
+}
+
+impl MoviesOptions {
+    /// Overwrite the locale which is set via [`crate::crunchyroll::CrunchyrollBuilder::locale`].
+    pub fn locale(mut self, locale: Locale) -> MoviesOptions {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Overwrite the preferred audio locale which is set via
+    /// [`crate::crunchyroll::CrunchyrollBuilder::preferred_audio_locale`].
+    pub fn preferred_audio_locale(mut self, preferred_audio_locale: Locale) -> MoviesOptions {
+        self.preferred_audio_locale = Some(preferred_audio_locale);
+        self
+    }
+
+    /// If mature movies should be included. The api has no such option, so all movies are
+    /// requested and the mature ones are filtered out afterwards. Default is `true`.
+    pub fn include_mature(mut self, include_mature: bool) -> MoviesOptions {
+        self.include_mature = include_mature;
+        self
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
@@ -18,6 +58,8 @@ pub struct MovieListingVersion {
     #[serde(rename = "guid")]
     pub id: String,
 
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
 
     pub original: bool,
@@ -48,6 +90,8 @@ impl MovieListingVersion {
 pub struct MovieListing {
     #[serde(skip)]
     pub(crate) executor: Arc<Executor>,
+    #[serde(skip)]
+    pub(crate) raw_json: RawJson,
 
     pub id: String,
     pub channel_id: String,
@@ -65,8 +109,13 @@ pub struct MovieListing {
 
     /// May be [`None`] if requested by some functions like [`Crunchyroll::browse`]. You might have
     /// to re-request it to get the audio locale. Crunchyroll :)
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub audio_locale: Option<Locale>,
     /// Sometimes empty, sometimes not. Not recommended to rely on this.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub subtitle_locales: Vec<Locale>,
 
     pub is_subbed: bool,
@@ -76,7 +125,7 @@ pub struct MovieListing {
 
     /// Descriptors about the movie listing content, e.g. 'Violence' or 'Sexualized Imagery'.
     #[serde(default)]
-    pub content_descriptors: Vec<String>,
+    pub content_descriptors: Vec<crate::media::ContentDescriptor>,
 
     #[serde(default)]
     pub keywords: Vec<String>,
@@ -96,6 +145,9 @@ pub struct MovieListing {
 
     pub maturity_ratings: Vec<String>,
     pub is_mature: bool,
+    /// Region specific maturity rating. Use [`MovieListing::is_age_restricted`] to check if the media is
+    /// suitable for a specific age.
+    pub extended_maturity_rating: crate::media::ExtendedMaturityRating,
     pub mature_blocked: bool,
 
     pub available_offline: bool,
@@ -106,8 +158,6 @@ pub struct MovieListing {
     /// All versions of this movie listing (same movie listing but each entry has a different language).
     pub versions: Vec<MovieListingVersion>,
 
-    #[cfg(feature = "__test_strict")]
-    extended_maturity_rating: crate::StrictValue,
     #[cfg(feature = "__test_strict")]
     identifier: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
@@ -144,13 +194,42 @@ pub struct MovieListing {
 }
 
 impl MovieListing {
+    /// Checks if a viewer of the given age is too young to watch this movie listing. See
+    /// [`crate::media::ExtendedMaturityRating::min_age`] for how the age is determined.
+    pub fn is_age_restricted(&self, age: u8) -> bool {
+        self.extended_maturity_rating
+            .is_age_restricted(age, self.is_mature)
+    }
+
+    /// The url of this movie listing on crunchyroll.com, e.g. to share it. It can be parsed back
+    /// via [`crate::parse_url`].
+    pub fn web_url(&self) -> String {
+        crate::media::util::web_url("movie_listing", &self.id, &self.slug_title)
+    }
+
     /// Returns all movies for this movie listing.
     pub async fn movies(&self) -> Result<Vec<Movie>> {
+        self.movies_with(MoviesOptions::default()).await
+    }
+
+    /// Returns all movies for this movie listing, requested with the given options.
+    pub async fn movies_with(&self, options: MoviesOptions) -> Result<Vec<Movie>> {
         let endpoint = format!(
             "https://www.crunchyroll.com/content/v2/cms/movie_listings/{}/movies",
             self.id
         );
-        request_media(self.executor.clone(), endpoint).await
+        let mut movies: Vec<Movie> = request_media_with_locales(
+            self.executor.clone(),
+            endpoint,
+            options.locale,
+            options.preferred_audio_locale,
+            &[],
+        )
+        .await?;
+        if !options.include_mature {
+            movies.retain(|m| !m.is_mature)
+        }
+        Ok(movies)
     }
 }
 
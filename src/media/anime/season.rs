@@ -1,13 +1,74 @@
-use crate::common::Request;
+use crate::common::{RawJson, Request};
 use crate::crunchyroll::Executor;
+use crate::media::anime::extra::extra_videos;
 use crate::media::anime::util::{fix_empty_episode_versions, fix_empty_season_versions};
-use crate::media::util::request_media;
-use crate::media::Media;
+use crate::media::util::{request_media, request_media_with_locales};
+use crate::media::{ExtraVideo, Media};
 use crate::{Crunchyroll, Episode, Locale, Result, Series};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Options for [`Season::episodes_with`].
+#[derive(Clone, Debug)]
+pub struct EpisodesOptions {
+    locale: Option<Locale>,
+    preferred_audio_locale: Option<Locale>,
+    include_versions: bool,
+    include_mature: bool,
+    fields: Vec<String>,
+}
+
+impl Default for EpisodesOptions {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            preferred_audio_locale: None,
+            include_versions: true,
+            include_mature: true,
+            fields: vec![],
+        }
+    }
+}
+
+impl EpisodesOptions {
+    /// Overwrite the locale which is set via [`crate::crunchyroll::CrunchyrollBuilder::locale`].
+    pub fn locale(mut self, locale: Locale) -> EpisodesOptions {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Overwrite the preferred audio locale which is set via
+    /// [`crate::crunchyroll::CrunchyrollBuilder::preferred_audio_locale`].
+    pub fn preferred_audio_locale(mut self, preferred_audio_locale: Locale) -> EpisodesOptions {
+        self.preferred_audio_locale = Some(preferred_audio_locale);
+        self
+    }
+
+    /// If the versions of the episodes should be included. If `false`, the versions of every
+    /// returned episode are empty. The api has no such option, so the versions are still part of the
+    /// response and only removed afterwards. Default is `true`.
+    pub fn include_versions(mut self, include_versions: bool) -> EpisodesOptions {
+        self.include_versions = include_versions;
+        self
+    }
+
+    /// If mature episodes should be included. The api has no such option, so all episodes are
+    /// requested and the mature ones are filtered out afterwards. Default is `true`.
+    pub fn include_mature(mut self, include_mature: bool) -> EpisodesOptions {
+        self.include_mature = include_mature;
+        self
+    }
+
+    /// Only request the given fields of the episodes (e.g. `["id", "title"]`) to reduce the response
+    /// size. All other fields have their default value. Default is to request all fields.
+    pub fn fields<S: AsRef<str>>(mut self, fields: &[S]) -> EpisodesOptions {
+        self.fields = fields.iter().map(|f| f.as_ref().to_string()).collect();
+        self
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, smart_default::SmartDefault)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
@@ -37,6 +98,8 @@ pub struct SeasonVersion {
     #[serde(rename = "guid")]
     pub id: String,
 
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
 
     pub original: bool,
@@ -69,6 +132,8 @@ impl SeasonVersion {
 pub struct Season {
     #[serde(skip)]
     pub(crate) executor: Arc<Executor>,
+    #[serde(skip)]
+    pub(crate) raw_json: RawJson,
 
     pub id: String,
     pub series_id: String,
@@ -93,22 +158,31 @@ pub struct Season {
     pub season_tags: Vec<String>,
     /// Descriptors about the season episodes' content, e.g. 'Violence' or 'Sexualized Imagery'.
     #[serde(default)]
-    pub content_descriptors: Vec<String>,
+    pub content_descriptors: Vec<crate::media::ContentDescriptor>,
 
     pub is_subbed: bool,
     pub is_dubbed: bool,
     pub is_simulcast: bool,
-    #[serde(skip_serializing)]
+    #[serde(
+        skip_serializing,
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     audio_locale: Option<Locale>,
     /// Most of the time, like 99%, this contains only one locale. But sometimes Crunchyroll does
     /// weird stuff and marks a season which clearly has only one locale with two locales. See
     /// [this](https://github.com/crunchy-labs/crunchy-cli/issues/81#issuecomment-1351813787) issue
     /// comment for an example.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub audio_locales: Vec<Locale>,
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub subtitle_locales: Vec<Locale>,
 
     pub maturity_ratings: Vec<String>,
     pub is_mature: bool,
+    /// Region specific maturity rating. Use [`Season::is_age_restricted`] to check if the media is
+    /// suitable for a specific age.
+    pub extended_maturity_rating: crate::media::ExtendedMaturityRating,
     pub mature_blocked: bool,
 
     /// If the season is not available this might contain some information why.
@@ -124,14 +198,19 @@ pub struct Season {
     #[cfg(feature = "__test_strict")]
     season_display_number: crate::StrictValue,
     #[cfg(feature = "__test_strict")]
-    extended_maturity_rating: crate::StrictValue,
-    #[cfg(feature = "__test_strict")]
     seo_title: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
     seo_description: Option<crate::StrictValue>,
 }
 
 impl Season {
+    /// Checks if a viewer of the given age is too young to watch this season. See
+    /// [`crate::media::ExtendedMaturityRating::min_age`] for how the age is determined.
+    pub fn is_age_restricted(&self, age: u8) -> bool {
+        self.extended_maturity_rating
+            .is_age_restricted(age, self.is_mature)
+    }
+
     /// Returns the series the season belongs to.
     pub async fn series(&self) -> Result<Series> {
         let endpoint = format!(
@@ -143,18 +222,80 @@ impl Season {
             .remove(0))
     }
 
+    /// Returns the ids of all [`Season::versions`], mapped by their audio locale. Use this to get
+    /// the id of a dubbed season without requesting the full season via [`SeasonVersion::season`].
+    pub fn version_ids(&self) -> HashMap<Locale, String> {
+        self.versions
+            .iter()
+            .map(|v| (v.audio_locale.clone(), v.id.clone()))
+            .collect()
+    }
+
+    /// Get the extra videos (trailers, PVs, interviews, ...) of this season.
+    pub async fn extras(&self) -> Result<Vec<ExtraVideo>> {
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/cms/seasons/{}/extra_videos",
+            self.id
+        );
+        extra_videos(self.executor.clone(), endpoint).await
+    }
+
     /// Returns all episodes of this season.
     pub async fn episodes(&self) -> Result<Vec<Episode>> {
+        self.episodes_with(EpisodesOptions::default()).await
+    }
+
+    /// Returns all episodes of this season, requested with the given options.
+    pub async fn episodes_with(&self, options: EpisodesOptions) -> Result<Vec<Episode>> {
         let endpoint = format!(
             "https://www.crunchyroll.com/content/v2/cms/seasons/{}/episodes",
             self.id
         );
-        let mut episodes: Vec<Episode> = request_media(self.executor.clone(), endpoint).await?;
+        let mut episodes: Vec<Episode> = request_media_with_locales(
+            self.executor.clone(),
+            endpoint,
+            options.locale,
+            options.preferred_audio_locale,
+            &options.fields,
+        )
+        .await?;
+        if !options.include_mature {
+            episodes.retain(|e| !e.is_mature)
+        }
         for episode in &mut episodes {
-            fix_empty_episode_versions(episode);
+            if options.include_versions {
+                fix_empty_episode_versions(episode);
+            } else {
+                episode.versions.clear()
+            }
         }
         Ok(episodes)
     }
+
+    /// Returns all episodes of this season which have the given audio locale. If this season
+    /// doesn't have the audio locale but one of its [`Season::versions`] does, the episodes of this
+    /// version are returned. Returns an empty list if no version has the audio locale.
+    pub async fn episodes_with_audio(&self, audio_locale: Locale) -> Result<Vec<Episode>> {
+        let version_season = if self.audio_locales.contains(&audio_locale) {
+            None
+        } else {
+            let Some(version) = self
+                .versions
+                .iter()
+                .find(|v| v.audio_locale == audio_locale && v.id != self.id)
+            else {
+                return Ok(vec![]);
+            };
+            Some(version.season().await?)
+        };
+        let season = version_season.as_ref().unwrap_or(self);
+
+        let mut episodes = season
+            .episodes_with(EpisodesOptions::default().preferred_audio_locale(audio_locale.clone()))
+            .await?;
+        episodes.retain(|e| e.audio_locale == audio_locale);
+        Ok(episodes)
+    }
 }
 
 #[async_trait::async_trait]
@@ -1,13 +1,77 @@
 use crate::categories::Category;
+use crate::common::RawJson;
 use crate::crunchyroll::Executor;
+use crate::media::anime::extra::extra_videos;
 use crate::media::anime::util::fix_empty_season_versions;
-use crate::media::util::request_media;
-use crate::media::{Media, PosterImages};
-use crate::{Crunchyroll, Locale, MusicVideo, Result, Season};
+use crate::media::util::{request_media, request_media_with_locales};
+use crate::media::{ExtraVideo, Media, PlayheadInformation, PosterImages};
+use crate::{Crunchyroll, Episode, Locale, MusicVideo, Result, Season};
 use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::iter;
 use std::sync::Arc;
 
+/// Options for [`Series::seasons_with`].
+#[derive(Clone, Debug)]
+pub struct SeasonsOptions {
+    locale: Option<Locale>,
+    preferred_audio_locale: Option<Locale>,
+    include_versions: bool,
+    include_mature: bool,
+    fields: Vec<String>,
+}
+
+impl Default for SeasonsOptions {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            preferred_audio_locale: None,
+            include_versions: true,
+            include_mature: true,
+            fields: vec![],
+        }
+    }
+}
+
+impl SeasonsOptions {
+    /// Overwrite the locale which is set via [`crate::crunchyroll::CrunchyrollBuilder::locale`].
+    pub fn locale(mut self, locale: Locale) -> SeasonsOptions {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Overwrite the preferred audio locale which is set via
+    /// [`crate::crunchyroll::CrunchyrollBuilder::preferred_audio_locale`].
+    pub fn preferred_audio_locale(mut self, preferred_audio_locale: Locale) -> SeasonsOptions {
+        self.preferred_audio_locale = Some(preferred_audio_locale);
+        self
+    }
+
+    /// If the versions of the seasons should be included. If `false`, the versions of every
+    /// returned season are empty. The api has no such option, so the versions are still part of the
+    /// response and only removed afterwards. Default is `true`.
+    pub fn include_versions(mut self, include_versions: bool) -> SeasonsOptions {
+        self.include_versions = include_versions;
+        self
+    }
+
+    /// If mature seasons should be included. The api has no such option, so all seasons are
+    /// requested and the mature ones are filtered out afterwards. Default is `true`.
+    pub fn include_mature(mut self, include_mature: bool) -> SeasonsOptions {
+        self.include_mature = include_mature;
+        self
+    }
+
+    /// Only request the given fields of the seasons (e.g. `["id", "title"]`) to reduce the response
+    /// size. All other fields have their default value. Default is to request all fields.
+    pub fn fields<S: AsRef<str>>(mut self, fields: &[S]) -> SeasonsOptions {
+        self.fields = fields.iter().map(|f| f.as_ref().to_string()).collect();
+        self
+    }
+}
+
 /// Information about a series which was nominated for the Crunchyroll Anime Awards.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
@@ -40,6 +104,19 @@ pub struct SeriesLivestream {
     pub episode_end_date: DateTime<Utc>,
 }
 
+/// All dubs of a logical season of a series. Obtained via [`Series::seasons_grouped`].
+#[derive(Clone, Debug, Default)]
+pub struct SeasonGroup {
+    /// The season number, taken from [`Season::identifier`] (or [`Season::season_number`] if the
+    /// identifier contains no season number) of the original version.
+    pub season_number: u32,
+    pub season_sequence_number: u32,
+    /// Title of the original version of the season.
+    pub title: String,
+    /// The seasons of this group, mapped by their audio locales.
+    pub seasons: HashMap<Locale, Season>,
+}
+
 /// Metadata for a series.
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -49,6 +126,8 @@ pub struct SeriesLivestream {
 pub struct Series {
     #[serde(skip)]
     pub(crate) executor: Arc<Executor>,
+    #[serde(skip)]
+    pub(crate) raw_json: RawJson,
 
     pub id: String,
     pub channel_id: String,
@@ -73,14 +152,16 @@ pub struct Series {
     pub season_tags: Vec<String>,
     /// Descriptors about the series episodes' content, e.g. 'Violence' or 'Sexualized Imagery'.
     #[serde(default)]
-    pub content_descriptors: Vec<String>,
+    pub content_descriptors: Vec<crate::media::ContentDescriptor>,
 
     pub is_subbed: bool,
     pub is_dubbed: bool,
     pub is_simulcast: bool,
     /// Might be empty. Some series have this field populated with locales, others not.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub audio_locales: Vec<Locale>,
     /// Might be empty. Some series have this field populated with locales, others not.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locales")]
     pub subtitle_locales: Vec<Locale>,
 
     pub images: PosterImages,
@@ -94,6 +175,9 @@ pub struct Series {
 
     pub maturity_ratings: Vec<String>,
     pub is_mature: bool,
+    /// Region specific maturity rating. Use [`Series::is_age_restricted`] to check if the media is
+    /// suitable for a specific age.
+    pub extended_maturity_rating: crate::media::ExtendedMaturityRating,
     pub mature_blocked: bool,
 
     pub availability_notes: String,
@@ -103,8 +187,6 @@ pub struct Series {
     /// Information about the livestream of an episode. The livestream may be already over.
     pub livestream: Option<SeriesLivestream>,
 
-    #[cfg(feature = "__test_strict")]
-    extended_maturity_rating: crate::StrictValue,
     #[cfg(feature = "__test_strict")]
     external_id: Option<crate::StrictValue>,
     #[cfg(feature = "__test_strict")]
@@ -131,19 +213,190 @@ pub struct Series {
 }
 
 impl Series {
+    /// Checks if a viewer of the given age is too young to watch this series. See
+    /// [`crate::media::ExtendedMaturityRating::min_age`] for how the age is determined.
+    pub fn is_age_restricted(&self, age: u8) -> bool {
+        self.extended_maturity_rating
+            .is_age_restricted(age, self.is_mature)
+    }
+
     /// Returns all series seasons.
     pub async fn seasons(&self) -> Result<Vec<Season>> {
+        self.seasons_with(SeasonsOptions::default()).await
+    }
+
+    /// Returns all series seasons, requested with the given options.
+    pub async fn seasons_with(&self, options: SeasonsOptions) -> Result<Vec<Season>> {
         let endpoint = format!(
             "https://www.crunchyroll.com/content/v2/cms/series/{}/seasons",
             self.id
         );
-        let mut seasons: Vec<Season> = request_media(self.executor.clone(), endpoint).await?;
+        let mut seasons: Vec<Season> = request_media_with_locales(
+            self.executor.clone(),
+            endpoint,
+            options.locale,
+            options.preferred_audio_locale,
+            &options.fields,
+        )
+        .await?;
+        if !options.include_mature {
+            seasons.retain(|s| !s.is_mature)
+        }
         for season in &mut seasons {
-            fix_empty_season_versions(season);
+            if options.include_versions {
+                fix_empty_season_versions(season);
+            } else {
+                season.versions.clear()
+            }
+        }
+        Ok(seasons)
+    }
+
+    /// Returns the episodes of all series seasons. The seasons are requested concurrently (at most
+    /// 4 at the same time) and the episodes are ordered by [`Season::season_sequence_number`] and
+    /// [`Episode::sequence_number`]. Which seasons / episodes are returned depends on
+    /// [`crate::crunchyroll::CrunchyrollBuilder::preferred_audio_locale`], like it does for
+    /// [`Series::seasons`] and [`Season::episodes`].
+    pub async fn episodes(&self) -> Result<Vec<Episode>> {
+        let mut seasons = self.seasons().await?;
+        seasons.sort_by_key(|s| s.season_sequence_number);
+
+        let season_episodes: Vec<Vec<Episode>> = futures_util::stream::iter(&seasons)
+            .map(|season| season.episodes())
+            .buffered(4)
+            .try_collect()
+            .await?;
+
+        let mut episodes = vec![];
+        for mut season_episodes in season_episodes {
+            season_episodes.sort_by(|a, b| a.sequence_number.total_cmp(&b.sequence_number));
+            episodes.extend(season_episodes)
+        }
+        Ok(episodes)
+    }
+
+    /// The url of this series on crunchyroll.com, e.g. to share it. It can be parsed back via
+    /// [`crate::parse_url`].
+    pub fn web_url(&self) -> String {
+        crate::media::util::web_url("series", &self.id, &self.slug_title)
+    }
+
+    /// Returns the episode which should be watched next, like the "Continue" button of the official
+    /// clients does. This is the most recently watched episode if it isn't fully watched yet,
+    /// otherwise the episode after it (ordered like [`Series::episodes`]). The playheads of all
+    /// [`Episode::versions`] count, so progress made in another dub is respected, and if
+    /// [`Series::episodes`] contains multiple dubs of an episode only the first one is considered.
+    /// If no episode was watched yet, the first episode is returned. Returns [`None`] if the last
+    /// episode of the series is fully watched or the series has no episodes.
+    pub async fn next_unwatched(&self) -> Result<Option<Episode>> {
+        let mut episodes = dedup_episode_versions(self.episodes().await?);
+        let mut ids: Vec<&str> = episodes
+            .iter()
+            .flat_map(|e| iter::once(e.id.as_str()).chain(e.versions.iter().map(|v| v.id.as_str())))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let playheads = crate::media::playback::playheads(&self.executor, &ids).await?;
+
+        match next_unwatched_index(&episodes, &playheads) {
+            Some(next) => Ok(Some(episodes.swap_remove(next))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all seasons of this series, including the ones which are only reachable via
+    /// [`Season::versions`], mapped by their audio locales. Every season appears only once per
+    /// locale and the seasons of each locale are sorted by [`Season::season_sequence_number`].
+    /// This requires one additional request for every season version which isn't returned by
+    /// [`Series::seasons`].
+    pub async fn audio_locales_deep(&self) -> Result<HashMap<Locale, Vec<Season>>> {
+        let mut result: HashMap<Locale, Vec<Season>> = HashMap::new();
+        for season in self.seasons_deep().await? {
+            for audio_locale in &season.audio_locales {
+                result
+                    .entry(audio_locale.clone())
+                    .or_default()
+                    .push(season.clone())
+            }
+        }
+        for seasons in result.values_mut() {
+            seasons.sort_by_key(|s| s.season_sequence_number)
+        }
+        Ok(result)
+    }
+
+    /// Returns all seasons of this series grouped by their logical season. Crunchyroll has a
+    /// separate [`Season`] for every dub of a season, every [`SeasonGroup`] contains all of these
+    /// seasons mapped by their audio locales. Seasons are grouped by their [`Season::versions`].
+    /// Seasons without versions are grouped by the season number in [`Season::identifier`] and
+    /// join the group of the seasons with versions which have the same number. The groups are
+    /// sorted by [`SeasonGroup::season_sequence_number`]. Like [`Series::audio_locales_deep`] this
+    /// requires one additional request for every season version which isn't returned by
+    /// [`Series::seasons`].
+    pub async fn seasons_grouped(&self) -> Result<Vec<SeasonGroup>> {
+        let seasons = self.seasons_deep().await?;
+        let keys = season_group_keys(&seasons);
+        let mut groups: Vec<(String, SeasonGroup)> = vec![];
+        for (season, key) in seasons.into_iter().zip(keys) {
+            let index = match groups.iter().position(|(k, _)| k == &key) {
+                Some(index) => index,
+                None => {
+                    groups.push((key, SeasonGroup::default()));
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index].1;
+
+            let is_original = season
+                .versions
+                .iter()
+                .any(|v| v.original && v.id == season.id);
+            if group.seasons.is_empty() || is_original {
+                group.season_number = season_number_from_identifier(&season.identifier)
+                    .unwrap_or(season.season_number);
+                group.season_sequence_number = season.season_sequence_number;
+                group.title.clone_from(&season.title);
+            }
+            for audio_locale in &season.audio_locales {
+                group.seasons.insert(audio_locale.clone(), season.clone());
+            }
+        }
+
+        let mut groups: Vec<SeasonGroup> = groups.into_iter().map(|(_, g)| g).collect();
+        groups.sort_by_key(|g| g.season_sequence_number);
+        Ok(groups)
+    }
+
+    /// Returns the seasons of [`Series::seasons`] and all seasons which are only reachable via
+    /// their [`Season::versions`].
+    async fn seasons_deep(&self) -> Result<Vec<Season>> {
+        let mut seasons = self.seasons().await?;
+
+        let mut known_ids: HashSet<String> = seasons.iter().map(|s| s.id.clone()).collect();
+        let mut missing_versions = vec![];
+        for season in &seasons {
+            for version in &season.versions {
+                if known_ids.insert(version.id.clone()) {
+                    missing_versions.push(version.clone())
+                }
+            }
+        }
+        for version in missing_versions {
+            seasons.push(version.season().await?)
         }
         Ok(seasons)
     }
 
+    /// Get the extra videos (trailers, PVs, interviews, ...) of this series. Use
+    /// [`Season::extras`] to get the extras of a specific season.
+    pub async fn extras(&self) -> Result<Vec<ExtraVideo>> {
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/cms/series/{}/extra_videos",
+            self.id
+        );
+        extra_videos(self.executor.clone(), endpoint).await
+    }
+
     /// Get music videos which are related to this series.
     pub async fn featured_music(&self) -> Result<Vec<MusicVideo>> {
         let endpoint = format!(
@@ -154,6 +407,98 @@ impl Series {
     }
 }
 
+/// Removes all episodes which are a version (dub) of an episode that comes before them.
+fn dedup_episode_versions(episodes: Vec<Episode>) -> Vec<Episode> {
+    let mut seen = HashSet::new();
+    episodes
+        .into_iter()
+        .filter(|episode| {
+            let is_new = !seen.contains(&episode.id);
+            seen.insert(episode.id.clone());
+            seen.extend(episode.versions.iter().map(|v| v.id.clone()));
+            is_new
+        })
+        .collect()
+}
+
+/// Returns the index of the episode which should be watched next, see [`Series::next_unwatched`].
+fn next_unwatched_index(
+    episodes: &[Episode],
+    playheads: &HashMap<String, PlayheadInformation>,
+) -> Option<usize> {
+    let last_watched = episodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| {
+            iter::once(&e.id)
+                .chain(e.versions.iter().map(|v| &v.id))
+                .filter_map(|id| playheads.get(id))
+                .max_by_key(|p| p.last_modified)
+                .map(|p| (i, p))
+        })
+        .max_by_key(|(_, p)| p.last_modified);
+    let next = match last_watched {
+        Some((i, playhead)) if playhead.fully_watched => i + 1,
+        Some((i, _)) => i,
+        None => 0,
+    };
+    (next < episodes.len()).then_some(next)
+}
+
+/// Returns a key for every season which is the same for all seasons that are dubs of the same
+/// logical season.
+fn season_group_keys(seasons: &[Season]) -> Vec<String> {
+    let number_key = |season: &Season| {
+        season_number_from_identifier(&season.identifier)
+            .map(|season_number| format!("{}|S{season_number}", season.series_id))
+    };
+    // all dubs of a season have the same versions, so the smallest version id is the same for all
+    let version_keys: Vec<Option<String>> = seasons
+        .iter()
+        .map(|season| season.versions.iter().map(|v| &v.id).min().cloned())
+        .collect();
+
+    // seasons without versions join the group of the seasons with versions which have the same
+    // season number. if multiple groups have the same season number it's unknown which one the
+    // season belongs to, so it gets its own group in this case
+    let mut number_to_version_key: HashMap<String, Option<String>> = HashMap::new();
+    for (season, version_key) in seasons.iter().zip(&version_keys) {
+        if let (Some(version_key), Some(number_key)) = (version_key, number_key(season)) {
+            number_to_version_key
+                .entry(number_key)
+                .and_modify(|key| {
+                    if key.as_ref() != Some(version_key) {
+                        *key = None
+                    }
+                })
+                .or_insert_with(|| Some(version_key.clone()));
+        }
+    }
+
+    seasons
+        .iter()
+        .zip(version_keys)
+        .map(|(season, version_key)| {
+            version_key.unwrap_or_else(|| match number_key(season) {
+                Some(number_key) => number_to_version_key
+                    .get(&number_key)
+                    .cloned()
+                    .flatten()
+                    .unwrap_or(number_key),
+                None => season.id.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the season number of a season identifier, which looks like `GY8VEQ95Y|S1` (some
+/// identifiers have additional segments, e.g. `GY8VEQ95Y|S1|D`).
+fn season_number_from_identifier(identifier: &str) -> Option<u32> {
+    identifier
+        .split('|')
+        .find_map(|segment| segment.strip_prefix('S')?.parse().ok())
+}
+
 #[async_trait::async_trait]
 impl Media for Series {
     async fn from_id(crunchyroll: &Crunchyroll, id: impl AsRef<str> + Send) -> Result<Self> {
@@ -194,3 +539,135 @@ impl Media for Series {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::{EpisodeVersion, SeasonVersion};
+    use chrono::TimeZone;
+
+    #[test]
+    fn season_number() {
+        assert_eq!(season_number_from_identifier("GY8VEQ95Y|S1"), Some(1));
+        assert_eq!(season_number_from_identifier("GY8VEQ95Y|S12|D"), Some(12));
+        assert_eq!(season_number_from_identifier("GY8VEQ95Y|M1"), None);
+        assert_eq!(season_number_from_identifier("GY8VEQ95Y|SP"), None);
+        assert_eq!(season_number_from_identifier(""), None)
+    }
+
+    fn season(id: &str, identifier: &str, versions: &[&str]) -> Season {
+        let mut season = Season::default();
+        season.id = id.to_string();
+        season.series_id = "GY8VEQ95Y".to_string();
+        season.identifier = identifier.to_string();
+        season.versions = versions
+            .iter()
+            .map(|id| SeasonVersion {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        season
+    }
+
+    #[test]
+    fn season_groups() {
+        let seasons = vec![
+            season("S1-JA", "GY8VEQ95Y|S1", &["S1-JA", "S1-EN"]),
+            season("S1-EN", "GY8VEQ95Y|S1", &["S1-JA", "S1-EN"]),
+            // a dub which isn't listed in the versions
+            season("S1-DE", "GY8VEQ95Y|S1|D", &[]),
+            season("S2-JA", "GY8VEQ95Y|S2", &[]),
+            season("S2-EN", "GY8VEQ95Y|S2", &[]),
+            season("SP", "GY8VEQ95Y|SP", &[]),
+        ];
+        assert_eq!(
+            season_group_keys(&seasons),
+            vec![
+                "S1-EN",
+                "S1-EN",
+                "S1-EN",
+                "GY8VEQ95Y|S2",
+                "GY8VEQ95Y|S2",
+                "SP"
+            ]
+        );
+
+        // two groups with the same season number, the season without versions can't be assigned
+        let seasons = vec![
+            season("A", "GY8VEQ95Y|S1", &["A"]),
+            season("B", "GY8VEQ95Y|S1", &["B"]),
+            season("C", "GY8VEQ95Y|S1", &[]),
+        ];
+        assert_eq!(season_group_keys(&seasons), vec!["A", "B", "GY8VEQ95Y|S1"])
+    }
+
+    // the strict feature adds private fields, so the struct can't be created with a literal
+    #[allow(clippy::field_reassign_with_default)]
+    fn episode(id: &str, versions: &[&str]) -> Episode {
+        let mut episode = Episode::default();
+        episode.id = id.to_string();
+        episode.versions = versions
+            .iter()
+            .map(|id| EpisodeVersion {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        episode
+    }
+
+    fn playheads(entries: &[(&str, bool, u32)]) -> HashMap<String, PlayheadInformation> {
+        entries
+            .iter()
+            .map(|(id, fully_watched, day)| {
+                (
+                    id.to_string(),
+                    PlayheadInformation {
+                        content_id: id.to_string(),
+                        fully_watched: *fully_watched,
+                        last_modified: Utc.with_ymd_and_hms(2024, 1, *day, 0, 0, 0).unwrap(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn next_unwatched_episode() {
+        let episodes = dedup_episode_versions(vec![
+            episode("E1-JA", &["E1-JA", "E1-EN"]),
+            episode("E2-JA", &["E2-JA", "E2-EN"]),
+            // dubs of the episodes above, e.g. from a dub season which got mixed in
+            episode("E1-EN", &["E1-JA", "E1-EN"]),
+            episode("E2-EN", &["E2-JA", "E2-EN"]),
+            episode("E3-JA", &[]),
+        ]);
+        let ids: Vec<&str> = episodes.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["E1-JA", "E2-JA", "E3-JA"]);
+
+        assert_eq!(next_unwatched_index(&episodes, &playheads(&[])), Some(0));
+        // progress in the english dub counts for the japanese episode
+        assert_eq!(
+            next_unwatched_index(&episodes, &playheads(&[("E1-EN", false, 1)])),
+            Some(0)
+        );
+        assert_eq!(
+            next_unwatched_index(&episodes, &playheads(&[("E1-EN", true, 1)])),
+            Some(1)
+        );
+        // the most recently modified playhead wins
+        assert_eq!(
+            next_unwatched_index(
+                &episodes,
+                &playheads(&[("E2-EN", true, 2), ("E1-JA", false, 3)])
+            ),
+            Some(0)
+        );
+        assert_eq!(
+            next_unwatched_index(&episodes, &playheads(&[("E3-JA", true, 1)])),
+            None
+        )
+    }
+}
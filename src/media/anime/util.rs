@@ -1,7 +1,37 @@
+use crate::common::{Pagination, PaginationBulkResultMeta, V2BulkResult};
 #[cfg(feature = "__test_strict")]
 use crate::internal::strict::StrictValue;
 use crate::media::{EpisodeVersion, SeasonVersion};
-use crate::{Episode, Locale, Season};
+use crate::{Episode, Executor, Locale, MediaCollection, Season};
+use futures_util::FutureExt;
+use std::sync::Arc;
+
+/// Series or movie listings which are similar to the series / movie listing with the given id.
+pub(crate) fn similar_to(executor: Arc<Executor>, id: String) -> Pagination<MediaCollection> {
+    Pagination::new(
+        |options| {
+            async move {
+                let endpoint = format!(
+                    "https://www.crunchyroll.com/content/v2/discover/{}/similar_to/{}",
+                    options.executor.details.account_id.clone()?,
+                    options.extra.get("id").unwrap()
+                );
+                let result: V2BulkResult<MediaCollection, PaginationBulkResultMeta> = options
+                    .executor
+                    .get(endpoint)
+                    .query(&[("n", options.page_size), ("start", options.start)])
+                    .apply_locale_query()
+                    .request()
+                    .await?;
+                Ok(result.into())
+            }
+            .boxed()
+        },
+        executor,
+        None,
+        Some(vec![("id", id)]),
+    )
+}
 
 #[cfg(feature = "experimental-stabilizations")]
 pub(crate) fn parse_locale_from_slug_title<S: AsRef<str>>(slug_title: S) -> crate::Locale {
@@ -0,0 +1,329 @@
+use crate::common::Cancellation;
+use crate::error::Error;
+use crate::media::{MediaStream, Resolution, Stream, StreamData, StreamPreference, Subtitle};
+use crate::{Episode, Locale, Result};
+use futures_util::future::try_join3;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Receives how fast segment data is downloaded. Set it via
+/// [`crate::crunchyroll::CrunchyrollBuilder::throughput_observer`], it's then notified about every
+/// chunk which [`crate::media::StreamSegment::data`] (and everything which uses it, like
+/// [`EpisodeDownloader`]) receives. Use [`RollingThroughput`] if you just need the current speed.
+pub trait ThroughputObserver: Send + Sync {
+    /// Called for every received chunk with its size in bytes and how long it took to receive
+    /// it. The duration of the first chunk of a segment includes the time until the server
+    /// responded.
+    fn observe(&self, bytes: usize, duration: Duration);
+}
+
+/// [`ThroughputObserver`] which calculates the average download speed of the last chunks. Clones
+/// share the same measurements, so keep a clone to read the speed after passing it to
+/// [`crate::crunchyroll::CrunchyrollBuilder::throughput_observer`].
+#[derive(Clone, Debug)]
+pub struct RollingThroughput {
+    window: Duration,
+    samples: Arc<Mutex<VecDeque<(Instant, usize)>>>,
+    started: Arc<Mutex<Option<Instant>>>,
+    total_bytes: Arc<AtomicU64>,
+}
+
+impl Default for RollingThroughput {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5))
+    }
+}
+
+impl RollingThroughput {
+    /// Create a new instance which averages over the chunks of the last `window`. Default is 5
+    /// seconds.
+    pub fn new(window: Duration) -> RollingThroughput {
+        Self {
+            window,
+            samples: Arc::new(Mutex::new(VecDeque::new())),
+            started: Arc::new(Mutex::new(None)),
+            total_bytes: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Average download speed in bytes per second over the last window. If the first chunk was
+    /// received less than a window ago, it's averaged over the time since then.
+    pub fn bytes_per_second(&self) -> f64 {
+        let Some(started) = *self.started.lock().unwrap() else {
+            return 0.0;
+        };
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap();
+        self.prune(&mut samples, now);
+
+        let span = now.duration_since(started).min(self.window).as_secs_f64();
+        if span == 0.0 {
+            return 0.0;
+        }
+        samples.iter().map(|(_, bytes)| *bytes as f64).sum::<f64>() / span
+    }
+
+    /// Total number of bytes which were observed.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Removes all samples which are older than the window.
+    fn prune(&self, samples: &mut VecDeque<(Instant, usize)>, now: Instant) {
+        while samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > self.window)
+        {
+            samples.pop_front();
+        }
+    }
+}
+
+impl ThroughputObserver for RollingThroughput {
+    fn observe(&self, bytes: usize, duration: Duration) {
+        let now = Instant::now();
+        self.started
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| now.checked_sub(duration).unwrap_or(now));
+        // prune here too, otherwise the samples would grow for the whole download if the speed is
+        // never read
+        let mut samples = self.samples.lock().unwrap();
+        self.prune(&mut samples, now);
+        samples.push_back((now, bytes));
+        self.total_bytes.fetch_add(bytes as u64, Ordering::SeqCst);
+    }
+}
+
+/// Downloads an [`Episode`] with the given preferences. It resolves the episode version with the
+/// preferred audio locale, requests its stream, selects the video / audio stream, downloads them
+/// and the subtitles concurrently and invalidates the stream afterward (also if the download
+/// failed).
+///
+/// The video and audio data is written as it is delivered by Crunchyroll. A segment which
+/// responds with an error status or arrives incomplete fails the download. If the stream is DRM
+/// encrypted (see [`DownloadSummary::video`] and [`MediaStream::drm`]) the written data is
+/// encrypted too.
+#[derive(Clone, Debug, Default)]
+pub struct EpisodeDownloader {
+    audio_locale: Option<Locale>,
+    hardsub: Option<Locale>,
+    max_resolution: Option<Resolution>,
+    cancellation: Cancellation,
+}
+
+/// Information about what was downloaded by [`EpisodeDownloader::download`].
+#[derive(Clone, Debug)]
+pub struct DownloadSummary {
+    /// The episode which was actually downloaded. Differs from the input episode if another
+    /// version was used because of [`EpisodeDownloader::audio_locale`].
+    pub episode: Episode,
+    /// The downloaded video stream.
+    pub video: MediaStream,
+    /// The downloaded audio stream.
+    pub audio: MediaStream,
+    /// The locales of all downloaded subtitles.
+    pub subtitles: Vec<Locale>,
+}
+
+impl EpisodeDownloader {
+    /// Create a new downloader with the default preferences.
+    pub fn new() -> EpisodeDownloader {
+        Self::default()
+    }
+
+    /// Audio locale which the episode should have. If the given episode has another audio locale,
+    /// the matching [`Episode::versions`] is used. Default is the audio locale of the given
+    /// episode.
+    pub fn audio_locale(mut self, audio_locale: Locale) -> EpisodeDownloader {
+        self.audio_locale = Some(audio_locale);
+        self
+    }
+
+    /// Hardsub which should be burned into the video. Default is no hardsub.
+    pub fn hardsub(mut self, hardsub: Locale) -> EpisodeDownloader {
+        self.hardsub = Some(hardsub);
+        self
+    }
+
+    /// Maximal resolution of the video. Default is the best available resolution.
+    pub fn max_resolution(mut self, max_resolution: Resolution) -> EpisodeDownloader {
+        self.max_resolution = Some(max_resolution);
+        self
+    }
+
+    /// Handle to cancel the download. The stream is invalidated if the download gets cancelled,
+    /// like it is if the download finishes or fails. Data which was already downloaded before the
+    /// cancellation stays written to the writers.
+    pub fn cancellation(mut self, cancellation: Cancellation) -> EpisodeDownloader {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Download the episode. The video and audio data is written to `video` and `audio`. For
+    /// every locale in `subtitles`, the matching subtitle (see [`Stream::subtitle`]) is written to
+    /// the corresponding writer, locales without subtitle are skipped.
+    pub async fn download<V, A, S>(
+        &self,
+        episode: &Episode,
+        video: &mut V,
+        audio: &mut A,
+        subtitles: &mut HashMap<Locale, S>,
+    ) -> Result<DownloadSummary>
+    where
+        V: Write + Send,
+        A: Write + Send,
+        S: Write + Send,
+    {
+        let episode = self.resolve_episode(episode).await?;
+        let stream = episode.stream().await?;
+
+        let result = self.download_stream(&stream, video, audio, subtitles).await;
+        let invalidate_result = stream.invalidate().await;
+
+        let (video, audio, subtitles) = result?;
+        invalidate_result?;
+        Ok(DownloadSummary {
+            episode,
+            video,
+            audio,
+            subtitles,
+        })
+    }
+
+    async fn resolve_episode(&self, episode: &Episode) -> Result<Episode> {
+        let Some(audio_locale) = &self.audio_locale else {
+            return Ok(episode.clone());
+        };
+        if &episode.audio_locale == audio_locale {
+            return Ok(episode.clone());
+        }
+
+        let Some(version) = episode
+            .versions
+            .iter()
+            .find(|v| &v.audio_locale == audio_locale)
+        else {
+            return Err(Error::Input {
+                message: format!(
+                    "episode {} has no version with audio locale {audio_locale}",
+                    episode.id
+                ),
+            });
+        };
+        version.episode().await
+    }
+
+    async fn download_stream<V, A, S>(
+        &self,
+        stream: &Stream,
+        video: &mut V,
+        audio: &mut A,
+        subtitles: &mut HashMap<Locale, S>,
+    ) -> Result<(MediaStream, MediaStream, Vec<Locale>)>
+    where
+        V: Write + Send,
+        A: Write + Send,
+        S: Write + Send,
+    {
+        let Some(stream_data) = stream.stream_data(self.hardsub.clone()).await? else {
+            return Err(Error::Input {
+                message: format!(
+                    "hardsub {} is not available",
+                    self.hardsub.clone().unwrap_or_default()
+                ),
+            });
+        };
+        let (video_stream, audio_stream) = self.select(&stream_data)?;
+
+        let subtitle_downloads: Vec<(&Subtitle, &mut S)> = subtitles
+            .iter_mut()
+            .filter_map(|(locale, writer)| {
+                stream
+                    .subtitle(std::slice::from_ref(locale))
+                    .map(|subtitle| (subtitle, writer))
+            })
+            .collect();
+        let subtitle_locales = subtitle_downloads
+            .iter()
+            .map(|(subtitle, _)| subtitle.locale.clone())
+            .collect();
+
+        let cancellation = &self.cancellation;
+        try_join3(
+            download_media_stream(&video_stream, video, cancellation),
+            download_media_stream(&audio_stream, audio, cancellation),
+            async move {
+                for (subtitle, writer) in subtitle_downloads {
+                    let data = cancellation.run(subtitle.data(), &subtitle.url).await?;
+                    write(writer, &data, &subtitle.url)?
+                }
+                Ok(())
+            },
+        )
+        .await?;
+
+        Ok((video_stream, audio_stream, subtitle_locales))
+    }
+
+    fn select(&self, stream_data: &StreamData) -> Result<(MediaStream, MediaStream)> {
+        let mut preference = StreamPreference::default();
+        if let Some(max_resolution) = &self.max_resolution {
+            preference = preference.max_resolution(max_resolution.clone())
+        }
+
+        match stream_data.preferred(&preference) {
+            (Some(video), Some(audio)) => Ok((video.clone(), audio.clone())),
+            (None, _) => Err(Error::Input {
+                message: "no video stream matches the preferences".to_string(),
+            }),
+            (_, None) => Err(Error::Input {
+                message: "no audio stream available".to_string(),
+            }),
+        }
+    }
+}
+
+async fn download_media_stream<W: Write + Send>(
+    media_stream: &MediaStream,
+    writer: &mut W,
+    cancellation: &Cancellation,
+) -> Result<()> {
+    for segment in media_stream.segments() {
+        // unlike `StreamSegment::data`, this fails on error responses instead of returning their
+        // body as segment data
+        let segment_data = cancellation
+            .run(segment.data_validated(0), &segment.url)
+            .await?;
+        write(writer, &segment_data.data, &segment.url)?
+    }
+    Ok(())
+}
+
+fn write<W: Write>(writer: &mut W, data: &[u8], url: &str) -> Result<()> {
+    writer.write_all(data).map_err(|e| Error::Internal {
+        message: format!("failed to write data of {url}: {e}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_throughput_prunes_on_observe() {
+        let throughput = RollingThroughput::new(Duration::from_millis(1));
+
+        for _ in 0..3 {
+            throughput.observe(100, Duration::from_millis(1));
+            std::thread::sleep(Duration::from_millis(5))
+        }
+        // only the latest sample is within the window, the older ones were pruned without ever
+        // reading the speed
+        assert_eq!(throughput.samples.lock().unwrap().len(), 1);
+        assert_eq!(throughput.total_bytes(), 300)
+    }
+}
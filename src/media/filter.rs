@@ -0,0 +1,262 @@
+use crate::categories::Category;
+use crate::{Locale, MediaCollection};
+use chrono::{DateTime, Datelike, Utc};
+use std::ops::RangeInclusive;
+
+/// Client side filter for media results. It can be applied to everything which contains a
+/// [`MediaCollection`], like browse results, [`crate::list::WatchlistEntry`] or
+/// [`crate::list::WatchHistoryEntry`]. All set criteria must match, media which doesn't contain the
+/// information a criteria checks (e.g. [`crate::Season`] has no year) never matches it.
+///
+/// ```no_run
+/// use crunchyroll_rs::media::MediaFilter;
+/// use crunchyroll_rs::{Crunchyroll, Locale};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), crunchyroll_rs::error::Error> {
+/// let crunchy = Crunchyroll::builder()
+///     .login_with_credentials("email", "password")
+///     .await?;
+/// let filter = MediaFilter::new()
+///     .text("dragon")
+///     .audio_locale(Locale::de_DE)
+///     .years(2010..=2020);
+/// let history = crunchy
+///     .watch_history()
+///     .filter_collect(|entry| filter.matches(entry))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MediaFilter {
+    title: Option<String>,
+    text: Option<String>,
+    audio_locales: Vec<Locale>,
+    years: Option<RangeInclusive<u32>>,
+    categories: Vec<Category>,
+}
+
+impl MediaFilter {
+    pub fn new() -> MediaFilter {
+        MediaFilter::default()
+    }
+
+    /// Only match media whose title contains `title` (case-insensitive).
+    pub fn title<S: AsRef<str>>(mut self, title: S) -> MediaFilter {
+        self.title = Some(title.as_ref().to_lowercase());
+        self
+    }
+
+    /// Only match media whose title or description contains `text` (case-insensitive).
+    pub fn text<S: AsRef<str>>(mut self, text: S) -> MediaFilter {
+        self.text = Some(text.as_ref().to_lowercase());
+        self
+    }
+
+    /// Only match media which is available in the given audio locale. If called multiple times,
+    /// media which has any of the locales matches.
+    pub fn audio_locale(mut self, audio_locale: Locale) -> MediaFilter {
+        self.audio_locales.push(audio_locale);
+        self
+    }
+
+    /// Only match media which was released in the given year range. Media whose release date is
+    /// unknown (Crunchyroll then mostly returns the unix epoch or `0`) never matches.
+    pub fn years(mut self, years: RangeInclusive<u32>) -> MediaFilter {
+        self.years = Some(years);
+        self
+    }
+
+    /// Only match media which has the given category. If called multiple times, media which has any
+    /// of the categories matches.
+    pub fn category(mut self, category: Category) -> MediaFilter {
+        self.categories.push(category);
+        self
+    }
+
+    /// Check if the given media matches all criteria of this filter.
+    pub fn matches<M: AsRef<MediaCollection>>(&self, media: &M) -> bool {
+        let media = media.as_ref();
+
+        if let Some(title) = &self.title {
+            if !media.title().to_lowercase().contains(title) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.text {
+            if !media.title().to_lowercase().contains(text)
+                && !description(media).to_lowercase().contains(text)
+            {
+                return false;
+            }
+        }
+        if !self.audio_locales.is_empty()
+            && !audio_locales(media)
+                .iter()
+                .any(|l| self.audio_locales.contains(l))
+        {
+            return false;
+        }
+        if let Some(years) = &self.years {
+            if !year(media).is_some_and(|year| years.contains(&year)) {
+                return false;
+            }
+        }
+        if !self.categories.is_empty()
+            && !categories(media)
+                .iter()
+                .any(|c| self.categories.contains(c))
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Return all items which match this filter.
+    pub fn apply<M: AsRef<MediaCollection>>(&self, items: Vec<M>) -> Vec<M> {
+        items.into_iter().filter(|i| self.matches(i)).collect()
+    }
+}
+
+fn description(media: &MediaCollection) -> &str {
+    match media {
+        MediaCollection::Series(series) => &series.description,
+        MediaCollection::Season(season) => &season.description,
+        MediaCollection::Episode(episode) => &episode.description,
+        MediaCollection::MovieListing(movie_listing) => &movie_listing.description,
+        MediaCollection::Movie(movie) => &movie.description,
+        MediaCollection::MusicVideo(music_video) => &music_video.description,
+        MediaCollection::Concert(concert) => &concert.description,
+    }
+}
+
+fn audio_locales(media: &MediaCollection) -> Vec<Locale> {
+    match media {
+        MediaCollection::Series(series) => series.audio_locales.clone(),
+        MediaCollection::Season(season) => season.audio_locales.clone(),
+        MediaCollection::Episode(episode) => vec![episode.audio_locale.clone()],
+        MediaCollection::MovieListing(movie_listing) => {
+            movie_listing.audio_locale.clone().into_iter().collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn year(media: &MediaCollection) -> Option<u32> {
+    let year = match media {
+        MediaCollection::Series(series) => series.series_launch_year,
+        MediaCollection::Episode(episode) => date_year(&episode.episode_air_date),
+        MediaCollection::MovieListing(movie_listing) => Some(movie_listing.movie_release_year),
+        MediaCollection::MusicVideo(music_video) => date_year(&music_video.original_release),
+        MediaCollection::Concert(concert) => date_year(&concert.original_release),
+        _ => None,
+    };
+    year.filter(|year| *year != 0)
+}
+
+/// Returns the year of `date`, or [`None`] if it's the unix epoch, which is the default if the
+/// date is missing in the api response.
+fn date_year(date: &DateTime<Utc>) -> Option<u32> {
+    (*date != DateTime::UNIX_EPOCH).then(|| date.year() as u32)
+}
+
+fn categories(media: &MediaCollection) -> &[Category] {
+    match media {
+        MediaCollection::Series(series) => &series.categories,
+        MediaCollection::MovieListing(movie_listing) => &movie_listing.categories,
+        _ => &[],
+    }
+}
+
+// the strict feature makes the fields which aren't part of the public api private to their module
+#[cfg(all(test, not(feature = "__test_strict")))]
+mod tests {
+    use super::*;
+    use crate::{Episode, MovieListing, Season, Series};
+    use chrono::TimeZone;
+
+    fn series() -> MediaCollection {
+        MediaCollection::Series(Series {
+            title: "Dragon Ball".to_string(),
+            description: "Goku's adventures".to_string(),
+            audio_locales: vec![Locale::ja_JP, Locale::de_DE],
+            series_launch_year: Some(1986),
+            categories: vec![Category::Action],
+            ..Default::default()
+        })
+    }
+
+    fn episode(air_date: DateTime<Utc>) -> MediaCollection {
+        MediaCollection::Episode(Episode {
+            title: "The Secret of the Dragon Balls".to_string(),
+            audio_locale: Locale::ja_JP,
+            episode_air_date: air_date,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn matches_text() {
+        assert!(MediaFilter::new().title("dragon").matches(&series()));
+        assert!(!MediaFilter::new().title("goku").matches(&series()));
+        // text also searches the description
+        assert!(MediaFilter::new().text("GOKU").matches(&series()));
+        assert!(!MediaFilter::new().text("vegeta").matches(&series()))
+    }
+
+    #[test]
+    fn matches_audio_locale_and_category() {
+        assert!(MediaFilter::new()
+            .audio_locale(Locale::en_US)
+            .audio_locale(Locale::de_DE)
+            .matches(&series()));
+        assert!(!MediaFilter::new()
+            .audio_locale(Locale::en_US)
+            .matches(&series()));
+        assert!(MediaFilter::new()
+            .category(Category::Action)
+            .matches(&series()));
+        assert!(!MediaFilter::new()
+            .category(Category::Romance)
+            .matches(&series()));
+        // all criteria must match
+        assert!(!MediaFilter::new()
+            .title("dragon")
+            .category(Category::Romance)
+            .matches(&series()))
+    }
+
+    #[test]
+    fn matches_years() {
+        let filter = MediaFilter::new().years(1980..=1990);
+        let air_date = Utc.with_ymd_and_hms(1986, 2, 26, 0, 0, 0).unwrap();
+
+        assert!(filter.matches(&series()));
+        assert!(filter.matches(&episode(air_date)));
+        assert!(!MediaFilter::new()
+            .years(2000..=2010)
+            .matches(&episode(air_date)));
+        // seasons have no year
+        assert!(!filter.matches(&MediaCollection::Season(Season::default())));
+    }
+
+    #[test]
+    fn unknown_year_never_matches() {
+        let filter = MediaFilter::new().years(1900..=2100);
+
+        assert!(!filter.matches(&episode(DateTime::UNIX_EPOCH)));
+        assert!(!filter.matches(&MediaCollection::MovieListing(MovieListing::default())));
+        assert!(!filter.matches(&MediaCollection::Series(Series::default())))
+    }
+
+    #[test]
+    fn apply() {
+        let items = vec![series(), episode(DateTime::UNIX_EPOCH)];
+
+        let filtered = MediaFilter::new().category(Category::Action).apply(items);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].title(), "Dragon Ball")
+    }
+}
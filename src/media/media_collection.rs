@@ -1,6 +1,7 @@
 use crate::common::Request;
 use crate::crunchyroll::Executor;
 use crate::error::Error;
+use crate::media::util::request_media;
 use crate::media::Media;
 use crate::{
     Concert, Crunchyroll, Episode, Movie, MovieListing, MusicVideo, Result, Season, Series,
@@ -50,6 +51,29 @@ impl MediaCollection {
     }
 }
 
+impl Crunchyroll {
+    /// Requests the media of all given ids in a single request. The ids can be of different media
+    /// types. Useful to get the actual media if only ids are available.
+    pub async fn objects_from_ids<S: AsRef<str>>(&self, ids: &[S]) -> Result<Vec<MediaCollection>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids: Vec<&str> = ids.iter().map(|id| id.as_ref()).collect();
+        let endpoint = format!(
+            "https://www.crunchyroll.com/content/v2/cms/objects/{}",
+            ids.join(",")
+        );
+        request_media(self.executor.clone(), endpoint).await
+    }
+}
+
+impl AsRef<MediaCollection> for MediaCollection {
+    fn as_ref(&self) -> &MediaCollection {
+        self
+    }
+}
+
 impl Default for MediaCollection {
     fn default() -> Self {
         Self::Series(Series::default())
@@ -129,17 +153,83 @@ impl Request for MediaCollection {
 }
 
 macro_rules! impl_media_collection {
-    ($($media:ident)*) => {
+    ($($media:ident $name:literal $is:ident $as:ident);*) => {
+        impl MediaCollection {
+            /// The id of the contained media.
+            pub fn id(&self) -> &str {
+                match self {
+                    $(
+                        MediaCollection::$media(media) => &media.id,
+                    )*
+                }
+            }
+
+            /// The title of the contained media.
+            pub fn title(&self) -> &str {
+                match self {
+                    $(
+                        MediaCollection::$media(media) => &media.title,
+                    )*
+                }
+            }
+
+            fn type_name(&self) -> &'static str {
+                match self {
+                    $(
+                        MediaCollection::$media(_) => $name,
+                    )*
+                }
+            }
+
+            $(
+                #[doc = concat!("Checks if this collection contains a [`", stringify!($media), "`].")]
+                pub fn $is(&self) -> bool {
+                    matches!(self, MediaCollection::$media(_))
+                }
+
+                #[doc = concat!("Returns the contained [`", stringify!($media), "`] if the collection is one.")]
+                pub fn $as(&self) -> Option<&$media> {
+                    match self {
+                        MediaCollection::$media(media) => Some(media),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+
         $(
             impl From<$media> for MediaCollection {
                 fn from(value: $media) -> Self {
                     MediaCollection::$media(value)
                 }
             }
+
+            impl TryFrom<MediaCollection> for $media {
+                type Error = Error;
+
+                fn try_from(value: MediaCollection) -> Result<Self> {
+                    match value {
+                        MediaCollection::$media(media) => Ok(media),
+                        _ => Err(Error::Input {
+                            message: format!(
+                                "media collection is a {}, not a {}",
+                                value.type_name(),
+                                $name
+                            ),
+                        }),
+                    }
+                }
+            }
         )*
     }
 }
 
 impl_media_collection! {
-    Series Season Episode MovieListing Movie MusicVideo Concert
+    Series "series" is_series as_series;
+    Season "season" is_season as_season;
+    Episode "episode" is_episode as_episode;
+    MovieListing "movie listing" is_movie_listing as_movie_listing;
+    Movie "movie" is_movie as_movie;
+    MusicVideo "music video" is_music_video as_music_video;
+    Concert "concert" is_concert as_concert
 }
@@ -1,15 +1,21 @@
 //! All media items like series, episodes or movies.
 
 mod anime;
+mod download;
+mod filter;
 mod media_collection;
 mod music;
+mod playback;
 mod shared;
 mod stream;
-mod util;
+pub(crate) mod util;
 
 pub use anime::*;
+pub use download::*;
+pub use filter::*;
 pub use media_collection::*;
 pub use music::*;
+pub use playback::*;
 pub use shared::*;
 pub use stream::*;
 
@@ -51,4 +57,13 @@ impl Crunchyroll {
     pub async fn media_collection_from_id<S: AsRef<str>>(&self, id: S) -> Result<MediaCollection> {
         MediaCollection::from_id(self, id).await
     }
+
+    /// Attach this instance to media which was deserialized from an export instead of requested
+    /// from the api. Media can be exported via its [`serde::Serialize`] implementation (e.g. to
+    /// store it in a local cache or database) and deserialized again later, but it can only make
+    /// requests (like [`Series::seasons`]) after it got attached.
+    pub async fn attach<M: Media>(&self, mut media: M) -> M {
+        media.__set_executor(self.executor.clone()).await;
+        media
+    }
 }
@@ -1,10 +1,11 @@
-use crate::common::V2BulkResult;
+use crate::common::{Pagination, PaginationBulkResultMeta, V2BulkResult};
 use crate::crunchyroll::Executor;
 use crate::media::music::concert::Concert;
 use crate::media::util::request_media;
 use crate::media::{MusicGenre, MusicVideo, PosterImages};
 use crate::{Crunchyroll, Request, Result};
 use chrono::{DateTime, Duration, Utc};
+use futures_util::FutureExt;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -112,32 +113,54 @@ impl Artist {
     }
 
     /// Return all concerts of this artist.
-    pub async fn concerts(&self) -> Result<Vec<Concert>> {
-        let endpoint = format!(
-            "https://www.crunchyroll.com/content/v2/music/artists/{}/concerts",
-            &self.id
-        );
-        Ok(self
-            .executor
-            .get(endpoint)
-            .apply_locale_query()
-            .request::<V2BulkResult<Concert>>()
-            .await?
-            .data)
+    pub fn concerts(&self) -> Pagination<Concert> {
+        Pagination::new(
+            |options| {
+                async move {
+                    let endpoint = format!(
+                        "https://www.crunchyroll.com/content/v2/music/artists/{}/concerts",
+                        options.extra.get("id").unwrap()
+                    );
+                    let result: V2BulkResult<Concert, PaginationBulkResultMeta> = options
+                        .executor
+                        .get(endpoint)
+                        .query(&[("n", options.page_size), ("start", options.start)])
+                        .apply_locale_query()
+                        .request()
+                        .await?;
+                    Ok(result.into())
+                }
+                .boxed()
+            },
+            self.executor.clone(),
+            None,
+            Some(vec![("id", self.id.clone())]),
+        )
     }
 
     /// Return all music videos of this artist.
-    pub async fn music_videos(&self) -> Result<Vec<MusicVideo>> {
-        let endpoint = format!(
-            "https://www.crunchyroll.com/content/v2/music/artists/{}/music_videos",
-            &self.id
-        );
-        Ok(self
-            .executor
-            .get(endpoint)
-            .apply_locale_query()
-            .request::<V2BulkResult<MusicVideo>>()
-            .await?
-            .data)
+    pub fn music_videos(&self) -> Pagination<MusicVideo> {
+        Pagination::new(
+            |options| {
+                async move {
+                    let endpoint = format!(
+                        "https://www.crunchyroll.com/content/v2/music/artists/{}/music_videos",
+                        options.extra.get("id").unwrap()
+                    );
+                    let result: V2BulkResult<MusicVideo, PaginationBulkResultMeta> = options
+                        .executor
+                        .get(endpoint)
+                        .query(&[("n", options.page_size), ("start", options.start)])
+                        .apply_locale_query()
+                        .request()
+                        .await?;
+                    Ok(result.into())
+                }
+                .boxed()
+            },
+            self.executor.clone(),
+            None,
+            Some(vec![("id", self.id.clone())]),
+        )
     }
 }
@@ -1,4 +1,4 @@
-use crate::media::Artist;
+use crate::media::{Artist, PlayheadInformation};
 use crate::{Concert, MusicVideo, Result};
 
 macro_rules! impl_manual_media_serialize {
@@ -35,6 +35,19 @@ macro_rules! impl_media_music {
                 pub async fn available(&self) -> bool {
                     self.executor.premium().await || !self.is_premium_only
                 }
+
+                /// Get playhead information.
+                pub async fn playhead(&self) -> Result<Option<PlayheadInformation>> {
+                    Ok($crate::media::playback::playheads(&self.executor, &[&self.id])
+                        .await?
+                        .remove(&self.id))
+                }
+
+                /// Set the playhead (current playback position) for this music video / concert.
+                /// Used unit is seconds.
+                pub async fn set_playhead(&self, position: u32) -> Result<()> {
+                    $crate::media::playback::set_playhead(&self.executor, &self.id, position, false).await
+                }
             }
         )*
     }
@@ -0,0 +1,502 @@
+use crate::common::V2BulkResult;
+use crate::crunchyroll::{Clock, Executor};
+use crate::error::Error;
+use crate::media::{PlayheadInformation, StreamSession};
+use crate::{EmptyJsonProxy, Episode, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Sets the playhead of the episode / movie / music video / concert with the id `content_id`.
+pub(crate) async fn set_playhead(
+    executor: &Arc<Executor>,
+    content_id: &str,
+    position: u32,
+    fully_watched: bool,
+) -> Result<()> {
+    let endpoint = format!(
+        "https://www.crunchyroll.com/content/v2/{}/playheads",
+        executor.details.account_id.clone()?
+    );
+    let mut body = json!({"content_id": content_id, "playhead": position});
+    if fully_watched {
+        body["fully_watched"] = json!(true)
+    }
+    executor
+        .post(endpoint)
+        .apply_locale_query()
+        .json(&body)
+        .request::<EmptyJsonProxy>()
+        .await?;
+    Ok(())
+}
+
+/// Requests the playheads of all given ids, mapped by their content id. Ids without a playhead are
+/// missing in the result.
+pub(crate) async fn playheads(
+    executor: &Arc<Executor>,
+    ids: &[&str],
+) -> Result<HashMap<String, PlayheadInformation>> {
+    let endpoint = format!(
+        "https://www.crunchyroll.com/content/v2/{}/playheads",
+        executor.details.account_id.clone()?
+    );
+
+    let mut playheads = HashMap::new();
+    // the query becomes too long if too many ids are requested at once
+    for chunk in ids.chunks(50) {
+        let result: V2BulkResult<PlayheadInformation> = executor
+            .get(&endpoint)
+            .query(&[("content_ids", chunk.join(","))])
+            .apply_locale_query()
+            .request()
+            .await?;
+        playheads.extend(result.data.into_iter().map(|p| (p.content_id.clone(), p)))
+    }
+    Ok(playheads)
+}
+
+/// Keeps the playhead of an [`Episode`] up to date while it's being watched. Feed it the position
+/// of your player (e.g. from its position callback) via [`WatchProgressTracker::position`], it then
+/// only sends a playhead update every [`WatchProgressTracker::interval`] and immediately on pause
+/// or seek. Once the position is near the end, the episode is marked as fully watched. All
+/// positions are in seconds.
+///
+/// Failed updates don't interrupt the playback: temporary failures are retried and, if they still
+/// fail, sent again with the next update. The last error is available via
+/// [`WatchProgressTracker::last_error`].
+///
+/// It doesn't need a [`Stream`](crate::media::Stream), so it also works if the episode is played
+/// from a download.
+#[derive(Clone, Debug)]
+pub struct WatchProgressTracker {
+    executor: Arc<Executor>,
+    content_id: String,
+    duration: u32,
+
+    interval: Duration,
+    completion_threshold: f64,
+    retries: u32,
+    retry_delay: Duration,
+
+    last_update: Option<DateTime<Utc>>,
+    reported: Option<u32>,
+    pending: Option<u32>,
+    completed: bool,
+    last_error: Option<Error>,
+}
+
+impl WatchProgressTracker {
+    /// Create a new tracker for the given episode.
+    pub fn new(episode: &Episode) -> WatchProgressTracker {
+        Self {
+            executor: episode.executor.clone(),
+            content_id: episode.id.clone(),
+            duration: episode.duration.num_seconds().max(0) as u32,
+
+            interval: Duration::from_secs(30),
+            completion_threshold: 0.9,
+            retries: 2,
+            retry_delay: Duration::from_secs(1),
+
+            last_update: None,
+            reported: None,
+            pending: None,
+            completed: false,
+            last_error: None,
+        }
+    }
+
+    /// Minimal time between two playhead updates while playing. Default is 30 seconds.
+    pub fn interval(mut self, interval: Duration) -> WatchProgressTracker {
+        self.interval = interval;
+        self
+    }
+
+    /// Fraction (`0.0` to `1.0`) of the episode duration after which the episode is marked as
+    /// fully watched. Default is `0.9`, which usually is where the ending starts.
+    pub fn completion_threshold(mut self, completion_threshold: f64) -> WatchProgressTracker {
+        self.completion_threshold = completion_threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How often a failed playhead update is retried. Only failures which may be temporary
+    /// (connection errors, server errors and rate limits) are retried, errors like an expired
+    /// session are not. Default is 2.
+    pub fn retries(mut self, retries: u32) -> WatchProgressTracker {
+        self.retries = retries;
+        self
+    }
+
+    /// How long to wait before the first retry of a failed playhead update. The delay doubles with
+    /// every further retry. Default is 1 second.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> WatchProgressTracker {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Report the current playback position. The playhead is only updated if
+    /// [`WatchProgressTracker::interval`] has passed since the last update, or if the position
+    /// reached the completion threshold.
+    pub async fn position(&mut self, position: u32) {
+        self.pending = Some(position);
+        let now = self.executor.clock.now();
+        let interval_passed = self.last_update.is_none_or(|last_update| {
+            (now - last_update).to_std().unwrap_or_default() >= self.interval
+        });
+        if interval_passed || (!self.completed && self.is_completed(position)) {
+            self.flush().await
+        }
+    }
+
+    /// Report that the playback was paused. The playhead is updated immediately.
+    pub async fn pause(&mut self, position: u32) {
+        self.pending = Some(position);
+        self.flush().await
+    }
+
+    /// Report that the user seeked to a new position. The playhead is updated immediately.
+    pub async fn seek(&mut self, position: u32) {
+        self.pending = Some(position);
+        self.flush().await
+    }
+
+    /// Sends the last reported position if it wasn't sent yet. Call it when the playback stops.
+    pub async fn finish(&mut self) {
+        self.flush().await
+    }
+
+    /// The last position which was successfully sent to Crunchyroll.
+    pub fn reported_position(&self) -> Option<u32> {
+        self.reported
+    }
+
+    /// If the episode was marked as fully watched.
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+
+    /// The error of the last failed playhead update. It's reset once an update succeeds.
+    pub fn last_error(&self) -> Option<&Error> {
+        self.last_error.as_ref()
+    }
+
+    fn is_completed(&self, position: u32) -> bool {
+        self.duration > 0 && position as f64 >= self.duration as f64 * self.completion_threshold
+    }
+
+    async fn flush(&mut self) {
+        let Some(position) = self.pending else {
+            return;
+        };
+        let fully_watched = !self.completed && self.is_completed(position);
+        if self.reported == Some(position) && !fully_watched {
+            self.pending = None;
+            return;
+        }
+
+        self.last_update = Some(self.executor.clock.now());
+        let mut attempt = 0;
+        let mut delay = self.retry_delay;
+        let result = loop {
+            match set_playhead(&self.executor, &self.content_id, position, fully_watched).await {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                    delay *= 2
+                }
+                result => break result,
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.pending = None;
+                self.reported = Some(position);
+                self.completed |= fully_watched;
+                self.last_error = None
+            }
+            // the position stays pending and is sent again with the next update
+            Err(e) => self.last_error = Some(e),
+        }
+    }
+}
+
+/// If a request which failed with `error` may succeed if it's sent again.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Request { status, .. } => status.is_none_or(|status| status.is_server_error()),
+        Error::RateLimited { .. } => true,
+        _ => false,
+    }
+}
+
+type OnWarningFn = Arc<dyn Fn(&StreamSessionWarning) + Send + Sync>;
+
+/// Warning emitted by [`StreamSessionGuard::check`] when a limit of a [`StreamSession`] is about to
+/// be, or already is, exceeded. Exceeding a limit makes Crunchyroll reject further requests of the
+/// stream with a `403`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamSessionWarning {
+    /// The playback is paused and [`StreamSession::maximum_pause`] is exceeded in the given time.
+    PauseLimitApproaching { remaining: Duration },
+    /// The playback was paused for longer than [`StreamSession::maximum_pause`].
+    PauseLimitExceeded,
+    /// [`StreamSession::session_expiration`] is reached in the given time.
+    SessionExpiring { remaining: Duration },
+    /// [`StreamSession::session_expiration`] is reached.
+    SessionExpired,
+}
+
+/// Tracks the active playback time of a [`Stream`](crate::media::Stream) and warns before the
+/// pause or session limits of its [`StreamSession`] are exceeded. Get it via
+/// [`Stream::session_guard`](crate::media::Stream::session_guard). The session expiration counts
+/// from the time the stream was requested, not from the time the guard was created. The guard
+/// doesn't make any requests, call [`StreamSessionGuard::check`] periodically (e.g. alongside
+/// [`WatchProgressTracker::position`]) to get notified.
+#[derive(Clone)]
+pub struct StreamSessionGuard {
+    maximum_pause: Duration,
+    session_expiration: Duration,
+    warn_before: Duration,
+    on_warning: Option<OnWarningFn>,
+    clock: Arc<dyn Clock>,
+
+    created: DateTime<Utc>,
+    session_start: DateTime<Utc>,
+    paused_since: Option<DateTime<Utc>>,
+    paused: Duration,
+}
+
+impl Debug for StreamSessionGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamSessionGuard")
+            .field("maximum_pause", &self.maximum_pause)
+            .field("session_expiration", &self.session_expiration)
+            .field("warn_before", &self.warn_before)
+            .field("created", &self.created)
+            .field("session_start", &self.session_start)
+            .field("paused_since", &self.paused_since)
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+impl StreamSessionGuard {
+    pub(crate) fn new(
+        session: &StreamSession,
+        clock: Arc<dyn Clock>,
+        requested_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            maximum_pause: session.maximum_pause(),
+            session_expiration: session.session_expiration(),
+            warn_before: Duration::from_secs(30),
+            on_warning: None,
+
+            created: clock.now(),
+            session_start: requested_at,
+            paused_since: None,
+            paused: Duration::ZERO,
+            clock,
+        }
+    }
+
+    /// How long before a limit is exceeded a warning should be emitted. Default is 30 seconds.
+    pub fn warn_before(mut self, warn_before: Duration) -> StreamSessionGuard {
+        self.warn_before = warn_before;
+        self
+    }
+
+    /// Callback which is called with every warning [`StreamSessionGuard::check`] emits.
+    pub fn on_warning<F: Fn(&StreamSessionWarning) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> StreamSessionGuard {
+        self.on_warning = Some(Arc::new(callback));
+        self
+    }
+
+    /// Marks the playback as paused. Does nothing if it's already paused.
+    pub fn pause(&mut self) {
+        if self.paused_since.is_none() {
+            self.paused_since = Some(self.clock.now())
+        }
+    }
+
+    /// Marks the playback as resumed. Does nothing if it isn't paused.
+    pub fn resume(&mut self) {
+        if let Some(paused_since) = self.paused_since.take() {
+            self.paused += self.elapsed(paused_since)
+        }
+    }
+
+    /// Resets the session expiration, call it if the stream got renewed (e.g. via
+    /// [`Stream::renew`](crate::media::Stream::renew)).
+    pub fn renewed(&mut self) {
+        self.session_start = self.clock.now()
+    }
+
+    /// Time the playback was active (not paused) since the guard was created.
+    pub fn active_time(&self) -> Duration {
+        let paused = self.paused
+            + self
+                .paused_since
+                .map_or(Duration::ZERO, |p| self.elapsed(p));
+        self.elapsed(self.created).saturating_sub(paused)
+    }
+
+    /// Checks the limits and returns a warning if one of them is about to be, or already is,
+    /// exceeded. The callback set via [`StreamSessionGuard::on_warning`] is called with the
+    /// warning too. Limits which Crunchyroll reports as `0` are not checked.
+    pub fn check(&self) -> Option<StreamSessionWarning> {
+        let pause_warning = self.paused_since.and_then(|paused_since| {
+            self.limit_warning(
+                self.maximum_pause,
+                self.elapsed(paused_since),
+                |remaining| StreamSessionWarning::PauseLimitApproaching { remaining },
+                StreamSessionWarning::PauseLimitExceeded,
+            )
+        });
+        let warning = pause_warning.or_else(|| {
+            self.limit_warning(
+                self.session_expiration,
+                self.elapsed(self.session_start),
+                |remaining| StreamSessionWarning::SessionExpiring { remaining },
+                StreamSessionWarning::SessionExpired,
+            )
+        });
+
+        if let (Some(warning), Some(on_warning)) = (&warning, &self.on_warning) {
+            on_warning(warning)
+        }
+        warning
+    }
+
+    fn elapsed(&self, since: DateTime<Utc>) -> Duration {
+        (self.clock.now() - since).to_std().unwrap_or_default()
+    }
+
+    fn limit_warning(
+        &self,
+        limit: Duration,
+        elapsed: Duration,
+        approaching: impl FnOnce(Duration) -> StreamSessionWarning,
+        exceeded: StreamSessionWarning,
+    ) -> Option<StreamSessionWarning> {
+        if limit.is_zero() {
+            return None;
+        }
+        let remaining = limit.saturating_sub(elapsed);
+        if remaining.is_zero() {
+            Some(exceeded)
+        } else if remaining <= self.warn_before {
+            Some(approaching(remaining))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct TestClock(Mutex<DateTime<Utc>>);
+
+    impl TestClock {
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn guard(requested_ago: Duration) -> (Arc<TestClock>, StreamSessionGuard) {
+        let clock = Arc::new(TestClock(Mutex::new(
+            DateTime::UNIX_EPOCH + Duration::from_secs(3600),
+        )));
+        let session = StreamSession {
+            maximum_pause_seconds: 120,
+            session_expiration_seconds: 600,
+            ..Default::default()
+        };
+        let guard = StreamSessionGuard::new(&session, clock.clone(), clock.now() - requested_ago);
+        (clock, guard)
+    }
+
+    #[test]
+    fn session_expiration_from_request() {
+        let (clock, guard) = guard(Duration::from_secs(500));
+        assert_eq!(guard.check(), None);
+
+        clock.advance(Duration::from_secs(80));
+        assert_eq!(
+            guard.check(),
+            Some(StreamSessionWarning::SessionExpiring {
+                remaining: Duration::from_secs(20)
+            })
+        );
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(guard.check(), Some(StreamSessionWarning::SessionExpired))
+    }
+
+    #[test]
+    fn session_renewed() {
+        let (clock, mut guard) = guard(Duration::from_secs(600));
+        assert_eq!(guard.check(), Some(StreamSessionWarning::SessionExpired));
+
+        guard.renewed();
+        assert_eq!(guard.check(), None);
+        clock.advance(Duration::from_secs(590));
+        assert_eq!(
+            guard.check(),
+            Some(StreamSessionWarning::SessionExpiring {
+                remaining: Duration::from_secs(10)
+            })
+        )
+    }
+
+    #[test]
+    fn pause_limit() {
+        let (clock, mut guard) = guard(Duration::ZERO);
+        clock.advance(Duration::from_secs(60));
+        guard.pause();
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(
+            guard.check(),
+            Some(StreamSessionWarning::PauseLimitApproaching {
+                remaining: Duration::from_secs(20)
+            })
+        );
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(
+            guard.check(),
+            Some(StreamSessionWarning::PauseLimitExceeded)
+        );
+
+        guard.resume();
+        assert_eq!(guard.check(), None);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(guard.active_time(), Duration::from_secs(90))
+    }
+
+    #[test]
+    fn zero_limits_unchecked() {
+        let clock = Arc::new(TestClock(Mutex::new(DateTime::UNIX_EPOCH)));
+        let mut guard =
+            StreamSessionGuard::new(&StreamSession::default(), clock.clone(), clock.now());
+        guard.pause();
+        clock.advance(Duration::from_secs(86400));
+        assert_eq!(guard.check(), None)
+    }
+}
@@ -2,32 +2,106 @@ use crate::common::Image;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
+/// The same image in different sizes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ImageSet {
+    pub images: Vec<Image>,
+}
+
+impl ImageSet {
+    /// Returns the largest image.
+    pub fn largest(&self) -> Option<&Image> {
+        largest(&self.images)
+    }
+
+    /// Returns the image whose size is the closest to the given size.
+    pub fn closest_to(&self, width: u32, height: u32) -> Option<&Image> {
+        closest_to(&self.images, width, height)
+    }
+}
+
+/// Crunchyroll may deliver multiple variants of an image (e.g. different art or aspect ratios).
+/// Every variant is a tier which contains the variant in different sizes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ImageVariants {
+    pub tiers: Vec<ImageSet>,
+}
+
+impl ImageVariants {
+    /// Returns the images of all tiers in a single list.
+    pub fn flatten(&self) -> Vec<Image> {
+        self.tiers
+            .iter()
+            .flat_map(|tier| tier.images.clone())
+            .collect()
+    }
+}
+
+impl TryFrom<Option<&Value>> for ImageVariants {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Option<&Value>) -> Result<Self, Self::Error> {
+        let Some(value) = value else {
+            return Ok(ImageVariants::default());
+        };
+
+        if let Ok(tiers) = serde_json::from_value::<Vec<Vec<Image>>>(value.clone()) {
+            Ok(ImageVariants {
+                tiers: tiers
+                    .into_iter()
+                    .map(|images| ImageSet { images })
+                    .collect(),
+            })
+        } else {
+            let images: Vec<Image> = serde_json::from_value(value.clone())?;
+            Ok(ImageVariants {
+                tiers: if images.is_empty() {
+                    vec![]
+                } else {
+                    vec![ImageSet { images }]
+                },
+            })
+        }
+    }
+}
+
 /// Images for a [`crate::Movie`] or [`crate::Concert`].
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(try_from = "Map<String, Value>")]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct ThumbnailImages {
+    /// All thumbnails of [`ThumbnailImages::thumbnail_variants`] in a single list.
     pub thumbnail: Vec<Image>,
+    pub thumbnail_variants: ImageVariants,
+}
+
+impl ThumbnailImages {
+    /// Returns the largest thumbnail.
+    pub fn largest(&self) -> Option<&Image> {
+        largest(&self.thumbnail)
+    }
+
+    /// Returns the thumbnail whose size is the closest to the given size.
+    pub fn closest_to(&self, width: u32, height: u32) -> Option<&Image> {
+        closest_to(&self.thumbnail, width, height)
+    }
 }
 
 impl TryFrom<Map<String, Value>> for ThumbnailImages {
     type Error = serde_json::Error;
 
     fn try_from(value: Map<String, Value>) -> Result<Self, Self::Error> {
-        if let Some(thumbnail) = value.get("thumbnail") {
-            if let Ok(thumb) = serde_json::from_value::<Vec<Vec<Image>>>(thumbnail.clone()) {
-                Ok(ThumbnailImages {
-                    thumbnail: thumb.into_iter().flatten().collect::<Vec<Image>>(),
-                })
-            } else {
-                Ok(ThumbnailImages {
-                    thumbnail: serde_json::from_value(thumbnail.clone())?,
-                })
-            }
-        } else {
-            Ok(ThumbnailImages { thumbnail: vec![] })
-        }
+        // 'thumbnail_variants' is only present if the images were exported via serialization
+        let thumbnail_variants = match value.get("thumbnail_variants") {
+            Some(variants) => serde_json::from_value(variants.clone())?,
+            None => ImageVariants::try_from(value.get("thumbnail"))?,
+        };
+
+        Ok(ThumbnailImages {
+            thumbnail: thumbnail_variants.flatten(),
+            thumbnail_variants,
+        })
     }
 }
 
@@ -37,36 +111,183 @@ impl TryFrom<Map<String, Value>> for ThumbnailImages {
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
 pub struct PosterImages {
+    /// All images of [`PosterImages::poster_tall_variants`] in a single list.
     pub poster_tall: Vec<Image>,
+    /// All images of [`PosterImages::poster_wide_variants`] in a single list.
     pub poster_wide: Vec<Image>,
+    pub poster_tall_variants: ImageVariants,
+    pub poster_wide_variants: ImageVariants,
+}
+
+impl PosterImages {
+    /// Returns the largest tall poster.
+    pub fn largest_tall(&self) -> Option<&Image> {
+        largest(&self.poster_tall)
+    }
+
+    /// Returns the largest wide poster.
+    pub fn largest_wide(&self) -> Option<&Image> {
+        largest(&self.poster_wide)
+    }
+
+    /// Returns the tall poster whose size is the closest to the given size.
+    pub fn closest_tall_to(&self, width: u32, height: u32) -> Option<&Image> {
+        closest_to(&self.poster_tall, width, height)
+    }
+
+    /// Returns the wide poster whose size is the closest to the given size.
+    pub fn closest_wide_to(&self, width: u32, height: u32) -> Option<&Image> {
+        closest_to(&self.poster_wide, width, height)
+    }
 }
 
 impl TryFrom<Map<String, Value>> for PosterImages {
     type Error = serde_json::Error;
 
     fn try_from(value: Map<String, Value>) -> Result<Self, Self::Error> {
-        let tall = if let Some(tall) = value.get("poster_tall") {
-            if let Ok(img) = serde_json::from_value::<Vec<Vec<Image>>>(tall.clone()) {
-                img.into_iter().flatten().collect::<Vec<Image>>()
-            } else {
-                serde_json::from_value(tall.clone())?
-            }
-        } else {
-            vec![]
+        // the '*_variants' fields are only present if the images were exported via serialization
+        let tall = match value.get("poster_tall_variants") {
+            Some(variants) => serde_json::from_value(variants.clone())?,
+            None => ImageVariants::try_from(value.get("poster_tall"))?,
         };
-        let wide = if let Some(wide) = value.get("poster_wide") {
-            if let Ok(img) = serde_json::from_value::<Vec<Vec<Image>>>(wide.clone()) {
-                img.into_iter().flatten().collect::<Vec<Image>>()
-            } else {
-                serde_json::from_value(wide.clone())?
-            }
-        } else {
-            vec![]
+        let wide = match value.get("poster_wide_variants") {
+            Some(variants) => serde_json::from_value(variants.clone())?,
+            None => ImageVariants::try_from(value.get("poster_wide"))?,
         };
 
         Ok(Self {
-            poster_tall: tall,
-            poster_wide: wide,
+            poster_tall: tall.flatten(),
+            poster_wide: wide.flatten(),
+            poster_tall_variants: tall,
+            poster_wide_variants: wide,
+        })
+    }
+}
+
+pub(crate) fn largest(images: &[Image]) -> Option<&Image> {
+    images
+        .iter()
+        .max_by_key(|i| i.width as u64 * i.height as u64)
+}
+
+fn closest_to(images: &[Image], width: u32, height: u32) -> Option<&Image> {
+    // prefer the larger image if two images are equally close, downscaling looks better than
+    // upscaling
+    images.iter().min_by_key(|i| {
+        (
+            i.width.abs_diff(width) as u64 + i.height.abs_diff(height) as u64,
+            u64::MAX - i.width as u64 * i.height as u64,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Image {
+        Image {
+            source: format!("{width}x{height}"),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn largest_image() {
+        let images = vec![image(640, 360), image(1920, 1080), image(1280, 720)];
+        assert_eq!(largest(&images).unwrap().source, "1920x1080");
+        assert!(largest(&[]).is_none());
+
+        // the area of these images doesn't fit into an u32
+        let images = vec![image(70000, 70000), image(80000, 60000)];
+        assert_eq!(largest(&images).unwrap().source, "70000x70000")
+    }
+
+    #[test]
+    fn closest_image() {
+        let images = vec![image(640, 360), image(1920, 1080), image(1280, 720)];
+        assert_eq!(closest_to(&images, 1200, 700).unwrap().source, "1280x720");
+        assert_eq!(closest_to(&images, 0, 0).unwrap().source, "640x360");
+        assert_eq!(closest_to(&images, 4000, 4000).unwrap().source, "1920x1080");
+
+        // both are 100 pixels off, the larger one is preferred
+        let images = vec![image(900, 500), image(1100, 500)];
+        assert_eq!(closest_to(&images, 1000, 500).unwrap().source, "1100x500");
+        assert!(closest_to(&[], 1000, 500).is_none())
+    }
+
+    fn api_image(width: u32, height: u32) -> Value {
+        serde_json::json!({
+            "source": format!("{width}x{height}"),
+            "type": "poster_tall",
+            "width": width,
+            "height": height
         })
     }
+
+    #[test]
+    fn image_variants_from_api() {
+        // multiple tiers
+        let value = Value::Array(vec![
+            Value::Array(vec![api_image(60, 90), api_image(480, 720)]),
+            Value::Array(vec![api_image(100, 150)]),
+        ]);
+        let variants = ImageVariants::try_from(Some(&value)).unwrap();
+        assert_eq!(variants.tiers.len(), 2);
+        assert_eq!(variants.tiers[0].images.len(), 2);
+        assert_eq!(variants.flatten().len(), 3);
+
+        // a flat list is a single tier
+        let value = Value::Array(vec![api_image(60, 90), api_image(480, 720)]);
+        let variants = ImageVariants::try_from(Some(&value)).unwrap();
+        assert_eq!(variants.tiers.len(), 1);
+        assert_eq!(variants.tiers[0].images[0].image_type, "poster_tall");
+
+        assert!(ImageVariants::try_from(Some(&Value::Array(vec![])))
+            .unwrap()
+            .tiers
+            .is_empty());
+        assert!(ImageVariants::try_from(None).unwrap().tiers.is_empty())
+    }
+
+    #[test]
+    fn poster_images_round_trip() {
+        let value = serde_json::json!({
+            "poster_tall": [[api_image(60, 90), api_image(480, 720)], [api_image(100, 150)]],
+            "poster_wide": [[api_image(1920, 1080)]]
+        });
+        let images: PosterImages = serde_json::from_value(value).unwrap();
+        assert_eq!(images.poster_tall.len(), 3);
+        assert_eq!(images.poster_tall_variants.tiers.len(), 2);
+        assert_eq!(images.largest_wide().unwrap().source, "1920x1080");
+
+        // the exported images contain both, the flattened and the '*_variants' fields. the tiers
+        // must survive re-importing them
+        let round_trip: PosterImages =
+            serde_json::from_value(serde_json::to_value(&images).unwrap()).unwrap();
+        assert_eq!(round_trip.poster_tall.len(), 3);
+        assert_eq!(round_trip.poster_tall_variants.tiers.len(), 2);
+        assert_eq!(round_trip.poster_wide_variants.tiers.len(), 1);
+        assert_eq!(
+            round_trip.largest_tall().unwrap().source,
+            images.largest_tall().unwrap().source
+        )
+    }
+
+    #[test]
+    fn thumbnail_images_round_trip() {
+        let value = serde_json::json!({
+            "thumbnail": [[api_image(320, 180), api_image(1920, 1080)]]
+        });
+        let images: ThumbnailImages = serde_json::from_value(value).unwrap();
+        assert_eq!(images.thumbnail.len(), 2);
+
+        let round_trip: ThumbnailImages =
+            serde_json::from_value(serde_json::to_value(&images).unwrap()).unwrap();
+        assert_eq!(round_trip.thumbnail.len(), 2);
+        assert_eq!(round_trip.thumbnail_variants.tiers.len(), 1);
+        assert_eq!(round_trip.closest_to(300, 200).unwrap().source, "320x180")
+    }
 }
@@ -0,0 +1,90 @@
+use crate::crunchyroll::MaturityRating;
+use crate::macros::enum_values;
+use serde::{Deserialize, Serialize};
+
+enum_values! {
+    /// Rating systems which Crunchyroll uses to classify content. Which one is used depends on the
+    /// region the request was made from. `UsTv` are the US TV parental guidelines (e.g. `TV-14`),
+    /// `ClassInd` the brazilian advisory rating (e.g. `L` or `16`) and `Fsk` the german FSK (e.g.
+    /// `12`).
+    pub enum RatingSystem {
+        UsTv = "TV"
+        ClassInd = "BR"
+        Fsk = "FSK"
+    }
+}
+
+enum_values! {
+    /// Advisory descriptors about the content of a media, shown next to its maturity rating.
+    /// Descriptors which aren't known (yet) are [`ContentDescriptor::Custom`].
+    pub enum ContentDescriptor {
+        Violence = "Violence"
+        Gore = "Gore"
+        SexualizedImagery = "Sexualized Imagery"
+        SexualContent = "Sexual Content"
+        Nudity = "Nudity"
+        Language = "Language"
+        Profanity = "Profanity"
+        DrugUse = "Drug Use"
+        AlcoholUse = "Alcohol Use"
+        TobaccoUse = "Tobacco Use"
+        Fear = "Fear"
+        Discrimination = "Discrimination"
+        SelfHarm = "Self-harm"
+        Suicide = "Suicide"
+    }
+}
+
+/// Region specific maturity rating of a media.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
+pub struct ExtendedMaturityRating {
+    /// The crunchyroll internal maturity level.
+    pub level: MaturityRating,
+    /// The rating in the format of [`ExtendedMaturityRating::system`], e.g. `TV-14` or `16`.
+    pub rating: String,
+    pub system: RatingSystem,
+}
+
+impl ExtendedMaturityRating {
+    /// Returns the minimum age which is required to watch the media, based on
+    /// [`ExtendedMaturityRating::rating`]. US TV ratings are mapped to `TV-Y` / `TV-G` = 0,
+    /// `TV-Y7` = 7, `TV-PG` = 10, `TV-14` = 14 and `TV-MA` = 17. Numeric ratings (like `16`,
+    /// `16+` or `FSK 16`) are used as they are. Returns [`None`] if the rating is unknown.
+    pub fn min_age(&self) -> Option<u8> {
+        let rating = self.rating.trim().to_ascii_uppercase();
+
+        if let Some(tv_rating) = rating.strip_prefix("TV-") {
+            return match tv_rating {
+                "Y" | "G" => Some(0),
+                "Y7" | "Y7-FV" => Some(7),
+                "PG" => Some(10),
+                "14" => Some(14),
+                "MA" => Some(17),
+                _ => None,
+            };
+        }
+        // 'L' ('Livre') is the brazilian rating for content suitable for all ages
+        if rating == "L" {
+            return Some(0);
+        }
+
+        rating
+            .trim_start_matches("FSK")
+            .trim()
+            .trim_end_matches('+')
+            .parse()
+            .ok()
+    }
+
+    /// Checks if a viewer of the given age is too young to watch the media. If the minimum age is
+    /// unknown (see [`ExtendedMaturityRating::min_age`]), `is_mature` is used as fallback and
+    /// treated as a minimum age of 18.
+    pub(crate) fn is_age_restricted(&self, age: u8, is_mature: bool) -> bool {
+        match self.min_age() {
+            Some(min_age) => age < min_age,
+            None => is_mature && age < 18,
+        }
+    }
+}
@@ -1,3 +1,5 @@
 mod image;
+mod maturity;
 
 pub use image::*;
+pub use maturity::*;
@@ -1,10 +1,14 @@
-use crate::error::{is_request_error, Error};
+use crate::common::Cancellation;
+use crate::error::{is_request_error, request_id, Error};
+use crate::media::StreamSessionGuard;
 use crate::{Crunchyroll, Executor, Locale, Request, Result};
+use chrono::{DateTime, Utc};
 use dash_mpd::MPD;
 use reqwest::StatusCode;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::io::Write;
 use std::iter;
 use std::sync::Arc;
 use std::time::Duration;
@@ -17,10 +21,57 @@ fn deserialize_hardsubs<'de, D: Deserializer<'de>>(
         url: String,
     }
 
-    Ok(HashMap::<String, HardSub>::deserialize(deserializer)?
-        .into_iter()
-        .map(|(l, hs)| (Locale::from(l), hs.url))
-        .collect())
+    Ok(
+        crate::internal::serde::deserialize_locale_map::<_, HardSub>(deserializer)?
+            .into_iter()
+            .map(|(l, hs)| (l, hs.url))
+            .collect(),
+    )
+}
+
+fn deserialize_bifs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<BifReference>, D::Error> {
+    // the field is either a single url, a list of urls or a list of objects containing the url
+    let value = serde_json::Value::deserialize(deserializer)?;
+    let values = match value {
+        serde_json::Value::Array(array) => array,
+        serde_json::Value::Null => vec![],
+        other => vec![other],
+    };
+
+    let mut bifs = vec![];
+    for value in values {
+        let url = match value {
+            serde_json::Value::String(url) => url,
+            serde_json::Value::Object(mut object) => match object.remove("url") {
+                Some(serde_json::Value::String(url)) => url,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if url.is_empty() {
+            continue;
+        }
+        bifs.push(BifReference {
+            executor: Default::default(),
+            resolution: resolution_from_url(&url),
+            url,
+        })
+    }
+    Ok(bifs)
+}
+
+/// Extracts a resolution in the format `<width>x<height>` from an url, if present.
+fn resolution_from_url(url: &str) -> Option<Resolution> {
+    url.split(['/', '_', '-', '.', '?', '&', '='])
+        .find_map(|part| {
+            let (width, height) = part.split_once('x')?;
+            Some(Resolution {
+                width: width.parse().ok()?,
+                height: height.parse().ok()?,
+            })
+        })
 }
 
 /// Platforms that can request a [`Stream`]. Because not all platforms have their own variant, use
@@ -52,6 +103,114 @@ pub enum StreamPlatform {
     },
 }
 
+impl StreamPlatform {
+    /// All platforms which have their own variant, in the order [`Stream::from_id_auto`] tries them
+    /// by default.
+    pub fn all() -> Vec<StreamPlatform> {
+        vec![
+            StreamPlatform::WebChrome,
+            StreamPlatform::WebFirefox,
+            StreamPlatform::WebEdge,
+            StreamPlatform::WebSafari,
+            StreamPlatform::AndroidPhone,
+            StreamPlatform::AndroidTablet,
+            StreamPlatform::IosIphone,
+            StreamPlatform::IosIpad,
+            StreamPlatform::IosVision,
+            StreamPlatform::TvSamsung,
+            StreamPlatform::TvLg,
+            StreamPlatform::TvRoku,
+            StreamPlatform::ConsolePs5,
+            StreamPlatform::ConsolePs4,
+            StreamPlatform::ConsoleXboxOne,
+            StreamPlatform::ConsoleSwitch,
+        ]
+    }
+
+    /// User agent of this platform. Crunchyroll only delivers H.265 and 4K streams to tv platforms
+    /// if the user agent matches the platform, [`Stream::from_id_with_preference`] sends it when
+    /// requesting the stream ([`Stream::from_id`] always uses the user agent of the client).
+    /// [`None`] if the platform has no dedicated user agent.
+    pub fn user_agent(&self) -> Option<&'static str> {
+        let user_agent = match self {
+            StreamPlatform::TvSamsung => "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36",
+            StreamPlatform::TvLg => "Mozilla/5.0 (Web0S; Linux/SmartTV) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/79.0.3945.79 Safari/537.36 WebAppManager",
+            StreamPlatform::TvRoku => "Roku/DVP-12.0 (12.0.0.4182-88)",
+            _ => return None,
+        };
+        Some(user_agent)
+    }
+}
+
+/// Requirements the stream data of [`Stream::from_id_auto`] must meet.
+#[derive(Clone, Debug, Default)]
+pub struct StreamRequirements {
+    drm_free: bool,
+    video_codecs: Vec<String>,
+    hardsub: Option<Locale>,
+    platforms: Option<Vec<StreamPlatform>>,
+}
+
+impl StreamRequirements {
+    pub fn new() -> StreamRequirements {
+        Self::default()
+    }
+
+    /// Require video and audio streams which aren't DRM encrypted.
+    pub fn drm_free(mut self) -> StreamRequirements {
+        self.drm_free = true;
+        self
+    }
+
+    /// Require at least one video stream whose codec starts with `codec`, e.g. `avc1` for H.264.
+    /// If called multiple times, any of the codecs matches.
+    pub fn video_codec<S: AsRef<str>>(mut self, codec: S) -> StreamRequirements {
+        self.video_codecs.push(codec.as_ref().to_lowercase());
+        self
+    }
+
+    /// Require at least one H.265 / HEVC video stream.
+    pub fn h265(self) -> StreamRequirements {
+        self.video_codec("hvc1").video_codec("hev1")
+    }
+
+    /// Hardsub the stream data should have. Default is no hardsub.
+    pub fn hardsub(mut self, hardsub: Locale) -> StreamRequirements {
+        self.hardsub = Some(hardsub);
+        self
+    }
+
+    /// Platforms which should be tried, in the given order. Default is [`StreamPlatform::all`].
+    pub fn platforms(mut self, platforms: Vec<StreamPlatform>) -> StreamRequirements {
+        self.platforms = Some(platforms);
+        self
+    }
+
+    fn matched_by(&self, stream_data: &StreamData) -> bool {
+        let streams = || stream_data.video.iter().chain(&stream_data.audio);
+        if self.drm_free && streams().any(|stream| stream.drm.is_some()) {
+            return false;
+        }
+        self.video_codecs.is_empty()
+            || stream_data.video.iter().any(|stream| {
+                let codecs = stream.codecs.to_lowercase();
+                self.video_codecs
+                    .iter()
+                    .any(|codec| codecs.starts_with(codec.as_str()))
+            })
+    }
+}
+
+/// Result of [`Stream::from_id_auto`].
+#[derive(Clone, Debug)]
+pub struct AutoStream {
+    /// The platform which yielded a matching stream.
+    pub platform: StreamPlatform,
+    pub stream: Stream,
+    /// The stream data of [`AutoStream::stream`], which matches the requirements.
+    pub stream_data: StreamData,
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
@@ -71,6 +230,8 @@ pub struct StreamVersion {
     #[serde(rename = "season_guid")]
     pub season_id: String,
 
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
 
     pub is_premium_only: bool,
@@ -86,16 +247,34 @@ impl StreamVersion {
     /// have less/no active other [`Stream`]s open (through this crate or as stream in the browser
     /// or app).
     pub async fn stream(&self) -> Result<Stream> {
+        self.stream_with_platform(self.platform.clone()).await
+    }
+
+    /// Requests an actual [`Stream`] from this version, but on a different platform than the
+    /// [`Stream`] this version belongs to.
+    pub async fn stream_with_platform(&self, stream_platform: StreamPlatform) -> Result<Stream> {
         Stream::from_id(
             &Crunchyroll {
                 executor: self.executor.clone(),
             },
             &self.id,
-            self.platform.clone(),
+            stream_platform,
             self.optional_media_type.clone(),
         )
         .await
     }
+
+    /// Returns the hardsub locales this version offers. The version metadata doesn't contain them,
+    /// so the [`Stream`] of this version must be requested and is invalidated right after. This
+    /// costs two requests, and while the stream is open it counts against the active stream limit
+    /// of the account: if the limit is already reached, this fails with
+    /// [`ApiErrorCode::TooManyActiveStreams`](crate::error::ApiErrorCode::TooManyActiveStreams).
+    pub async fn hardsub_locales(&self) -> Result<Vec<Locale>> {
+        let stream = self.stream().await?;
+        let hardsub_locales = stream.hard_subs.keys().cloned().collect();
+        stream.invalidate().await?;
+        Ok(hardsub_locales)
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -110,8 +289,40 @@ pub struct StreamSession {
     pub uses_stream_limits: bool,
 }
 
+impl StreamSession {
+    /// [`StreamSession::renew_seconds`] as [`Duration`].
+    pub fn renew_interval(&self) -> Duration {
+        Duration::from_secs(self.renew_seconds as u64)
+    }
+
+    /// [`StreamSession::no_network_retry_interval_seconds`] as [`Duration`].
+    pub fn no_network_retry_interval(&self) -> Duration {
+        Duration::from_secs(self.no_network_retry_interval_seconds as u64)
+    }
+
+    /// [`StreamSession::no_network_timeout_seconds`] as [`Duration`].
+    pub fn no_network_timeout(&self) -> Duration {
+        Duration::from_secs(self.no_network_timeout_seconds as u64)
+    }
+
+    /// [`StreamSession::maximum_pause_seconds`] as [`Duration`].
+    pub fn maximum_pause(&self) -> Duration {
+        Duration::from_secs(self.maximum_pause_seconds as u64)
+    }
+
+    /// [`StreamSession::end_of_video_unload_seconds`] as [`Duration`].
+    pub fn end_of_video_unload(&self) -> Duration {
+        Duration::from_secs(self.end_of_video_unload_seconds as u64)
+    }
+
+    /// [`StreamSession::session_expiration_seconds`] as [`Duration`].
+    pub fn session_expiration(&self) -> Duration {
+        Duration::from_secs(self.session_expiration_seconds as u64)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, smart_default::SmartDefault, Request)]
-#[request(executor(versions))]
+#[request(executor(subtitles, captions, versions, bifs))]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
 #[cfg_attr(not(feature = "__test_strict"), serde(default))]
@@ -120,15 +331,22 @@ pub struct Stream {
     executor: Arc<Executor>,
 
     pub url: String,
+    /// Unknown (see [`Locale::is_unknown`]) if Crunchyroll doesn't specify it.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale")]
     pub audio_locale: Locale,
-    #[serde(deserialize_with = "crate::internal::serde::deserialize_empty_pre_string_to_none")]
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub burned_in_locale: Option<Locale>,
 
     #[serde(deserialize_with = "deserialize_hardsubs")]
     pub hard_subs: HashMap<Locale, String>,
 
     /// All subtitles.
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale_map")]
     pub subtitles: HashMap<Locale, Subtitle>,
+    #[serde(deserialize_with = "crate::internal::serde::deserialize_locale_map")]
     pub captions: HashMap<Locale, Subtitle>,
 
     pub token: String,
@@ -139,17 +357,21 @@ pub struct Stream {
     /// All versions of this stream (same stream but each entry has a different language).
     pub versions: Vec<StreamVersion>,
 
+    /// Trick-play image archives of this stream, used to show preview images while seeking.
+    #[serde(deserialize_with = "deserialize_bifs")]
+    pub bifs: Vec<BifReference>,
+
     #[serde(skip)]
     id: String,
     #[serde(skip)]
     optional_media_type: Option<String>,
+    #[serde(skip)]
+    requested_at: DateTime<Utc>,
 
     #[cfg(feature = "__test_strict")]
     asset_id: crate::StrictValue,
     #[cfg(feature = "__test_strict")]
     playback_type: Option<crate::StrictValue>,
-    #[cfg(feature = "__test_strict")]
-    bifs: crate::StrictValue,
 }
 
 impl Stream {
@@ -159,6 +381,16 @@ impl Stream {
         id: impl AsRef<str>,
         stream_platform: StreamPlatform,
         optional_media_type: Option<String>,
+    ) -> Result<Self> {
+        Stream::request(crunchyroll, id, stream_platform, optional_media_type, None).await
+    }
+
+    async fn request(
+        crunchyroll: &Crunchyroll,
+        id: impl AsRef<str>,
+        stream_platform: StreamPlatform,
+        optional_media_type: Option<String>,
+        user_agent: Option<&str>,
     ) -> Result<Self> {
         let (device, platform) = match &stream_platform {
             StreamPlatform::AndroidPhone => ("android", "phone"),
@@ -189,14 +421,16 @@ impl Stream {
             id.as_ref()
         );
 
-        let mut stream = crunchyroll
-            .executor
-            .get(endpoint)
-            .request::<Stream>()
-            .await?;
+        let requested_at = crunchyroll.executor.clock.now();
+        let mut builder = crunchyroll.executor.get(endpoint);
+        if let Some(user_agent) = user_agent {
+            builder = builder.header(reqwest::header::USER_AGENT, user_agent)
+        }
+        let mut stream = builder.request::<Stream>().await?;
         stream.__set_executor(crunchyroll.executor.clone()).await;
         stream.id = id.as_ref().to_string();
         stream.optional_media_type = optional_media_type;
+        stream.requested_at = requested_at;
 
         for version in &mut stream.versions {
             version.platform = stream_platform.clone();
@@ -208,6 +442,84 @@ impl Stream {
         Ok(stream)
     }
 
+    /// Like [`Stream::from_id`], but requests the stream on the platform which
+    /// [`StreamPreference::platform`] suggests for the given preference. If this is a tv platform,
+    /// its [`StreamPlatform::user_agent`] is sent along. Use [`StreamData::preferred`] with the
+    /// same preference to select the streams afterward.
+    pub async fn from_id_with_preference(
+        crunchyroll: &Crunchyroll,
+        id: impl AsRef<str>,
+        preference: &StreamPreference,
+        optional_media_type: Option<String>,
+    ) -> Result<Self> {
+        let platform = preference.platform();
+        let user_agent = platform.user_agent();
+        Stream::request(crunchyroll, id, platform, optional_media_type, user_agent).await
+    }
+
+    /// Requests the stream of the given id on multiple platforms until one yields stream data
+    /// which meets `requirements`, e.g. to get a stream without DRM or with H.265 video. The
+    /// platforms are tried in the order of [`StreamRequirements::platforms`]; streams which don't
+    /// match or whose stream data can't be requested are invalidated right away. Every tried
+    /// platform requests stream data, which counts towards the stream limit (see
+    /// [`Stream::stream_data`]). Returns [`Error::NoMatchingStream`] if no platform yields a
+    /// matching stream.
+    pub async fn from_id_auto(
+        crunchyroll: &Crunchyroll,
+        id: impl AsRef<str>,
+        requirements: StreamRequirements,
+        optional_media_type: Option<String>,
+    ) -> Result<AutoStream> {
+        let platforms = requirements
+            .platforms
+            .clone()
+            .unwrap_or_else(StreamPlatform::all);
+
+        let mut tried = vec![];
+        for platform in platforms {
+            let stream = match Stream::from_id(
+                crunchyroll,
+                id.as_ref(),
+                platform.clone(),
+                optional_media_type.clone(),
+            )
+            .await
+            {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tried.push(format!("{platform:?} ({e})"));
+                    continue;
+                }
+            };
+            let mut attempt = match stream.stream_data(requirements.hardsub.clone()).await {
+                Ok(Some(stream_data)) if requirements.matched_by(&stream_data) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(platform = ?platform, "found stream matching the requirements");
+                    return Ok(AutoStream {
+                        platform,
+                        stream,
+                        stream_data,
+                    });
+                }
+                Ok(_) => format!("{platform:?}"),
+                Err(e) => format!("{platform:?} ({e})"),
+            };
+            // a failed invalidation doesn't stop the search, the other platforms are tried anyway
+            if let Err(e) = stream.invalidate().await {
+                attempt = format!("{attempt} (invalidate failed: {e})")
+            }
+            tried.push(attempt)
+        }
+
+        Err(Error::NoMatchingStream {
+            message: format!(
+                "no platform yields a stream of {} which matches the requirements",
+                id.as_ref()
+            ),
+            tried,
+        })
+    }
+
     /// Requests all available video and audio streams. Returns [`None`] if the requested hardsub
     /// isn't available.
     /// You will run into an error when requesting this function too often without invalidating the
@@ -215,38 +527,234 @@ impl Stream {
     /// time, typically the exact amount depends on the type of (premium) subscription you have. You
     /// can use [`Stream::invalidate`] to invalidate all stream data for this stream.
     pub async fn stream_data(&self, hardsub: Option<Locale>) -> Result<Option<StreamData>> {
+        let Some(url) = self.manifest_url(hardsub) else {
+            return Ok(None);
+        };
+        Ok(Some(
+            StreamData::from_url(
+                self.executor.clone(),
+                url,
+                &self.token,
+                &self.id,
+                &self.audio_locale,
+            )
+            .await?,
+        ))
+    }
+
+    /// Requests the raw DASH manifest (MPD) of this stream. Use this if you need data which
+    /// [`StreamData`] doesn't expose. Returns [`None`] if the requested hardsub isn't available.
+    /// Like [`Stream::stream_data`], requesting the manifest counts towards the stream limit.
+    pub async fn manifest_raw(&self, hardsub: Option<Locale>) -> Result<Option<Vec<u8>>> {
+        let Some(url) = self.manifest_url(hardsub) else {
+            return Ok(None);
+        };
+        Ok(Some(
+            request_manifest(&self.executor, url, &self.token).await?,
+        ))
+    }
+
+    /// Requests the DASH manifest (MPD) of this stream and parses it. See [`Stream::manifest_raw`]
+    /// for more information.
+    pub async fn manifest(&self, hardsub: Option<Locale>) -> Result<Option<MPD>> {
+        let Some(url) = self.manifest_url(hardsub) else {
+            return Ok(None);
+        };
+        let raw = request_manifest(&self.executor, url, &self.token).await?;
+        Ok(Some(parse_manifest(raw, url)?))
+    }
+
+    fn manifest_url(&self, hardsub: Option<Locale>) -> Option<&String> {
         if let Some(hardsub) = hardsub {
-            let Some(url) = self
-                .hard_subs
-                .iter()
-                .find_map(|(locale, url)| (locale == &hardsub).then_some(url))
-            else {
-                return Ok(None);
+            self.hard_subs.get(&hardsub)
+        } else {
+            Some(&self.url)
+        }
+    }
+
+    /// Creates a [`dash_mpd::fetch::DashDownloader`] which is configured to download this stream.
+    /// It uses the same client as this crate (and therefore the same cookies) and the current
+    /// access token for authentication. The access token is only valid for a few minutes, so the
+    /// downloader should be used right after it was created. Returns [`None`] if the requested
+    /// hardsub isn't available.
+    #[cfg(feature = "dash-downloader")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dash-downloader")))]
+    pub async fn to_dash_downloader(
+        &self,
+        hardsub: Option<Locale>,
+    ) -> Result<Option<dash_mpd::fetch::DashDownloader>> {
+        let Some(url) = self.manifest_url(hardsub) else {
+            return Ok(None);
+        };
+        let mut url = reqwest::Url::parse(url).map_err(|e| Error::Internal {
+            message: format!("invalid stream url '{url}': {e}"),
+        })?;
+        url.query_pairs_mut()
+            .append_pair(
+                "accountid",
+                &self.executor.details.account_id.clone().unwrap_or_default(),
+            )
+            .append_pair("playbackGuid", &self.token);
+
+        // refreshes the access token if it's expired. the request itself isn't needed
+        let _ = self
+            .executor
+            .auth_req(self.executor.client.get(url.clone()))
+            .await?;
+        let access_token = self.executor.config.read().await.access_token.clone();
+
+        Ok(Some(
+            dash_mpd::fetch::DashDownloader::new(url.as_str())
+                .with_http_client(self.executor.client.clone())
+                .with_auth_bearer(access_token),
+        ))
+    }
+
+    /// Like [`Stream::stream_data`] but walks through the given hardsub locale preferences (see
+    /// [`Stream::hardsub_locale`] for how they're matched) and uses the first available one. If no
+    /// preferred hardsub is available, the stream data without hardsub is returned. The returned
+    /// locale is the hardsub which was actually used, [`None`] if no hardsub is used.
+    pub async fn stream_data_with_fallback(
+        &self,
+        hardsub_preferences: &[Locale],
+    ) -> Result<(StreamData, Option<Locale>)> {
+        let hardsub = self.hardsub_locale(hardsub_preferences);
+        let stream_data =
+            self.stream_data(hardsub.clone())
+                .await?
+                .ok_or_else(|| Error::Internal {
+                    message: "Stream data of an available hardsub is missing".to_string(),
+                })?;
+        Ok((stream_data, hardsub))
+    }
+
+    /// Returns the first subtitle which matches `locale_preferences`. See
+    /// [`Stream::hardsub_locale`] for how the preferences are matched.
+    pub fn subtitle(&self, locale_preferences: &[Locale]) -> Option<&Subtitle> {
+        find_by_locale_preferences(&self.subtitles, locale_preferences).map(|(_, s)| s)
+    }
+
+    /// Returns the first caption which matches `locale_preferences`. See
+    /// [`Stream::hardsub_locale`] for how the preferences are matched.
+    pub fn caption(&self, locale_preferences: &[Locale]) -> Option<&Subtitle> {
+        find_by_locale_preferences(&self.captions, locale_preferences).map(|(_, c)| c)
+    }
+
+    /// Returns the first hardsub locale which matches `locale_preferences`. The returned locale
+    /// can be passed to [`Stream::stream_data`]. All preferences are checked in order for an
+    /// exact match first. If none matches, the preferences are checked again in order, but this
+    /// time any locale with the same language matches (e.g. [`Locale::en_IN`] if
+    /// [`Locale::en_US`] is preferred).
+    pub fn hardsub_locale(&self, locale_preferences: &[Locale]) -> Option<Locale> {
+        find_by_locale_preferences(&self.hard_subs, locale_preferences).map(|(l, _)| l.clone())
+    }
+
+    /// Returns the first version which has an audio locale of `audio_locales`. The locales are
+    /// checked in order. Use [`StreamVersion::stream`] to get the actual stream of the version.
+    pub fn version(&self, audio_locales: &[Locale]) -> Option<&StreamVersion> {
+        audio_locales
+            .iter()
+            .find_map(|locale| self.versions.iter().find(|v| &v.audio_locale == locale))
+    }
+
+    /// Returns the version with the original audio of the media.
+    pub fn original_version(&self) -> Option<&StreamVersion> {
+        self.versions.iter().find(|v| v.original)
+    }
+
+    /// Returns the audio locales of all versions. If the stream has no versions, only
+    /// [`Stream::audio_locale`] is returned.
+    pub fn available_audio_locales(&self) -> Vec<Locale> {
+        let mut audio_locales: Vec<Locale> = vec![];
+        for version in &self.versions {
+            if !audio_locales.contains(&version.audio_locale) {
+                audio_locales.push(version.audio_locale.clone())
+            }
+        }
+        if audio_locales.is_empty() {
+            audio_locales.push(self.audio_locale.clone())
+        }
+        audio_locales
+    }
+
+    /// Returns the hardsub locales of every version of this stream. The hardsub locales of the
+    /// version this stream represents are taken from [`Stream::hard_subs`], for all other versions
+    /// their stream has to be requested (see [`StreamVersion::hardsub_locales`] for the cost of
+    /// it). The streams are requested one after another, so besides this stream at most one more
+    /// stream is active at a time.
+    pub async fn versions_hardsub_locales(&self) -> Result<Vec<(StreamVersion, Vec<Locale>)>> {
+        let mut result = vec![];
+        for version in &self.versions {
+            let hardsub_locales = if version.id == self.id {
+                self.hard_subs.keys().cloned().collect()
+            } else {
+                version.hardsub_locales().await?
             };
-            Ok(Some(
-                StreamData::from_url(
-                    self.executor.clone(),
-                    url,
-                    &self.token,
-                    &self.id,
-                    &self.audio_locale,
-                )
-                .await?,
-            ))
+            result.push((version.clone(), hardsub_locales))
+        }
+        Ok(result)
+    }
+
+    /// Renews the playback token of this stream. Crunchyroll expects the token to be renewed every
+    /// [`StreamSession::renew_seconds`] seconds, otherwise the stream data obtained from
+    /// [`Stream::stream_data`] might stop working after some time. Use [`Stream::keep_alive`] to
+    /// renew the token continuously.
+    ///
+    /// `playhead` is the current playback position in seconds. It is only sent if it's set, so
+    /// renewing the token of a stream which is e.g. downloaded doesn't reset the playhead.
+    pub async fn renew(&self, playhead: Option<u32>) -> Result<()> {
+        if !self.session.uses_stream_limits {
+            return Ok(());
+        }
+
+        let endpoint = format!(
+            "https://cr-play-service.prd.crunchyrollsvc.com/v1/token/{}/{}/keepAlive",
+            self.id, self.token
+        );
+
+        let mut builder = self.executor.patch(endpoint);
+        if let Some(playhead) = playhead {
+            builder = builder.query(&[("playhead", playhead)])
+        }
+        builder.request_raw(true).await?;
+
+        Ok(())
+    }
+
+    /// Calls [`Stream::renew`] without a playhead every [`StreamSession::renew_seconds`] seconds.
+    /// The returned future only finishes if an error occurs, so it should be run alongside the
+    /// actual download, e.g. via `tokio::select!` or by spawning it as its own task (use a clone of
+    /// this stream in this case).
+    pub async fn keep_alive(&self) -> Result<()> {
+        if !self.session.uses_stream_limits {
+            return std::future::pending().await;
+        }
+
+        // fallback if crunchyroll doesn't deliver a renew interval. 30 seconds is the value which
+        // was always returned at the time of writing
+        let renew_seconds = if self.session.renew_seconds > 0 {
+            self.session.renew_seconds
         } else {
-            Ok(Some(
-                StreamData::from_url(
-                    self.executor.clone(),
-                    &self.url,
-                    &self.token,
-                    &self.id,
-                    &self.audio_locale,
-                )
-                .await?,
-            ))
+            30
+        };
+        let mut interval = tokio::time::interval(Duration::from_secs(renew_seconds as u64));
+        // the first tick completes immediately but the token is fresh at this point
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            self.renew(None).await?
         }
     }
 
+    /// Returns a [`StreamSessionGuard`] which tracks the pause and session limits of this stream.
+    pub fn session_guard(&self) -> StreamSessionGuard {
+        StreamSessionGuard::new(
+            &self.session,
+            self.executor.clock.clone(),
+            self.requested_at,
+        )
+    }
+
     /// Invalidates all the stream data which may be obtained from [`Stream::stream_data`]. You will
     /// run into errors if you request multiple [`Stream::stream_data`]s without invalidating them.
     pub async fn invalidate(self) -> Result<()> {
@@ -265,6 +773,95 @@ impl Stream {
     }
 }
 
+fn find_by_locale_preferences<'a, T>(
+    map: &'a HashMap<Locale, T>,
+    locale_preferences: &[Locale],
+) -> Option<(&'a Locale, &'a T)> {
+    for locale in locale_preferences {
+        if let Some(entry) = map.get_key_value(locale) {
+            return Some(entry);
+        }
+    }
+
+    let language = |locale: &Locale| locale.to_string().split('-').next().unwrap().to_string();
+    // sort to always get the same result if multiple locales with the same language are present
+    let mut entries: Vec<(&Locale, &T)> = map.iter().collect();
+    entries.sort_by_key(|(l, _)| *l);
+    for locale in locale_preferences {
+        let preferred_language = language(locale);
+        if let Some(entry) = entries
+            .iter()
+            .find(|(l, _)| language(l) == preferred_language)
+        {
+            return Some(*entry);
+        }
+    }
+
+    None
+}
+
+/// A stream session which is currently active on the account. Obtained via
+/// [`Crunchyroll::active_streams`].
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
+pub struct ActiveStream {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
+    pub content_id: String,
+    pub token: String,
+    pub device_id: String,
+    pub device_type: String,
+}
+
+impl ActiveStream {
+    /// Invalidates this stream session, just like [`Stream::invalidate`] does.
+    pub async fn invalidate(self) -> Result<()> {
+        let endpoint = format!(
+            "https://cr-play-service.prd.crunchyrollsvc.com/v1/token/{}/{}",
+            self.content_id, self.token
+        );
+        self.executor.delete(endpoint).request_raw(true).await?;
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Deserialize, Request)]
+#[request(executor(items))]
+#[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+#[cfg_attr(not(feature = "__test_strict"), serde(default))]
+struct ActiveStreams {
+    items: Vec<ActiveStream>,
+}
+
+impl Crunchyroll {
+    /// Returns all stream sessions which are currently active on the account, including sessions
+    /// which were opened by other devices or processes.
+    pub async fn active_streams(&self) -> Result<Vec<ActiveStream>> {
+        let endpoint = "https://cr-play-service.prd.crunchyrollsvc.com/v1/sessions/streaming";
+        Ok(self
+            .executor
+            .get(endpoint)
+            .request::<ActiveStreams>()
+            .await?
+            .items)
+    }
+
+    /// Invalidates all active stream sessions of the account (see
+    /// [`Crunchyroll::active_streams`]). Useful to recover from a too many active streams error,
+    /// e.g. if a process crashed before it could call [`Stream::invalidate`].
+    pub async fn invalidate_all_remote_streams(&self) -> Result<()> {
+        for active_stream in self.active_streams().await? {
+            active_stream.invalidate().await?
+        }
+        Ok(())
+    }
+}
+
 /// Subtitle for streams.
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
 #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
@@ -290,225 +887,361 @@ impl Subtitle {
 #[derive(Clone, Debug, Serialize)]
 pub struct StreamData {
     pub audio: Vec<MediaStream>,
+    /// Video streams of the main content.
     pub video: Vec<MediaStream>,
+    /// Trick-mode video streams (low frame rate streams which players use for fast forward /
+    /// seeking previews). They're not included in [`StreamData::video`].
+    pub trickplay: Vec<MediaStream>,
+    /// Video streams of trailers or other supplementary content which is embedded into the
+    /// manifest of the actual content. They're not included in [`StreamData::video`].
+    pub trailers: Vec<MediaStream>,
     pub subtitle: Option<Subtitle>,
 }
 
-impl StreamData {
-    async fn from_url(
-        executor: Arc<Executor>,
-        url: impl AsRef<str>,
-        token: impl AsRef<str>,
-        watch_id: impl AsRef<str>,
-        audio_locale: &Locale,
-    ) -> Result<Self> {
-        let mut video = vec![];
-        let mut audio = vec![];
-        let mut subtitle = None;
+/// Constraints to select streams from [`StreamData`] with [`StreamData::preferred`]. All
+/// constraints are optional, the best stream which matches all set constraints is selected.
+#[derive(Clone, Debug, Default)]
+pub struct StreamPreference {
+    video_codec: Option<String>,
+    audio_codec: Option<String>,
+    max_resolution: Option<Resolution>,
+    max_fps: Option<f64>,
+    max_bandwidth: Option<u64>,
+    prefer_h265: bool,
+    prefer_4k: bool,
+}
 
-        let err_fn = |msg: &str| Error::Request {
-            message: msg.to_string(),
-            status: None,
-            url: url.as_ref().to_string(),
-        };
+impl StreamPreference {
+    /// Codec the video stream must have. Matches against the beginning of
+    /// [`MediaStream::codecs`], so `avc1` matches every h264 stream, `hvc1` / `hev1` every h265
+    /// stream.
+    pub fn video_codec(mut self, codec: impl Into<String>) -> StreamPreference {
+        self.video_codec = Some(codec.into());
+        self
+    }
 
-        let raw_mpd = executor
-            .get(url.as_ref())
-            .query(&[
-                (
-                    "accountid",
-                    executor
-                        .details
-                        .account_id
-                        .clone()
-                        .unwrap_or_default()
-                        .as_str(),
-                ),
-                ("playbackGuid", token.as_ref()),
-            ])
-            .request_raw(true)
-            .await?;
-        // if the response is json and not xml it should always be an error
-        if let Ok(json) = serde_json::from_slice(&raw_mpd) {
-            is_request_error(json, url.as_ref(), &StatusCode::FORBIDDEN)?;
-        }
-        let mut mpd: MPD =
-            dash_mpd::parse(&String::from_utf8_lossy(&raw_mpd)).map_err(|e| Error::Decode {
-                message: e.to_string(),
-                content: raw_mpd,
-                url: url.as_ref().to_string(),
-            })?;
-        let period = mpd.periods.remove(0);
-
-        for adaption in period.adaptations {
-            // skip subtitles that are embedded in the mpd manifest for now
-            if adaption.contentType.is_some_and(|ct| ct == "text") {
-                if !adaption.mimeType.is_some_and(|mime| mime == "text/vtt") {
-                    continue;
-                }
-                subtitle = Some(Subtitle {
-                    executor: executor.clone(),
-                    locale: audio_locale.clone(),
-                    url: adaption
-                        .representations
-                        .first()
-                        .ok_or("no subtitle representation found")
-                        .map_err(err_fn)?
-                        .BaseURL
-                        .first()
-                        .ok_or("no subtitle url found")
-                        .map_err(err_fn)?
-                        .base
-                        .clone(),
-                    format: "vtt".to_string(),
-                });
-                continue;
-            }
+    /// Codec the audio stream must have. Matches against the beginning of
+    /// [`MediaStream::codecs`], e.g. `mp4a`.
+    pub fn audio_codec(mut self, codec: impl Into<String>) -> StreamPreference {
+        self.audio_codec = Some(codec.into());
+        self
+    }
+
+    /// Maximal resolution the video stream may have.
+    pub fn max_resolution(mut self, resolution: Resolution) -> StreamPreference {
+        self.max_resolution = Some(resolution);
+        self
+    }
+
+    /// Maximal fps the video stream may have.
+    pub fn max_fps(mut self, fps: f64) -> StreamPreference {
+        self.max_fps = Some(fps);
+        self
+    }
 
-            let segment_template = adaption
-                .SegmentTemplate
-                .ok_or("no segment template found")
-                .map_err(err_fn)?;
-            let segment_lengths = segment_template
-                .SegmentTimeline
+    /// Maximal bandwidth the video and audio streams may have.
+    pub fn max_bandwidth(mut self, bandwidth: u64) -> StreamPreference {
+        self.max_bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// Prefer H.265 video streams over H.264 ones with the same resolution. Unlike
+    /// [`StreamPreference::video_codec`], H.264 streams are still selected if no H.265 stream is
+    /// available. Most platforms only receive H.265 streams if the stream was requested with
+    /// [`StreamPreference::platform`]. Default is `false`.
+    pub fn prefer_h265(mut self, prefer_h265: bool) -> StreamPreference {
+        self.prefer_h265 = prefer_h265;
+        self
+    }
+
+    /// Prefer 4K video streams. If not set, 4K streams are only selected if no other video stream
+    /// matches the preference. 4K streams are only delivered to some platforms, request the stream
+    /// with [`StreamPreference::platform`] (or [`Stream::from_id_with_preference`]) to get them.
+    /// Requires a premium account. Default is `false`.
+    pub fn prefer_4k(mut self, prefer_4k: bool) -> StreamPreference {
+        self.prefer_4k = prefer_4k;
+        self
+    }
+
+    /// Returns the platform the stream should be requested with to get streams matching this
+    /// preference. This is a tv platform if [`StreamPreference::prefer_h265`] or
+    /// [`StreamPreference::prefer_4k`] is set, else the default platform.
+    pub fn platform(&self) -> StreamPlatform {
+        if self.prefer_h265 || self.prefer_4k {
+            StreamPlatform::TvSamsung
+        } else {
+            StreamPlatform::default()
+        }
+    }
+
+    fn video_sort_key(&self, stream: &MediaStream) -> (bool, u64, u64, bool, u64) {
+        let (height, width, bandwidth) = video_sort_key(stream);
+        let preferred_resolution = self.prefer_4k || height < 2160;
+        let preferred_codec = self.prefer_h265 && stream.codec() == Codec::H265;
+        (
+            preferred_resolution,
+            height,
+            width,
+            preferred_codec,
+            bandwidth,
+        )
+    }
+
+    fn matches_video(&self, stream: &MediaStream) -> bool {
+        let Some(resolution) = stream.resolution() else {
+            return false;
+        };
+        self.video_codec
+            .as_ref()
+            .is_none_or(|codec| stream.codecs.starts_with(codec))
+            && self
+                .max_resolution
                 .as_ref()
-                .ok_or("no segment timeline found")
-                .map_err(err_fn)?
-                .segments
-                .iter()
-                .flat_map(|s| {
-                    iter::repeat(s.d as u32)
-                        .take(s.r.unwrap_or_default() as usize + 1)
-                        .collect::<Vec<u32>>()
-                })
-                .collect::<Vec<u32>>();
-            let segment_init_url = segment_template
-                .initialization
-                .ok_or("no init url found")
-                .map_err(err_fn)?;
-            let segment_media_url = segment_template
-                .media
-                .ok_or("no media url found")
-                .map_err(err_fn)?;
-            let pssh = adaption.ContentProtection.into_iter().find_map(|cp| {
-                cp.cenc_pssh
-                    .first()
-                    .map(|pssh| pssh.clone().content.expect("pssh"))
-            });
+                .is_none_or(|max| resolution.width <= max.width && resolution.height <= max.height)
+            && self
+                .max_fps
+                .is_none_or(|max| stream.fps().is_some_and(|fps| fps <= max))
+            && self.max_bandwidth.is_none_or(|max| stream.bandwidth <= max)
+    }
 
-            if adaption.maxWidth.is_some() || adaption.maxHeight.is_some() {
-                for representation in adaption.representations {
-                    let (Some(width), Some(height)) = (representation.width, representation.height)
-                    else {
-                        return Err(err_fn("invalid resolution"));
-                    };
-                    let resolution = Resolution { width, height };
-
-                    let frame_rate = representation
-                        .frameRate
-                        .ok_or("no fps found")
-                        .map_err(err_fn)?;
-                    let fps: f64 = if let Some((l, r)) = frame_rate.split_once('/') {
-                        let left = l
-                            .parse::<f64>()
-                            .map_err(|_| err_fn(&format!("invalid (left) fps: {l}")))?;
-                        let right = r
-                            .parse::<f64>()
-                            .map_err(|_| err_fn(&format!("invalid (right) fps: {l}")))?;
-                        left / right
-                    } else {
-                        frame_rate
-                            .parse()
-                            .map_err(|_| err_fn(&format!("invalid fps: {frame_rate}")))?
-                    };
-
-                    video.push(MediaStream {
-                        executor: executor.clone(),
-                        bandwidth: representation
-                            .bandwidth
-                            .ok_or("no bandwidth found")
-                            .map_err(err_fn)?,
-                        codecs: representation
-                            .codecs
-                            .ok_or("no codecs found")
-                            .map_err(err_fn)?,
-                        info: MediaStreamInfo::Video { resolution, fps },
-                        drm: pssh.as_ref().map(|pssh| MediaStreamDRM {
-                            pssh: pssh.clone(),
-                            token: token.as_ref().to_string(),
-                        }),
-                        watch_id: watch_id.as_ref().to_string(),
-                        representation_id: representation
-                            .id
-                            .ok_or("no representation id found")
-                            .map_err(err_fn)?,
-                        segment_start: segment_template
-                            .startNumber
-                            .ok_or("no start number found")
-                            .map_err(err_fn)? as u32,
-                        segment_lengths: segment_lengths.clone(),
-                        segment_base_url: representation
-                            .BaseURL
-                            .first()
-                            .ok_or("no base url found")
-                            .map_err(err_fn)?
-                            .base
-                            .clone(),
-                        segment_init_url: segment_init_url.clone(),
-                        segment_media_url: segment_media_url.clone(),
-                    })
-                }
-            } else {
-                for representation in adaption.representations {
-                    let sampling_rate = representation
-                        .audioSamplingRate
-                        .ok_or("no audio sampling rate found")
-                        .map_err(err_fn)?
-                        .parse::<u32>()
-                        .map_err(|e| err_fn(&e.to_string()))?;
-
-                    audio.push(MediaStream {
-                        executor: executor.clone(),
-                        bandwidth: representation
-                            .bandwidth
-                            .ok_or("no bandwith found")
-                            .map_err(err_fn)?,
-                        codecs: representation
-                            .codecs
-                            .ok_or("no codecs found")
-                            .map_err(err_fn)?,
-                        info: MediaStreamInfo::Audio { sampling_rate },
-                        drm: pssh.as_ref().map(|pssh| MediaStreamDRM {
-                            pssh: pssh.clone(),
-                            token: token.as_ref().to_string(),
-                        }),
-                        watch_id: watch_id.as_ref().to_string(),
-                        representation_id: representation
-                            .id
-                            .ok_or("no representation id found")
-                            .map_err(err_fn)?,
-                        segment_start: segment_template
-                            .startNumber
-                            .ok_or("no start number found")
-                            .map_err(err_fn)? as u32,
-                        segment_lengths: segment_lengths.clone(),
-                        segment_base_url: representation
-                            .BaseURL
-                            .first()
-                            .ok_or("no base url found")
-                            .map_err(err_fn)?
-                            .base
-                            .clone(),
-                        segment_init_url: segment_init_url.clone(),
-                        segment_media_url: segment_media_url.clone(),
-                    })
-                }
-            }
+    fn matches_audio(&self, stream: &MediaStream) -> bool {
+        self.audio_codec
+            .as_ref()
+            .is_none_or(|codec| stream.codecs.starts_with(codec))
+            && self.max_bandwidth.is_none_or(|max| stream.bandwidth <= max)
+    }
+}
+
+/// Hysteresis parameters for [`StreamData::select_for_bandwidth_with`].
+#[derive(Clone, Debug)]
+pub struct BandwidthHysteresis {
+    safety_factor: f64,
+    up_switch_factor: f64,
+    current_bandwidth: Option<u64>,
+}
+
+impl Default for BandwidthHysteresis {
+    fn default() -> Self {
+        Self {
+            safety_factor: 0.8,
+            up_switch_factor: 1.2,
+            current_bandwidth: None,
         }
+    }
+}
+
+impl BandwidthHysteresis {
+    /// Fraction of the measured bandwidth which may be used by the selected streams. Default is
+    /// `0.8`.
+    pub fn safety_factor(mut self, safety_factor: f64) -> BandwidthHysteresis {
+        self.safety_factor = safety_factor;
+        self
+    }
+
+    /// How much headroom a higher quality video stream needs before it is selected instead of the
+    /// current one (see [`BandwidthHysteresis::current_bandwidth`]). With the default of `1.2`,
+    /// the usable bandwidth must be 20% above the bandwidth of the higher quality stream. Switching
+    /// to a lower quality stream happens without any headroom.
+    pub fn up_switch_factor(mut self, up_switch_factor: f64) -> BandwidthHysteresis {
+        self.up_switch_factor = up_switch_factor;
+        self
+    }
+
+    /// Bandwidth of the video stream which is currently played. Used to prevent rapid switching
+    /// between qualities if the measured bandwidth fluctuates.
+    pub fn current_bandwidth(mut self, current_bandwidth: u64) -> BandwidthHysteresis {
+        self.current_bandwidth = Some(current_bandwidth);
+        self
+    }
+}
+
+impl StreamData {
+    /// Returns the video stream with the highest resolution. If multiple streams have the same
+    /// resolution, the one with the highest bandwidth is returned.
+    pub fn best_video(&self) -> Option<&MediaStream> {
+        self.video.iter().max_by_key(|v| video_sort_key(v))
+    }
+
+    /// Returns the video stream with the lowest resolution. If multiple streams have the same
+    /// resolution, the one with the lowest bandwidth is returned.
+    pub fn worst_video(&self) -> Option<&MediaStream> {
+        self.video.iter().min_by_key(|v| video_sort_key(v))
+    }
+
+    /// Returns the video stream with the given resolution. If multiple streams have this
+    /// resolution, the one with the highest bandwidth is returned.
+    pub fn video_by_resolution(&self, resolution: Resolution) -> Option<&MediaStream> {
+        self.video
+            .iter()
+            .filter(|v| v.resolution().as_ref() == Some(&resolution))
+            .max_by_key(|v| v.bandwidth)
+    }
+
+    /// Returns the best video stream (see [`StreamData::best_video`]) whose height is not greater
+    /// than `max_height`.
+    pub fn video_by_max_height(&self, max_height: u64) -> Option<&MediaStream> {
+        self.video
+            .iter()
+            .filter(|v| v.resolution().is_some_and(|r| r.height <= max_height))
+            .max_by_key(|v| video_sort_key(v))
+    }
+
+    /// Returns the audio stream with the highest bandwidth whose codec starts with `codec`, e.g.
+    /// `mp4a`.
+    pub fn audio_by_codec(&self, codec: &str) -> Option<&MediaStream> {
+        self.audio
+            .iter()
+            .filter(|a| a.codecs.starts_with(codec))
+            .max_by_key(|a| a.bandwidth)
+    }
+
+    /// Returns the best video and audio stream which match the given [`StreamPreference`].
+    pub fn preferred(
+        &self,
+        preference: &StreamPreference,
+    ) -> (Option<&MediaStream>, Option<&MediaStream>) {
+        let video = self
+            .video
+            .iter()
+            .filter(|v| preference.matches_video(v))
+            .max_by_key(|v| preference.video_sort_key(v));
+        let audio = self
+            .audio
+            .iter()
+            .filter(|a| preference.matches_audio(a))
+            .max_by_key(|a| a.bandwidth);
+        (video, audio)
+    }
+
+    /// Returns the approximate size in bytes of the video and audio stream which are selected by
+    /// [`StreamData::preferred`] (see [`MediaStream::estimated_size`]). [`None`] if no stream
+    /// matches the preferences.
+    pub fn estimated_size(&self, preference: &StreamPreference) -> Option<u64> {
+        match self.preferred(preference) {
+            (None, None) => None,
+            (video, audio) => Some(
+                video.map_or(0, |v| v.estimated_size()) + audio.map_or(0, |a| a.estimated_size()),
+            ),
+        }
+    }
+
+    /// Returns the video and audio stream which fit best into the given bandwidth (in bits per
+    /// second). Uses the default [`BandwidthHysteresis`], see
+    /// [`StreamData::select_for_bandwidth_with`] for details.
+    pub fn select_for_bandwidth(
+        &self,
+        bits_per_sec: u64,
+    ) -> (Option<&MediaStream>, Option<&MediaStream>) {
+        self.select_for_bandwidth_with(bits_per_sec, &BandwidthHysteresis::default())
+    }
+
+    /// Returns the video and audio stream which fit best into the given bandwidth (in bits per
+    /// second). The audio stream with the highest bandwidth which leaves room for the lowest
+    /// quality video stream is selected first, the remaining bandwidth is then used for the video
+    /// stream with the highest bandwidth. If no stream fits into the bandwidth, the stream with the
+    /// lowest bandwidth is returned.
+    pub fn select_for_bandwidth_with(
+        &self,
+        bits_per_sec: u64,
+        hysteresis: &BandwidthHysteresis,
+    ) -> (Option<&MediaStream>, Option<&MediaStream>) {
+        let budget = (bits_per_sec as f64 * hysteresis.safety_factor) as u64;
+        let min_video_bandwidth = self
+            .video
+            .iter()
+            .map(|v| v.bandwidth)
+            .min()
+            .unwrap_or_default();
+
+        let audio = self
+            .audio
+            .iter()
+            .filter(|a| a.bandwidth + min_video_bandwidth <= budget)
+            .max_by_key(|a| a.bandwidth)
+            .or_else(|| self.audio.iter().min_by_key(|a| a.bandwidth));
+
+        let video_budget = budget.saturating_sub(audio.map(|a| a.bandwidth).unwrap_or_default());
+        let video = self
+            .video
+            .iter()
+            .filter(|v| {
+                v.bandwidth <= video_budget
+                    && (hysteresis
+                        .current_bandwidth
+                        .is_none_or(|current| v.bandwidth <= current)
+                        || v.bandwidth as f64 * hysteresis.up_switch_factor <= video_budget as f64)
+            })
+            .max_by_key(|v| (v.bandwidth, video_sort_key(v)))
+            .or_else(|| self.video.iter().min_by_key(|v| v.bandwidth));
+
+        (video, audio)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(url = url.as_ref()))
+    )]
+    async fn from_url(
+        executor: Arc<Executor>,
+        url: impl AsRef<str>,
+        token: impl AsRef<str>,
+        watch_id: impl AsRef<str>,
+        audio_locale: &Locale,
+    ) -> Result<Self> {
+        let mut video = vec![];
+        let mut trickplay = vec![];
+        let mut trailers = vec![];
+        let mut audio = vec![];
+        let mut subtitle = None;
+
+        let raw_mpd = request_manifest(&executor, url.as_ref(), token.as_ref()).await?;
+        let mpd = parse_manifest(raw_mpd, url.as_ref())?;
+
+        let mut period_start = Duration::ZERO;
+        for period in mpd.periods {
+            // periods without explicit start directly follow the previous one
+            let start = period.start.unwrap_or(period_start);
+            let period = parse_period(
+                &executor,
+                period,
+                start,
+                token.as_ref(),
+                watch_id.as_ref(),
+                audio_locale,
+                url.as_ref(),
+            )?;
+            // the subtitle is the same in every period
+            subtitle = subtitle.or(period.subtitle);
+
+            let period_duration = period
+                .video
+                .iter()
+                .chain(&period.audio)
+                .map(|s| s.duration())
+                .max()
+                .unwrap_or_default();
+            append_period(&mut video, period.video);
+            append_period(&mut trickplay, period.trickplay);
+            append_period(&mut trailers, period.trailers);
+            append_period(&mut audio, period.audio);
+            period_start = start + period_duration;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            video = video.len(),
+            trickplay = trickplay.len(),
+            trailers = trailers.len(),
+            audio = audio.len(),
+            subtitle = subtitle.is_some(),
+            "parsed stream data"
+        );
 
         Ok(Self {
             audio,
             video,
+            trickplay,
+            trailers,
             subtitle,
         })
     }
@@ -530,19 +1263,49 @@ pub struct MediaStream {
     pub watch_id: String,
 
     #[serde(skip_serializing)]
+    periods: Vec<MediaStreamPeriod>,
+}
+
+/// Segment information of a [`MediaStream`] in a single period of the manifest.
+#[derive(Clone, Debug)]
+struct MediaStreamPeriod {
+    id: Option<String>,
+    start: Duration,
     representation_id: String,
-    #[serde(skip_serializing)]
     segment_start: u32,
-    #[serde(skip_serializing)]
-    segment_lengths: Vec<u32>,
-    #[serde(skip_serializing)]
+    /// Lengths of the segments in units of `timescale`.
+    segment_lengths: Vec<u64>,
+    /// Units per second of `segment_lengths`.
+    timescale: u64,
     segment_base_url: String,
-    #[serde(skip_serializing)]
     segment_init_url: String,
-    #[serde(skip_serializing)]
     segment_media_url: String,
 }
 
+impl MediaStreamPeriod {
+    fn duration(&self) -> Duration {
+        self.scaled(self.segment_lengths.iter().sum())
+    }
+
+    /// Converts `units` of the period's timescale to a duration.
+    fn scaled(&self, units: u64) -> Duration {
+        Duration::from_nanos((units as u128 * 1_000_000_000 / self.timescale as u128) as u64)
+    }
+}
+
+/// A period of a [`MediaStream`]. Most streams consist of a single period, but manifests with e.g.
+/// stitched-in ads have multiple. Every period starts with its own initialization segment (see
+/// [`MediaStream::segments`]), so the boundaries between them are discontinuities which players
+/// have to handle, e.g. by re-initializing the decoder.
+#[derive(Clone, Debug, Serialize, Request)]
+pub struct StreamPeriod {
+    /// Id of the period in the manifest.
+    pub id: Option<String>,
+    /// Presentation time at which the period starts.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
 #[derive(Clone, Debug, Serialize, Request)]
 pub struct MediaStreamDRM {
     pub pssh: String,
@@ -551,21 +1314,77 @@ pub struct MediaStreamDRM {
 
 #[derive(Clone, Debug, Serialize, Request)]
 pub enum MediaStreamInfo {
-    Audio { sampling_rate: u32 },
-    Video { resolution: Resolution, fps: f64 },
+    Audio {
+        sampling_rate: u32,
+        /// Number of audio channels, e.g. `2` for stereo or `6` for 5.1 surround. [`None`] if the
+        /// manifest doesn't specify it.
+        channels: Option<u32>,
+        /// Role of the audio track as specified in the manifest, e.g. `main` or `description`
+        /// (audio description for visually impaired).
+        role: Option<String>,
+    },
+    Video {
+        resolution: Resolution,
+        fps: f64,
+    },
+}
+
+/// Codec of a [`MediaStream`], parsed from [`MediaStream::codecs`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize)]
+pub enum Codec {
+    H264,
+    H265,
+    Aac,
+    Ac3,
+    Eac3,
+    Opus,
+    Flac,
+    Unknown,
 }
 
 impl MediaStream {
+    /// Returns the codec of the stream. See [`MediaStream::codec_profile`] for a more detailed
+    /// description of it.
+    pub fn codec(&self) -> Codec {
+        codec(&self.codecs)
+    }
+
     /// Returns the streams' audio sampling rate. Only [`Some`] if the stream is an audio stream
     /// (check [`MediaStream::info`]).
     pub fn sampling_rate(&self) -> Option<u32> {
-        if let MediaStreamInfo::Audio { sampling_rate } = &self.info {
+        if let MediaStreamInfo::Audio { sampling_rate, .. } = &self.info {
             Some(*sampling_rate)
         } else {
             None
         }
     }
 
+    /// Returns the number of audio channels. Only [`Some`] if the stream is an audio stream (check
+    /// [`MediaStream::info`]) and the manifest contains the channel configuration.
+    pub fn channels(&self) -> Option<u32> {
+        if let MediaStreamInfo::Audio { channels, .. } = &self.info {
+            *channels
+        } else {
+            None
+        }
+    }
+
+    /// Returns the role of the audio track, e.g. `main` or `description`. Only [`Some`] if the
+    /// stream is an audio stream (check [`MediaStream::info`]) and the manifest contains a role.
+    pub fn audio_role(&self) -> Option<&str> {
+        if let MediaStreamInfo::Audio { role, .. } = &self.info {
+            role.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Returns a human-readable name of the codec profile, e.g. `AAC-LC` for `mp4a.40.2` or
+    /// `E-AC-3` for `ec-3`. [`None`] if the codec is unknown.
+    pub fn codec_profile(&self) -> Option<&'static str> {
+        codec_profile(&self.codecs)
+    }
+
     /// Returns the streams' video resolution. Only [`Some`] if the stream is a video stream (check
     /// [`MediaStream::info`]).
     pub fn resolution(&self) -> Option<Resolution> {
@@ -586,39 +1405,633 @@ impl MediaStream {
         }
     }
 
-    /// Returns all segment this stream is made of.
+    /// Returns the duration of the stream, calculated from the segment timeline of the manifest.
+    /// Unlike [`crate::Episode::duration`] it's the duration of the actual stream data.
+    pub fn duration(&self) -> Duration {
+        self.periods.iter().map(|p| p.duration()).sum()
+    }
+
+    /// Returns the periods this stream is made of. See [`StreamPeriod`] for details.
+    pub fn periods(&self) -> Vec<StreamPeriod> {
+        self.periods
+            .iter()
+            .map(|p| StreamPeriod {
+                id: p.id.clone(),
+                start: p.start,
+                duration: p.duration(),
+            })
+            .collect()
+    }
+
+    /// Returns the approximate size of the stream in bytes, calculated from its bandwidth and
+    /// [`MediaStream::duration`]. The actual size may differ, as the bandwidth which is announced
+    /// by the manifest isn't the exact bitrate of the data.
+    pub fn estimated_size(&self) -> u64 {
+        (self.bandwidth as u128 * self.duration().as_millis() / 8 / 1000) as u64
+    }
+
+    /// Returns all segment this stream is made of. Every period (see [`MediaStream::periods`])
+    /// starts with its initialization segment, followed by the segments of the period.
     pub fn segments(&self) -> Vec<StreamSegment> {
-        let mut segments = vec![StreamSegment {
-            executor: self.executor.clone(),
-            url: format!(
-                "{}{}",
-                self.segment_base_url,
-                self.segment_init_url
-                    .replace("$RepresentationID$", &self.representation_id)
-            ),
-            length: Duration::from_secs(0),
-        }];
+        let mut segments = vec![];
 
-        for i in 0..self.segment_lengths.len() {
+        for period in &self.periods {
             segments.push(StreamSegment {
                 executor: self.executor.clone(),
                 url: format!(
                     "{}{}",
-                    self.segment_base_url,
-                    self.segment_media_url
-                        .replace("$RepresentationID$", &self.representation_id)
-                        .replace("$Number$", &(self.segment_start + i as u32).to_string())
+                    period.segment_base_url,
+                    period
+                        .segment_init_url
+                        .replace("$RepresentationID$", &period.representation_id)
                 ),
-                length: Duration::from_millis(self.segment_lengths[i] as u64),
-            })
+                length: Duration::from_secs(0),
+                representation_id: period.representation_id.clone(),
+                number: None,
+                start: period.start,
+            });
+
+            // the start and length are calculated from the summed up units, so the rounding
+            // errors of the single segments don't add up
+            let mut units = 0;
+            for i in 0..period.segment_lengths.len() {
+                let number = period.segment_start + i as u32;
+                let start = period.start + period.scaled(units);
+                units += period.segment_lengths[i];
+                let length = period.start + period.scaled(units) - start;
+                segments.push(StreamSegment {
+                    executor: self.executor.clone(),
+                    url: format!(
+                        "{}{}",
+                        period.segment_base_url,
+                        period
+                            .segment_media_url
+                            .replace("$RepresentationID$", &period.representation_id)
+                            .replace("$Number$", &number.to_string())
+                    ),
+                    length,
+                    representation_id: period.representation_id.clone(),
+                    number: Some(number),
+                    start,
+                })
+            }
         }
 
         segments
     }
+
+    /// Returns the segments which are required to play the stream from `start` to `end`, e.g. to
+    /// prefetch the part after an intro (see [`crate::media::SkipEvents`]) or to download only a
+    /// part of the stream. Like [`MediaStream::segments`], the first segment is always an
+    /// initialization segment, and every other period in the range is preceded by its
+    /// initialization segment too. The other segments are the minimal set of segments which cover
+    /// the given time range, so the first and last one may start before / end after the range.
+    pub fn segments_between(&self, start: Duration, end: Duration) -> Vec<StreamSegment> {
+        let segments = self.segments();
+        let mut between = vec![];
+
+        let mut init = None;
+        for segment in &segments {
+            if segment.number.is_none() {
+                init = Some(segment);
+                continue;
+            }
+            if segment.start >= end {
+                break;
+            }
+            if segment.start + segment.length > start {
+                between.extend(init.take().cloned());
+                between.push(segment.clone())
+            }
+        }
+        if between.is_empty() {
+            between.extend(segments.into_iter().next())
+        }
+
+        between
+    }
+}
+
+/// Streams and subtitle of a single period of a manifest.
+struct ParsedPeriod {
+    video: Vec<MediaStream>,
+    trickplay: Vec<MediaStream>,
+    trailers: Vec<MediaStream>,
+    audio: Vec<MediaStream>,
+    subtitle: Option<Subtitle>,
+}
+
+/// Parses the streams of a single manifest period which starts at `start`.
+fn parse_period(
+    executor: &Arc<Executor>,
+    period: dash_mpd::Period,
+    start: Duration,
+    token: &str,
+    watch_id: &str,
+    audio_locale: &Locale,
+    url: &str,
+) -> Result<ParsedPeriod> {
+    let mut video = vec![];
+    let mut trickplay = vec![];
+    let mut trailers = vec![];
+    let mut audio = vec![];
+    let mut subtitle = None;
+
+    let err_fn = |msg: &str| Error::Request {
+        message: msg.to_string(),
+        status: None,
+        url: url.to_string(),
+        code: None,
+        request_id: None,
+    };
+
+    let period_id = period.id;
+    for adaption in period.adaptations {
+        // skip subtitles that are embedded in the mpd manifest for now
+        if adaption.contentType.as_ref().is_some_and(|ct| ct == "text") {
+            if adaption
+                .mimeType
+                .as_ref()
+                .is_none_or(|mime| mime != "text/vtt")
+            {
+                continue;
+            }
+            subtitle = Some(Subtitle {
+                executor: executor.clone(),
+                locale: audio_locale.clone(),
+                url: adaption
+                    .representations
+                    .first()
+                    .ok_or("no subtitle representation found")
+                    .map_err(err_fn)?
+                    .BaseURL
+                    .first()
+                    .ok_or("no subtitle url found")
+                    .map_err(err_fn)?
+                    .base
+                    .clone(),
+                format: "vtt".to_string(),
+            });
+            continue;
+        }
+
+        let segment_template = adaption
+            .SegmentTemplate
+            .ok_or("no segment template found")
+            .map_err(err_fn)?;
+        let segment_lengths = segment_template
+            .SegmentTimeline
+            .as_ref()
+            .ok_or("no segment timeline found")
+            .map_err(err_fn)?
+            .segments
+            .iter()
+            .flat_map(|s| iter::repeat_n(s.d, s.r.unwrap_or_default() as usize + 1))
+            .collect::<Vec<u64>>();
+        // the dash specification defaults to 1 if the timescale is missing
+        let timescale = segment_template.timescale.filter(|t| *t > 0).unwrap_or(1);
+        let segment_init_url = segment_template
+            .initialization
+            .ok_or("no init url found")
+            .map_err(err_fn)?;
+        let segment_media_url = segment_template
+            .media
+            .ok_or("no media url found")
+            .map_err(err_fn)?;
+        let pssh = adaption.ContentProtection.into_iter().find_map(|cp| {
+            cp.cenc_pssh
+                .first()
+                .map(|pssh| pssh.clone().content.expect("pssh"))
+        });
+
+        // some manifests (e.g. the ones of music videos and concerts) have no max resolution
+        // and store attributes like codecs or frame rate in the adaption instead of the
+        // representations
+        let is_video = adaption.maxWidth.is_some()
+            || adaption.maxHeight.is_some()
+            || adaption
+                .contentType
+                .as_ref()
+                .is_some_and(|ct| ct == "video")
+            || adaption
+                .mimeType
+                .as_ref()
+                .is_some_and(|mime| mime.starts_with("video/"));
+        let adaption_codecs = adaption.codecs.clone();
+
+        if is_video {
+            // trailers are sometimes embedded into the manifest of the actual content
+            let is_trailer = adaption.Role.iter().any(|role| {
+                role.value
+                    .as_ref()
+                    .is_some_and(|v| v == "supplementary" || v == "trailer")
+            }) || adaption
+                .Label
+                .iter()
+                .any(|label| label.content.to_lowercase().contains("trailer"));
+            let is_trickplay = adaption
+                .essential_property
+                .iter()
+                .any(|ep| ep.schemeIdUri.contains("trickmode"))
+                || adaption
+                    .Role
+                    .iter()
+                    .any(|role| role.value.as_ref().is_some_and(|v| v == "trickplay"));
+            let streams = if is_trailer {
+                &mut trailers
+            } else if is_trickplay {
+                &mut trickplay
+            } else {
+                &mut video
+            };
+
+            for representation in adaption.representations {
+                let (Some(width), Some(height)) = (
+                    representation.width.or(adaption.width),
+                    representation.height.or(adaption.height),
+                ) else {
+                    return Err(err_fn("invalid resolution"));
+                };
+                let resolution = Resolution { width, height };
+
+                let frame_rate = representation
+                    .frameRate
+                    .or(adaption.frameRate.clone())
+                    .ok_or("no fps found")
+                    .map_err(err_fn)?;
+                let fps: f64 = if let Some((l, r)) = frame_rate.split_once('/') {
+                    let left = l
+                        .parse::<f64>()
+                        .map_err(|_| err_fn(&format!("invalid (left) fps: {l}")))?;
+                    let right = r
+                        .parse::<f64>()
+                        .map_err(|_| err_fn(&format!("invalid (right) fps: {l}")))?;
+                    left / right
+                } else {
+                    frame_rate
+                        .parse()
+                        .map_err(|_| err_fn(&format!("invalid fps: {frame_rate}")))?
+                };
+
+                streams.push(MediaStream {
+                    executor: executor.clone(),
+                    bandwidth: representation
+                        .bandwidth
+                        .ok_or("no bandwidth found")
+                        .map_err(err_fn)?,
+                    codecs: representation
+                        .codecs
+                        .or(adaption_codecs.clone())
+                        .ok_or("no codecs found")
+                        .map_err(err_fn)?,
+                    info: MediaStreamInfo::Video { resolution, fps },
+                    drm: pssh.as_ref().map(|pssh| MediaStreamDRM {
+                        pssh: pssh.clone(),
+                        token: token.to_string(),
+                    }),
+                    watch_id: watch_id.to_string(),
+                    periods: vec![MediaStreamPeriod {
+                        id: period_id.clone(),
+                        start,
+                        representation_id: representation
+                            .id
+                            .ok_or("no representation id found")
+                            .map_err(err_fn)?,
+                        segment_start: segment_template
+                            .startNumber
+                            .ok_or("no start number found")
+                            .map_err(err_fn)? as u32,
+                        segment_lengths: segment_lengths.clone(),
+                        timescale,
+                        segment_base_url: representation
+                            .BaseURL
+                            .first()
+                            .ok_or("no base url found")
+                            .map_err(err_fn)?
+                            .base
+                            .clone(),
+                        segment_init_url: segment_init_url.clone(),
+                        segment_media_url: segment_media_url.clone(),
+                    }],
+                })
+            }
+        } else {
+            let adaption_channels = audio_channels(&adaption.AudioChannelConfiguration);
+            let adaption_role = adaption.Role.iter().find_map(|role| role.value.clone());
+            for representation in adaption.representations {
+                let channels =
+                    audio_channels(&representation.AudioChannelConfiguration).or(adaption_channels);
+                let sampling_rate = representation
+                    .audioSamplingRate
+                    .or(adaption.audioSamplingRate.clone())
+                    .ok_or("no audio sampling rate found")
+                    .map_err(err_fn)?
+                    .parse::<u32>()
+                    .map_err(|e| err_fn(&e.to_string()))?;
+
+                audio.push(MediaStream {
+                    executor: executor.clone(),
+                    bandwidth: representation
+                        .bandwidth
+                        .ok_or("no bandwith found")
+                        .map_err(err_fn)?,
+                    codecs: representation
+                        .codecs
+                        .or(adaption_codecs.clone())
+                        .ok_or("no codecs found")
+                        .map_err(err_fn)?,
+                    info: MediaStreamInfo::Audio {
+                        sampling_rate,
+                        channels,
+                        role: adaption_role.clone(),
+                    },
+                    drm: pssh.as_ref().map(|pssh| MediaStreamDRM {
+                        pssh: pssh.clone(),
+                        token: token.to_string(),
+                    }),
+                    watch_id: watch_id.to_string(),
+                    periods: vec![MediaStreamPeriod {
+                        id: period_id.clone(),
+                        start,
+                        representation_id: representation
+                            .id
+                            .ok_or("no representation id found")
+                            .map_err(err_fn)?,
+                        segment_start: segment_template
+                            .startNumber
+                            .ok_or("no start number found")
+                            .map_err(err_fn)? as u32,
+                        segment_lengths: segment_lengths.clone(),
+                        timescale,
+                        segment_base_url: representation
+                            .BaseURL
+                            .first()
+                            .ok_or("no base url found")
+                            .map_err(err_fn)?
+                            .base
+                            .clone(),
+                        segment_init_url: segment_init_url.clone(),
+                        segment_media_url: segment_media_url.clone(),
+                    }],
+                })
+            }
+        }
+    }
+
+    Ok(ParsedPeriod {
+        video,
+        trickplay,
+        trailers,
+        audio,
+        subtitle,
+    })
+}
+
+/// Appends the periods of `period_streams`, which were parsed from a period of the manifest, to the
+/// matching streams in `streams`, which contain the previous periods. Streams are matched by their
+/// representation id or, as e.g. ad periods mostly have other ids, by the closest bandwidth. Period
+/// streams which aren't the match of any stream are added as separate streams, which start at their
+/// period, so no stream of the manifest is lost. If the period has no streams at all, the streams
+/// end before it, which is visible via [`MediaStream::periods`]. Every period keeps its own
+/// timescale, so periods with different timescales are converted correctly.
+fn append_period(streams: &mut Vec<MediaStream>, period_streams: Vec<MediaStream>) {
+    if period_streams.is_empty() {
+        #[cfg(feature = "tracing")]
+        if !streams.is_empty() {
+            tracing::warn!("manifest period contains none of the streams of the previous periods")
+        }
+        return;
+    }
+
+    let mut used = vec![false; period_streams.len()];
+    for stream in streams.iter_mut() {
+        let representation_id = &stream.periods[0].representation_id;
+        let Some(pos) = period_streams
+            .iter()
+            .position(|s| &s.periods[0].representation_id == representation_id)
+            .or_else(|| {
+                (0..period_streams.len())
+                    .min_by_key(|pos| period_streams[*pos].bandwidth.abs_diff(stream.bandwidth))
+            })
+        else {
+            continue;
+        };
+        used[pos] = true;
+        stream
+            .periods
+            .extend(period_streams[pos].periods.iter().cloned())
+    }
+
+    streams.extend(
+        period_streams
+            .into_iter()
+            .zip(used)
+            .filter_map(|(stream, used)| (!used).then_some(stream)),
+    )
+}
+
+async fn request_manifest(executor: &Arc<Executor>, url: &str, token: &str) -> Result<Vec<u8>> {
+    let raw_mpd = executor
+        .get(url)
+        .query(&[
+            (
+                "accountid",
+                executor
+                    .details
+                    .account_id
+                    .clone()
+                    .unwrap_or_default()
+                    .as_str(),
+            ),
+            ("playbackGuid", token),
+        ])
+        .request_raw(true)
+        .await?;
+    // if the response is json and not xml it should always be an error
+    if let Ok(json) = serde_json::from_slice(&raw_mpd) {
+        is_request_error(json, url, &StatusCode::FORBIDDEN)?;
+    }
+    Ok(raw_mpd)
+}
+
+/// Parses the codec from the codecs string of a manifest representation.
+fn codec(codecs: &str) -> Codec {
+    let codecs = codecs.to_lowercase();
+    match codecs.split('.').next().unwrap_or_default() {
+        "avc1" | "avc3" => Codec::H264,
+        "hvc1" | "hev1" => Codec::H265,
+        "mp4a" => Codec::Aac,
+        "ac-3" => Codec::Ac3,
+        "ec-3" => Codec::Eac3,
+        "opus" => Codec::Opus,
+        "flac" => Codec::Flac,
+        _ => Codec::Unknown,
+    }
+}
+
+/// Returns the human-readable codec profile of the codecs string of a manifest representation.
+fn codec_profile(codecs: &str) -> Option<&'static str> {
+    let codecs = codecs.to_lowercase();
+    let profile = match codec(&codecs) {
+        Codec::H264 => match codecs.get(5..7) {
+            Some("42") => "H.264 Baseline",
+            Some("4d") => "H.264 Main",
+            Some("64") => "H.264 High",
+            _ => return None,
+        },
+        Codec::H265 => match codecs.get(5..7) {
+            Some("1.") => "H.265 Main",
+            Some("2.") => "H.265 Main 10",
+            _ => return None,
+        },
+        Codec::Aac => match codecs.as_str() {
+            "mp4a.40.2" => "AAC-LC",
+            "mp4a.40.5" => "HE-AAC",
+            "mp4a.40.29" => "HE-AACv2",
+            _ => return None,
+        },
+        Codec::Ac3 => "AC-3",
+        Codec::Eac3 => "E-AC-3",
+        Codec::Opus => "Opus",
+        Codec::Flac => "FLAC",
+        Codec::Unknown => return None,
+    };
+    Some(profile)
+}
+
+/// Reads the number of channels from the audio channel configuration of a manifest. The MPEG scheme
+/// specifies the number directly, the Dolby scheme as bitmask of speaker positions (some of them
+/// are speaker pairs).
+fn audio_channels(configurations: &[dash_mpd::AudioChannelConfiguration]) -> Option<u32> {
+    configurations.iter().find_map(|configuration| {
+        let value = configuration.value.as_ref()?;
+        let is_dolby = configuration
+            .schemeIdUri
+            .as_ref()
+            .is_some_and(|scheme| scheme.contains("dolby"));
+        if !is_dolby {
+            return value.parse().ok();
+        }
+
+        let mask = u16::from_str_radix(value, 16).ok()?;
+        const PAIRS: [u32; 6] = [5, 6, 9, 10, 11, 13];
+        Some(
+            (0..16)
+                .filter(|bit| mask & (0x8000 >> bit) != 0)
+                .map(|bit| if PAIRS.contains(&bit) { 2 } else { 1 })
+                .sum(),
+        )
+    })
+}
+
+fn parse_manifest(raw_mpd: Vec<u8>, url: &str) -> Result<MPD> {
+    dash_mpd::parse(&String::from_utf8_lossy(&raw_mpd)).map_err(|e| Error::Decode {
+        message: e.to_string(),
+        content: raw_mpd,
+        url: url.to_string(),
+        request_id: None,
+    })
+}
+
+fn video_sort_key(stream: &MediaStream) -> (u64, u64, u64) {
+    let resolution = stream.resolution().unwrap_or(Resolution {
+        width: 0,
+        height: 0,
+    });
+    (resolution.height, resolution.width, stream.bandwidth)
+}
+
+/// Reference to a trick-play image archive in the
+/// [BIF](https://developer.roku.com/docs/developer-program/media-playback/trick-mode/bif-file-creation.md)
+/// format. Get it via [`Stream::bifs`].
+#[derive(Clone, Debug, Serialize, Request)]
+pub struct BifReference {
+    #[serde(skip)]
+    executor: Arc<Executor>,
+
+    /// Url to the archive.
+    pub url: String,
+    /// Resolution of the images in the archive. Only available if it's encoded in the url.
+    pub resolution: Option<Resolution>,
+}
+
+impl BifReference {
+    /// Get the raw archive data.
+    pub async fn data(&self) -> Result<Vec<u8>> {
+        self.executor.get(&self.url).request_raw(false).await
+    }
+
+    /// Get all preview images of the archive.
+    pub async fn images(&self) -> Result<Vec<BifImage>> {
+        let data = self.data().await?;
+        parse_bif(&data).ok_or_else(|| Error::Decode {
+            message: "invalid bif archive".to_string(),
+            content: vec![],
+            url: self.url.clone(),
+            request_id: None,
+        })
+    }
+}
+
+/// A single preview image of a [`BifReference`] archive.
+#[derive(Clone, Debug)]
+pub struct BifImage {
+    /// Position in the video where this image is shown.
+    pub timestamp: Duration,
+    /// The image itself, usually a jpeg.
+    pub data: Vec<u8>,
+}
+
+fn parse_bif(data: &[u8]) -> Option<Vec<BifImage>> {
+    const MAGIC: [u8; 8] = [0x89, 0x42, 0x49, 0x46, 0x0d, 0x0a, 0x1a, 0x0a];
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+
+    if data.get(0..8)? != MAGIC {
+        return None;
+    }
+    let count = read_u32(12)? as usize;
+    // a multiplier for the timestamps in milliseconds. 0 means the default of 1000
+    let separation = match read_u32(16)? {
+        0 => 1000,
+        separation => separation as u64,
+    };
+
+    // the count is read from the file, every index entry has 8 bytes so the data limits how many
+    // images there can actually be
+    let mut images = Vec::with_capacity(count.min(data.len() / 8));
+    for i in 0..count {
+        let entry = 64 + i * 8;
+        let timestamp = read_u32(entry)? as u64;
+        let start = read_u32(entry + 4)? as usize;
+        let end = read_u32(entry + 12)? as usize;
+        images.push(BifImage {
+            timestamp: Duration::from_millis(timestamp * separation),
+            data: data.get(start..end)?.to_vec(),
+        })
+    }
+    Some(images)
+}
+
+/// Waits before the next attempt of a failed segment request. The delay starts at 500
+/// milliseconds and doubles with every attempt, up to 8 seconds.
+async fn retry_delay(attempt: u32) {
+    let delay = Duration::from_millis(500).saturating_mul(2u32.saturating_pow(attempt - 1));
+    tokio::time::sleep(delay.min(Duration::from_secs(8))).await
+}
+
+/// Returns the first byte of a `Content-Range` header value like `bytes 100-199/200`.
+fn content_range_start(content_range: &str) -> Option<u64> {
+    let range = content_range.strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
 }
 
 /// Video resolution.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Resolution {
     pub width: u64,
     pub height: u64,
@@ -639,11 +2052,359 @@ pub struct StreamSegment {
     pub url: String,
     /// Video length of this segment.
     pub length: Duration,
+
+    #[serde(skip_serializing)]
+    representation_id: String,
+    #[serde(skip_serializing)]
+    number: Option<u32>,
+    #[serde(skip_serializing)]
+    start: Duration,
 }
 
 impl StreamSegment {
-    /// Get the raw data for the current segment.
+    /// Returns a stable identifier of this segment. Unlike [`StreamSegment::url`], which contains
+    /// expiring tokens, it stays the same across stream requests, so it can be used to cache and
+    /// deduplicate segments on disk or to resume downloads.
+    pub fn identity(&self) -> SegmentIdentity {
+        SegmentIdentity {
+            representation_id: self.representation_id.clone(),
+            number: self.number,
+            start: self.start,
+            length: self.length,
+        }
+    }
+
+    /// Get the raw data for the current segment. If a
+    /// [`crate::crunchyroll::CrunchyrollBuilder::throughput_observer`] is set, it's notified about
+    /// every received chunk.
     pub async fn data(&self) -> Result<Vec<u8>> {
-        self.executor.get(&self.url).request_raw(false).await
+        let Some(observer) = &self.executor.hooks.throughput_observer else {
+            return self.executor.get(&self.url).request_raw(false).await;
+        };
+
+        let mut last_chunk = std::time::Instant::now();
+        let mut resp = self.executor.get(&self.url).request_response(false).await?;
+        let mut data = Vec::with_capacity(resp.content_length().unwrap_or_default() as usize);
+        while let Some(chunk) = resp.chunk().await? {
+            observer.observe(chunk.len(), last_chunk.elapsed());
+            data.extend_from_slice(&chunk);
+            last_chunk = std::time::Instant::now();
+        }
+        Ok(data)
+    }
+
+    /// Get the raw data for the current segment, starting at byte `offset`. Use it to continue a
+    /// download which broke off after `offset` bytes were received, instead of requesting the whole
+    /// segment again. Returns an empty vec if `offset` is at or after the end of the data.
+    pub async fn data_from(&self, offset: u64) -> Result<Vec<u8>> {
+        let Some((resp, skip)) = self.range_response(offset).await? else {
+            return Ok(vec![]);
+        };
+        let data = resp.bytes().await?;
+        Ok(data.get(skip..).unwrap_or_default().to_vec())
+    }
+
+    /// Write the data of the current segment to `writer`, starting at byte `offset` (`0` to write
+    /// the whole segment, or the number of bytes which were already written by a previous
+    /// attempt). If the connection breaks (or can't be established), the download is resumed at
+    /// the last written byte, up to `max_retries` times. The delay between the attempts starts at
+    /// 500 milliseconds and doubles with every attempt, up to 8 seconds. Error responses aren't
+    /// retried. Returns the
+    /// number of bytes the segment has in total (including the first `offset` bytes).
+    pub async fn write_resumable<W: Write>(
+        &self,
+        writer: &mut W,
+        offset: u64,
+        max_retries: u32,
+    ) -> Result<u64> {
+        let observer = &self.executor.hooks.throughput_observer;
+        let mut written = offset;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut last_chunk = std::time::Instant::now();
+            let (mut resp, mut skip) = match self.range_response(written).await {
+                Ok(Some(response)) => response,
+                Ok(None) => return Ok(written),
+                // a connection error has no status and is retried like a broken off transfer
+                Err(Error::Request { status: None, .. }) if attempts <= max_retries => {
+                    retry_delay(attempts).await;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let error = loop {
+                let chunk = match resp.chunk().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => return Ok(written),
+                    Err(e) => break e,
+                };
+                if let Some(observer) = observer {
+                    observer.observe(chunk.len(), last_chunk.elapsed())
+                }
+                last_chunk = std::time::Instant::now();
+
+                let skipped = skip.min(chunk.len());
+                skip -= skipped;
+                let chunk = &chunk[skipped..];
+                writer.write_all(chunk).map_err(|e| Error::Internal {
+                    message: format!("failed to write data of {}: {e}", self.url),
+                })?;
+                written += chunk.len() as u64;
+            };
+            if attempts > max_retries {
+                return Err(error.into());
+            }
+            retry_delay(attempts).await
+        }
+    }
+
+    /// Requests the data of this segment, starting at byte `offset`. Returns the response and how
+    /// many bytes of its body must be skipped because the server ignored the range, or [`None`] if
+    /// `offset` is out of range.
+    async fn range_response(&self, offset: u64) -> Result<Option<(reqwest::Response, usize)>> {
+        let resp = self
+            .executor
+            .get(&self.url)
+            .header(reqwest::header::RANGE, format!("bytes={offset}-"))
+            .request_response(false)
+            .await?;
+        match resp.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let start = resp
+                    .headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(content_range_start);
+                // a range which starts after the requested offset would leave a gap in the data
+                match start {
+                    Some(start) if start <= offset => Ok(Some((resp, (offset - start) as usize))),
+                    _ => Err(Error::Request {
+                        message: format!(
+                            "Server responded with an unexpected range when requesting segment data from byte {offset}"
+                        ),
+                        status: Some(StatusCode::PARTIAL_CONTENT),
+                        url: self.url.clone(),
+                        code: None,
+                        request_id: request_id(resp.headers()),
+                    }),
+                }
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok(None),
+            status if status.is_success() => Ok(Some((resp, offset as usize))),
+            status => Err(Error::Request {
+                message: format!("Failed to request segment data from byte {offset}"),
+                status: Some(status),
+                url: self.url.clone(),
+                code: None,
+                request_id: request_id(resp.headers()),
+            }),
+        }
+    }
+
+    /// Like [`StreamSegment::data`] but the request is aborted and [`Error::Cancelled`] returned
+    /// if `cancellation` gets cancelled. Dropping the future of [`StreamSegment::data`] aborts the
+    /// request too, use this if the cancellation is triggered from somewhere else.
+    pub async fn data_cancellable(&self, cancellation: &Cancellation) -> Result<Vec<u8>> {
+        cancellation.run(self.data(), &self.url).await
+    }
+
+    /// Get the raw data for the current segment and validate it. The received data is compared
+    /// against the `Content-Length` header and the segment is requested again (up to
+    /// `max_retries` times) if they don't match or the body couldn't be read completely. Returns
+    /// [`Error::CorruptSegment`] if the segment is still damaged after all retries. Responses with
+    /// a server error status are retried too, other error statuses return [`Error::Request`]
+    /// immediately. Like in [`StreamSegment::write_resumable`], the delay between the attempts
+    /// starts at 500 milliseconds and doubles with every attempt, up to 8 seconds.
+    pub async fn data_validated(&self, max_retries: u32) -> Result<SegmentData> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let resp = self.executor.get(&self.url).request_response(false).await?;
+
+            let status = resp.status();
+            if !status.is_success() {
+                // server errors are usually temporary, client errors (e.g. an expired segment url)
+                // won't go away by retrying
+                if status.is_server_error() && attempts <= max_retries {
+                    retry_delay(attempts).await;
+                    continue;
+                }
+                return Err(Error::Request {
+                    message: "Failed to request segment data".to_string(),
+                    status: Some(status),
+                    url: self.url.clone(),
+                    code: None,
+                    request_id: request_id(resp.headers()),
+                });
+            }
+
+            let header_value = |name: &str| {
+                resp.headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+            };
+            let etag = header_value("etag");
+            let digest = header_value("digest").or_else(|| header_value("content-md5"));
+            let expected_length = resp.content_length();
+
+            let (data, message) = match resp.bytes().await {
+                Ok(bytes) => {
+                    let data = bytes.to_vec();
+                    if expected_length.is_none_or(|l| l == data.len() as u64) {
+                        return Ok(SegmentData { data, etag, digest });
+                    }
+                    (
+                        data,
+                        "Segment length doesn't match the announced length".to_string(),
+                    )
+                }
+                Err(e) => (vec![], format!("Failed to read segment data: {e}")),
+            };
+
+            if attempts > max_retries {
+                return Err(Error::CorruptSegment {
+                    message,
+                    expected_length,
+                    received_length: data.len() as u64,
+                    attempts,
+                    url: self.url.clone(),
+                });
+            }
+            retry_delay(attempts).await
+        }
+    }
+}
+
+/// Stable identifier of a [`StreamSegment`], get it via [`StreamSegment::identity`]. Its
+/// [`Display`](std::fmt::Display) implementation can be used as file name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct SegmentIdentity {
+    /// Id of the [`MediaStream`] representation the segment belongs to.
+    pub representation_id: String,
+    /// Number of the segment in the representation. [`None`] if it's the initialization segment.
+    pub number: Option<u32>,
+    /// Position in the video where the segment starts.
+    pub start: Duration,
+    /// Video length of the segment.
+    pub length: Duration,
+}
+
+impl std::fmt::Display for SegmentIdentity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let representation_id: String = self
+            .representation_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        match self.number {
+            Some(number) => write!(f, "{representation_id}-{number}-{}", self.start.as_millis()),
+            // periods after the first one have their own initialization segment
+            None if self.start.is_zero() => write!(f, "{representation_id}-init"),
+            None => write!(f, "{representation_id}-init-{}", self.start.as_millis()),
+        }
+    }
+}
+
+/// Data of a [`StreamSegment`] which got validated via [`StreamSegment::data_validated`].
+#[derive(Clone, Debug)]
+pub struct SegmentData {
+    /// The raw segment data.
+    pub data: Vec<u8>,
+    /// The `ETag` header of the segment response, if the CDN provided one.
+    pub etag: Option<String>,
+    /// The `Digest` or `Content-MD5` header of the segment response, if the CDN provided one.
+    pub digest: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_profiles() {
+        assert_eq!(codec_profile("avc1.640028"), Some("H.264 High"));
+        assert_eq!(codec_profile("avc1.4D401F"), Some("H.264 Main"));
+        assert_eq!(codec_profile("avc1.42c01e"), Some("H.264 Baseline"));
+        assert_eq!(codec_profile("hvc1.1.6.L120.90"), Some("H.265 Main"));
+        assert_eq!(codec_profile("hev1.2.4.L153.90"), Some("H.265 Main 10"));
+        assert_eq!(codec_profile("mp4a.40.2"), Some("AAC-LC"));
+        assert_eq!(codec_profile("mp4a.40.29"), Some("HE-AACv2"));
+        assert_eq!(codec_profile("ec-3"), Some("E-AC-3"));
+        assert_eq!(codec_profile("avc1"), None);
+        assert_eq!(codec_profile("vp09.00.10.08"), None)
+    }
+
+    #[test]
+    fn channels() {
+        let configuration = |scheme: &str, value: &str| dash_mpd::AudioChannelConfiguration {
+            schemeIdUri: Some(scheme.to_string()),
+            value: Some(value.to_string()),
+            ..Default::default()
+        };
+        let mpeg = "urn:mpeg:dash:23003:3:audio_channel_configuration:2011";
+        let dolby = "tag:dolby.com,2014:dash:audio_channel_configuration:2011";
+
+        assert_eq!(audio_channels(&[configuration(mpeg, "2")]), Some(2));
+        // L, C, R, Ls, Rs, LFE
+        assert_eq!(audio_channels(&[configuration(dolby, "F801")]), Some(6));
+        // L, R
+        assert_eq!(audio_channels(&[configuration(dolby, "A000")]), Some(2));
+        // L, C, R, Ls, Rs, LFE and the Lrs / Rrs pair
+        assert_eq!(audio_channels(&[configuration(dolby, "F805")]), Some(8));
+        assert_eq!(audio_channels(&[configuration(dolby, "invalid")]), None);
+        assert_eq!(audio_channels(&[]), None)
+    }
+
+    fn bif(count: u32, entries: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0x89, 0x42, 0x49, 0x46, 0x0d, 0x0a, 0x1a, 0x0a];
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.resize(64, 0);
+
+        // the index has an extra entry which marks the end of the last image
+        let mut offset = 64 + (entries.len() as u32 + 1) * 8;
+        for (timestamp, image) in entries {
+            data.extend_from_slice(&timestamp.to_le_bytes());
+            data.extend_from_slice(&offset.to_le_bytes());
+            offset += image.len() as u32
+        }
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&offset.to_le_bytes());
+        for (_, image) in entries {
+            data.extend_from_slice(image)
+        }
+        data
+    }
+
+    #[test]
+    fn bif_images() {
+        let images = parse_bif(&bif(2, &[(0, b"first"), (5, b"second")])).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1].timestamp, Duration::from_secs(5));
+        assert_eq!(images[1].data, b"second")
+    }
+
+    #[test]
+    fn bif_invalid_count() {
+        // the count claims far more images than the data contains
+        assert!(parse_bif(&bif(u32::MAX, &[(0, b"first")])).is_none())
+    }
+
+    #[test]
+    fn content_range() {
+        assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+        assert_eq!(content_range_start("bytes 0-99/*"), Some(0));
+        assert_eq!(content_range_start("bytes */200"), None);
+        assert_eq!(content_range_start("items 0-1/2"), None)
     }
 }
@@ -1,6 +1,6 @@
 use crate::common::{Request, V2BulkResult};
 use crate::crunchyroll::Executor;
-use crate::Result;
+use crate::{Locale, Result};
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
@@ -8,11 +8,39 @@ pub(crate) async fn request_media<T: Default + DeserializeOwned + Request>(
     executor: Arc<Executor>,
     endpoint: String,
 ) -> Result<Vec<T>> {
-    let result: V2BulkResult<T> = executor
-        .get(endpoint)
-        .apply_locale_query()
-        .apply_preferred_audio_locale_query()
-        .request()
-        .await?;
+    request_media_with_locales(executor, endpoint, None, None, &[]).await
+}
+
+/// Like [`request_media`] but the locale and preferred audio locale of the executor can be
+/// overwritten and only the given fields can be requested.
+pub(crate) async fn request_media_with_locales<T: Default + DeserializeOwned + Request>(
+    executor: Arc<Executor>,
+    endpoint: String,
+    locale: Option<Locale>,
+    preferred_audio_locale: Option<Locale>,
+    fields: &[String],
+) -> Result<Vec<T>> {
+    let mut builder = executor.get(endpoint).fields(fields);
+    builder = if let Some(locale) = locale {
+        builder.query(&[("locale", locale)])
+    } else {
+        builder.apply_locale_query()
+    };
+    builder = if let Some(preferred_audio_locale) = preferred_audio_locale {
+        builder.query(&[("preferred_audio_language", preferred_audio_locale)])
+    } else {
+        builder.apply_preferred_audio_locale_query()
+    };
+    let result: V2BulkResult<T> = builder.request().await?;
     Ok(result.data)
 }
+
+/// Builds the url of a media on crunchyroll.com. `path` is the media type specific path segment,
+/// like `series` or `watch`.
+pub(crate) fn web_url(path: &str, id: &str, slug_title: &str) -> String {
+    if slug_title.is_empty() {
+        format!("https://www.crunchyroll.com/{path}/{id}")
+    } else {
+        format!("https://www.crunchyroll.com/{path}/{id}/{slug_title}")
+    }
+}
@@ -1,5 +1,8 @@
 //! Url parsing.
 
+use crate::error::Error;
+use crate::media::Media;
+use crate::{Concert, Crunchyroll, MediaCollection, MovieListing, MusicVideo, Result, Series};
 use regex::Regex;
 
 /// Types of Crunchyroll urls, pointing to media.
@@ -26,15 +29,19 @@ pub enum UrlType {
     /// The parsed url points to a music video. Use [`crate::Concert::from_id`] with the value of
     /// this field to get a usable struct out of it.
     Concert(String),
+    /// The parsed url points to an artist. Use [`crate::media::Artist::from_id`] with the value of
+    /// this field to get a usable struct out of it.
+    Artist(String),
 }
 
 /// Extract information out of Crunchyroll urls which are pointing to media.
 #[cfg_attr(docsrs, doc(cfg(feature = "parse")))]
 pub fn parse_url<S: AsRef<str>>(url: S) -> Option<UrlType> {
     lazy_static::lazy_static! {
-        static ref SERIES_REGEX: Regex = Regex::new(r"^https?://(www\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?(?P<type>series|movie_listing)/(?P<id>[^/]+).*$").unwrap();
-        static ref MUSIC_REGEX: Regex = Regex::new(r"^https?://(www\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?watch/(?P<music_type>musicvideo|concert)/(?P<id>[^/]+).*$").unwrap();
-        static ref EPISODE_REGEX: Regex = Regex::new(r"^https?://(www\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?watch/(?P<id>[^/]+).*$").unwrap();
+        static ref SERIES_REGEX: Regex = Regex::new(r"^(https?://)?((www|beta)\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?(?P<type>series|movie_listing)/(?P<id>[^/?#]+).*$").unwrap();
+        static ref MUSIC_REGEX: Regex = Regex::new(r"^(https?://)?((www|beta)\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?watch/(?P<music_type>musicvideo|concert)/(?P<id>[^/?#]+).*$").unwrap();
+        static ref ARTIST_REGEX: Regex = Regex::new(r"^(https?://)?((www|beta)\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?artist/(?P<id>[^/?#]+).*$").unwrap();
+        static ref EPISODE_REGEX: Regex = Regex::new(r"^(https?://)?((www|beta)\.)?crunchyroll\.com/([a-zA-Z]{2}(-[a-zA-Z]{2})?/)?watch/(?P<id>[^/?#]+).*$").unwrap();
     }
 
     #[allow(clippy::manual_map)]
@@ -55,6 +62,10 @@ pub fn parse_url<S: AsRef<str>>(url: S) -> Option<UrlType> {
             )),
             _ => unreachable!(),
         }
+    } else if let Some(capture) = ARTIST_REGEX.captures(url.as_ref()) {
+        Some(UrlType::Artist(
+            capture.name("id").unwrap().as_str().to_string(),
+        ))
     } else if let Some(capture) = EPISODE_REGEX.captures(url.as_ref()) {
         Some(UrlType::EpisodeOrMovie(
             capture.name("id").unwrap().as_str().to_string(),
@@ -63,3 +74,55 @@ pub fn parse_url<S: AsRef<str>>(url: S) -> Option<UrlType> {
         None
     }
 }
+
+impl Crunchyroll {
+    /// Resolves a Crunchyroll url to the media it points to. Other than [`parse_url`], this also
+    /// supports short links (like `crunchyroll.onelink.me`) by following their redirects. Artist
+    /// urls are not supported as artists are no [`MediaCollection`], use [`parse_url`] and
+    /// [`crate::media::Artist::from_id`] for them.
+    #[cfg_attr(docsrs, doc(cfg(feature = "parse")))]
+    pub async fn resolve_url<S: AsRef<str>>(&self, url: S) -> Result<MediaCollection> {
+        let url_type = if let Some(url_type) = parse_url(&url) {
+            url_type
+        } else {
+            let resolved_url = self.follow_short_link(url.as_ref()).await?;
+            parse_url(&resolved_url).ok_or_else(|| Error::Input {
+                message: format!(
+                    "'{}' (resolved to '{resolved_url}') is not a valid Crunchyroll url",
+                    url.as_ref()
+                ),
+            })?
+        };
+
+        match url_type {
+            UrlType::Series(id) => Ok(Series::from_id(self, id).await?.into()),
+            UrlType::MovieListing(id) => Ok(MovieListing::from_id(self, id).await?.into()),
+            UrlType::EpisodeOrMovie(id) => MediaCollection::from_id(self, id).await,
+            UrlType::MusicVideo(id) => Ok(MusicVideo::from_id(self, id).await?.into()),
+            UrlType::Concert(id) => Ok(Concert::from_id(self, id).await?.into()),
+            UrlType::Artist(id) => Err(Error::Input {
+                message: format!("'{}' points to an artist ({id})", url.as_ref()),
+            }),
+        }
+    }
+
+    /// Returns the url a short link points to.
+    async fn follow_short_link(&self, url: &str) -> Result<String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| Error::Input {
+            message: format!("'{url}' is not a valid url: {e}"),
+        })?;
+        // short links may contain the actual url as query parameter, which saves a request
+        for (key, value) in parsed.query_pairs() {
+            if matches!(key.as_ref(), "af_web_dp" | "af_dp" | "deep_link_value")
+                && parse_url(value.as_ref()).is_some()
+            {
+                return Ok(value.to_string());
+            }
+        }
+
+        // not authenticated as the short link host isn't part of the api, but it still goes
+        // through the hooks and middleware of the session
+        let resp = self.executor.get(parsed).request_response(false).await?;
+        Ok(resp.url().to_string())
+    }
+}
@@ -0,0 +1,101 @@
+//! Manage multiple accounts.
+
+use crate::crunchyroll::{CrunchyrollBuilder, RateLimiter};
+use crate::Crunchyroll;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::hash::Hash;
+use tokio::sync::RwLock;
+
+/// A collection of [`Crunchyroll`] sessions, identified by a key of your choice (e.g. an user id).
+/// All sessions which are created via [`CrunchyrollPool::builder`] share the same client (and
+/// therefore the same connections) and, if set, the same [`RateLimiter`]. Every session still
+/// refreshes its own access token.
+///
+/// Because the client is shared, its cookies are shared as well. This is fine for the cookies which
+/// Crunchyroll sets to verify a client but means that the cookie based
+/// [`CrunchyrollBuilder::login_with_etp_rt`] should not be used concurrently.
+#[derive(Debug)]
+pub struct CrunchyrollPool<K: Eq + Hash + Clone> {
+    client: Client,
+    rate_limiter: Option<RateLimiter>,
+
+    sessions: RwLock<HashMap<K, Crunchyroll>>,
+}
+
+impl<K: Eq + Hash + Clone> Default for CrunchyrollPool<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone> CrunchyrollPool<K> {
+    /// Create a new pool with a client built from
+    /// [`CrunchyrollBuilder::predefined_client_builder`].
+    pub fn new() -> Self {
+        Self::with_client(
+            CrunchyrollBuilder::predefined_client_builder()
+                .build()
+                .unwrap(),
+        )
+    }
+
+    /// Create a new pool which uses the given client for all sessions.
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            rate_limiter: None,
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Set a rate limiter which is shared by all sessions created via
+    /// [`CrunchyrollPool::builder`].
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Return a [`CrunchyrollBuilder`] which uses the shared client and rate limiter of this pool.
+    /// Add the session which is created with it via [`CrunchyrollPool::insert`]. Setting
+    /// [`CrunchyrollBuilder::client`] or [`CrunchyrollBuilder::proxy`] on the builder creates a
+    /// session which doesn't share the client anymore.
+    pub fn builder(&self) -> CrunchyrollBuilder {
+        let mut builder = Crunchyroll::builder().client(self.client.clone());
+        if let Some(rate_limiter) = &self.rate_limiter {
+            builder = builder.rate_limiter(rate_limiter.clone())
+        }
+        builder
+    }
+
+    /// Add a session to the pool. If a session with the same key already exists, it's replaced and
+    /// returned.
+    pub async fn insert(&self, key: K, crunchyroll: Crunchyroll) -> Option<Crunchyroll> {
+        self.sessions.write().await.insert(key, crunchyroll)
+    }
+
+    /// Get the session with the given key.
+    pub async fn get(&self, key: &K) -> Option<Crunchyroll> {
+        self.sessions.read().await.get(key).cloned()
+    }
+
+    /// Remove the session with the given key from the pool.
+    pub async fn remove(&self, key: &K) -> Option<Crunchyroll> {
+        self.sessions.write().await.remove(key)
+    }
+
+    /// Return the keys of all sessions in the pool.
+    pub async fn keys(&self) -> Vec<K> {
+        self.sessions.read().await.keys().cloned().collect()
+    }
+
+    /// Return the number of sessions in the pool.
+    pub async fn len(&self) -> usize {
+        self.sessions.read().await.len()
+    }
+
+    /// Check if the pool has no sessions.
+    pub async fn is_empty(&self) -> bool {
+        self.sessions.read().await.is_empty()
+    }
+}
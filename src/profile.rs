@@ -42,8 +42,20 @@ pub struct Profile {
 
     pub maturity_rating: MaturityRating,
 
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub preferred_communication_language: Option<Locale>,
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub preferred_content_audio_language: Option<Locale>,
+    #[serde(
+        default,
+        deserialize_with = "crate::internal::serde::deserialize_maybe_empty_locale"
+    )]
     pub preferred_content_subtitle_language: Option<Locale>,
 
     #[cfg(feature = "__test_strict")]
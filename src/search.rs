@@ -3,10 +3,17 @@
 mod browse {
     use crate::categories::Category;
     use crate::common::{Pagination, PaginationBulkResultMeta, V2BulkResult};
-    use crate::media::MediaType;
+    use crate::common::{PaginationData, PaginationNextType};
+    use crate::crunchyroll::Executor;
+    use crate::error::Error;
+    use crate::media::{MediaType, Series};
     use crate::{enum_values, options, Crunchyroll, Locale, MediaCollection, Request, Result};
+    use chrono::Datelike;
     use futures_util::FutureExt;
     use serde::{Deserialize, Serialize};
+    use std::cmp::Ordering;
+    use std::str::FromStr;
+    use std::sync::Arc;
 
     /// Human readable implementation of [`SimulcastSeason`].
     #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -17,13 +24,114 @@ mod browse {
         pub description: String,
     }
 
-    /// A simulcast season.
+    /// Season of a year in which anime are simulcasted. The variants are ordered chronologically.
+    #[derive(
+        Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd, Hash,
+    )]
+    #[serde(rename_all = "lowercase")]
+    pub enum SeasonOfYear {
+        /// January to March.
+        #[default]
+        Winter,
+        /// April to June.
+        Spring,
+        /// July to September.
+        Summer,
+        /// October to December.
+        Fall,
+    }
+
+    impl SeasonOfYear {
+        /// The season the given month (`1` to `12`) belongs to.
+        pub fn from_month(month: u32) -> SeasonOfYear {
+            match month {
+                1..=3 => SeasonOfYear::Winter,
+                4..=6 => SeasonOfYear::Spring,
+                7..=9 => SeasonOfYear::Summer,
+                _ => SeasonOfYear::Fall,
+            }
+        }
+    }
+
+    impl FromStr for SeasonOfYear {
+        type Err = Error;
+
+        fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+            match s {
+                "winter" => Ok(SeasonOfYear::Winter),
+                "spring" => Ok(SeasonOfYear::Spring),
+                "summer" => Ok(SeasonOfYear::Summer),
+                "fall" => Ok(SeasonOfYear::Fall),
+                _ => Err(Error::Input {
+                    message: format!("'{s}' is no season of year"),
+                }),
+            }
+        }
+    }
+
+    /// A simulcast season. Simulcast seasons are compared / ordered chronologically by
+    /// [`SimulcastSeason::year`] and [`SimulcastSeason::season`], seasons whose id has an unknown
+    /// format are ordered before all others. Ties are broken by [`SimulcastSeason::id`].
     #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
-    #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
-    #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+    #[serde(from = "SimulcastSeasonData")]
     pub struct SimulcastSeason {
+        /// Id of the season, e.g. `fall-2024`. Use it as [`BrowseOptions::simulcast_season`].
         pub id: String,
         pub localization: SimulcastSeasonLocalization,
+
+        /// Year of the season, parsed from [`SimulcastSeason::id`]. [`None`] if the id has an
+        /// unknown format.
+        pub year: Option<u16>,
+        /// Season of the year, parsed from [`SimulcastSeason::id`]. [`None`] if the id has an
+        /// unknown format.
+        pub season: Option<SeasonOfYear>,
+    }
+
+    /// The fields of a [`SimulcastSeason`] which the api returns.
+    #[derive(Default, Deserialize)]
+    #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+    #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+    struct SimulcastSeasonData {
+        id: String,
+        localization: SimulcastSeasonLocalization,
+    }
+
+    impl From<SimulcastSeasonData> for SimulcastSeason {
+        fn from(data: SimulcastSeasonData) -> Self {
+            let (year, season) = parse_id(&data.id).unzip();
+            Self {
+                id: data.id,
+                localization: data.localization,
+                year,
+                season,
+            }
+        }
+    }
+
+    /// Parses the year and season of a simulcast season id like `fall-2024`.
+    fn parse_id(id: &str) -> Option<(u16, SeasonOfYear)> {
+        let (season, year) = id.split_once('-')?;
+        Some((year.parse().ok()?, season.parse().ok()?))
+    }
+
+    impl PartialEq for SimulcastSeason {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for SimulcastSeason {}
+
+    impl PartialOrd for SimulcastSeason {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for SimulcastSeason {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (self.year, self.season, &self.id).cmp(&(other.year, other.season, &other.id))
+        }
     }
 
     #[allow(dead_code)]
@@ -63,7 +171,99 @@ mod browse {
         /// Specifies how the entries should be sorted.
         sort(BrowseSortType, "sort_by") = Some(BrowseSortType::NewlyAdded),
         /// Specifies the media type of the entries.
-        media_type(MediaType, "type") = None
+        media_type(MediaType, "type") = None,
+        /// Only request the given fields of the entries (e.g. `["id", "title"]`) to reduce the
+        /// response size. All other fields have their default value.
+        fields(Vec<String>, "fields") = None
+    }
+
+    enum_values! {
+        /// Type of music media.
+        pub enum MusicMediaType {
+            MusicVideo = "music_video"
+            Concert = "music_concert"
+        }
+    }
+
+    options! {
+        /// Options how to browse the music catalog.
+        MusicBrowseOptions;
+        /// Specifies how the entries should be sorted.
+        sort(BrowseSortType, "sort_by") = Some(BrowseSortType::NewlyAdded),
+        /// Specifies the music media type of the entries. If not set, music videos and concerts
+        /// are returned.
+        media_type(MusicMediaType, "type") = None
+    }
+
+    /// Total amount of entries per media type in the Crunchyroll catalog. Obtained via
+    /// [`Crunchyroll::browse_totals`]. See [`Crunchyroll::browse_index`] for the amount of series
+    /// per letter.
+    #[derive(Clone, Debug, Default, Serialize)]
+    pub struct BrowseTotals {
+        pub series: u32,
+        pub movie_listings: u32,
+        pub music_videos: u32,
+        pub concerts: u32,
+    }
+
+    /// All series of the alphabetically sorted catalog which start with the same prefix (a letter
+    /// or `#` for everything which doesn't start with a letter). Obtained via
+    /// [`Crunchyroll::browse_index`].
+    #[derive(Clone, Debug, Default, Deserialize, Serialize, Request)]
+    #[cfg_attr(feature = "__test_strict", serde(deny_unknown_fields))]
+    #[cfg_attr(not(feature = "__test_strict"), serde(default))]
+    pub struct BrowseIndexPrefix {
+        #[serde(skip)]
+        executor: Arc<Executor>,
+
+        pub prefix: String,
+        /// Position of the first series with this prefix in the alphabetically sorted catalog.
+        pub offset: u32,
+        /// Amount of series with this prefix.
+        pub total: u32,
+    }
+
+    impl BrowseIndexPrefix {
+        /// Returns all series which start with [`BrowseIndexPrefix::prefix`].
+        pub fn series(&self) -> Pagination<Series> {
+            let (offset, total) = (self.offset, self.total);
+            Pagination::new(
+                move |options| {
+                    async move {
+                        if options.start >= total {
+                            return Ok(PaginationData {
+                                data: vec![],
+                                next_type: PaginationNextType::Total(total),
+                            });
+                        }
+                        let endpoint = "https://www.crunchyroll.com/content/v2/discover/browse";
+                        let result: V2BulkResult<Series, PaginationBulkResultMeta> = options
+                            .executor
+                            .clone()
+                            .get(endpoint)
+                            .query(&[
+                                ("type", MediaType::Series.to_string()),
+                                ("sort_by", BrowseSortType::Alphabetical.to_string()),
+                            ])
+                            .query(&[
+                                ("n", options.page_size.min(total - options.start)),
+                                ("start", offset + options.start),
+                            ])
+                            .apply_locale_query()
+                            .request()
+                            .await?;
+                        Ok(PaginationData {
+                            data: result.data,
+                            next_type: PaginationNextType::Total(total),
+                        })
+                    }
+                    .boxed()
+                },
+                self.executor.clone(),
+                None,
+                None,
+            )
+        }
     }
 
     impl Crunchyroll {
@@ -95,6 +295,112 @@ mod browse {
             )
         }
 
+        /// Browses the crunchyroll music catalog filtered by the specified options and returns all
+        /// found music videos and concerts.
+        pub fn browse_music(&self, options: MusicBrowseOptions) -> Pagination<MediaCollection> {
+            let mut query = options.into_query();
+            if !query.iter().any(|(k, _)| k == "type") {
+                query.push((
+                    "type".to_string(),
+                    format!("{},{}", MusicMediaType::MusicVideo, MusicMediaType::Concert),
+                ))
+            }
+
+            Pagination::new(
+                |options| {
+                    async move {
+                        let endpoint = "https://www.crunchyroll.com/content/v2/discover/browse";
+                        let result: V2BulkResult<MediaCollection, PaginationBulkResultMeta> =
+                            options
+                                .executor
+                                .clone()
+                                .get(endpoint)
+                                .query(&options.query)
+                                .query(&[("n", options.page_size), ("start", options.start)])
+                                .apply_locale_query()
+                                .request()
+                                .await?;
+                        Ok(result.into())
+                    }
+                    .boxed()
+                },
+                self.executor.clone(),
+                Some(query),
+                None,
+            )
+        }
+
+        /// Returns the total amount of entries which match the specified options without fetching
+        /// the entries themselves. Useful to count the entries of e.g. a specific category.
+        pub async fn browse_total(&self, options: BrowseOptions) -> Result<u32> {
+            self.browse_total_from_query(options.into_query()).await
+        }
+
+        /// Returns the total amount of entries per media type in the Crunchyroll catalog. Only
+        /// requires one (small) request per media type instead of crawling the whole catalog.
+        pub async fn browse_totals(&self) -> Result<BrowseTotals> {
+            let (series, movie_listings, music_videos, concerts) = futures_util::try_join!(
+                self.browse_total_from_query(vec![(
+                    "type".to_string(),
+                    MediaType::Series.to_string()
+                )]),
+                self.browse_total_from_query(vec![(
+                    "type".to_string(),
+                    MediaType::Movie.to_string()
+                )]),
+                self.browse_total_from_query(vec![(
+                    "type".to_string(),
+                    MusicMediaType::MusicVideo.to_string()
+                )]),
+                self.browse_total_from_query(vec![(
+                    "type".to_string(),
+                    MusicMediaType::Concert.to_string()
+                )])
+            )?;
+
+            Ok(BrowseTotals {
+                series,
+                movie_listings,
+                music_videos,
+                concerts,
+            })
+        }
+
+        /// Returns the alphabetical (A-Z) index of all series in the catalog, with the amount of
+        /// series per prefix. Use [`BrowseIndexPrefix::series`] to get the series of a prefix, this
+        /// enumerates the whole catalog more reliably than [`Crunchyroll::browse`] does.
+        ///
+        /// The `/content/v2/discover/browse/index` endpoint this uses is undocumented and its
+        /// response format is only known from the website, so it may change without notice.
+        pub async fn browse_index(&self) -> Result<Vec<BrowseIndexPrefix>> {
+            let endpoint = "https://www.crunchyroll.com/content/v2/discover/browse/index";
+            Ok(self
+                .executor
+                .get(endpoint)
+                .query(&[
+                    ("type", MediaType::Series.to_string()),
+                    ("sort_by", BrowseSortType::Alphabetical.to_string()),
+                ])
+                .apply_locale_query()
+                .request::<V2BulkResult<BrowseIndexPrefix>>()
+                .await?
+                .data)
+        }
+
+        async fn browse_total_from_query(&self, query: Vec<(String, String)>) -> Result<u32> {
+            let endpoint = "https://www.crunchyroll.com/content/v2/discover/browse";
+            let result: V2BulkResult<MediaCollection> = self
+                .executor
+                .get(endpoint)
+                .query(&query)
+                // requesting zero items is not supported, so request as few as possible
+                .query(&[("n", 1)])
+                .apply_locale_query()
+                .request()
+                .await?;
+            Ok(result.total)
+        }
+
         /// Returns all simulcast seasons. The locale specified which language the localization /
         /// human readable name ([`SimulcastSeasonLocalization::title`]) has.
         pub async fn simulcast_seasons(&self, locale: Locale) -> Result<Vec<SimulcastSeason>> {
@@ -107,14 +413,123 @@ mod browse {
                 .await?
                 .items)
         }
+
+        /// Returns the simulcast season which is currently airing. If Crunchyroll hasn't listed the
+        /// current season (yet), the latest season before it is returned. Use the id of the result
+        /// as [`BrowseOptions::simulcast_season`] to browse the current season.
+        pub async fn current_simulcast_season(
+            &self,
+            locale: Locale,
+        ) -> Result<Option<SimulcastSeason>> {
+            let now = self.executor.clock.now();
+            let current = (
+                Some(now.year() as u16),
+                Some(SeasonOfYear::from_month(now.month())),
+            );
+            Ok(self
+                .simulcast_seasons(locale)
+                .await?
+                .into_iter()
+                .filter(|season| season.year.is_some() && (season.year, season.season) <= current)
+                .max())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn season(id: &str) -> SimulcastSeason {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "localization": { "title": "", "description": "" }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn season_of_year_from_str() {
+            assert_eq!(
+                "winter".parse::<SeasonOfYear>().unwrap(),
+                SeasonOfYear::Winter
+            );
+            assert_eq!("fall".parse::<SeasonOfYear>().unwrap(), SeasonOfYear::Fall);
+            assert!("autumn".parse::<SeasonOfYear>().is_err());
+            assert!("Fall".parse::<SeasonOfYear>().is_err())
+        }
+
+        #[test]
+        fn simulcast_season_parse_id() {
+            assert_eq!(parse_id("fall-2024"), Some((2024, SeasonOfYear::Fall)));
+            assert_eq!(parse_id("spring-1999"), Some((1999, SeasonOfYear::Spring)));
+            assert_eq!(parse_id("fall2024"), None);
+            assert_eq!(parse_id("fall-twenty"), None);
+            assert_eq!(parse_id("monsoon-2024"), None);
+
+            let parsed = season("summer-2023");
+            assert_eq!(parsed.year, Some(2023));
+            assert_eq!(parsed.season, Some(SeasonOfYear::Summer));
+            let unparsed = season("special");
+            assert_eq!((unparsed.year, unparsed.season), (None, None))
+        }
+
+        #[test]
+        fn simulcast_season_ord() {
+            // seasons with an unknown id format are not equal to each other
+            assert_ne!(season("special"), season("other"));
+            assert_eq!(season("fall-2024"), season("fall-2024"));
+
+            let mut seasons = [
+                season("winter-2025"),
+                season("special"),
+                season("fall-2024"),
+                season("winter-2024"),
+            ];
+            seasons.sort();
+            assert_eq!(
+                seasons.iter().map(|s| s.id.as_str()).collect::<Vec<&str>>(),
+                vec!["special", "winter-2024", "fall-2024", "winter-2025"]
+            )
+        }
+
+        // the strict feature rejects the parsed fields, which aren't part of the api response
+        #[cfg(not(feature = "__test_strict"))]
+        #[test]
+        fn simulcast_season_serde_round_trip() {
+            let original = season("spring-2024");
+            let round_trip: SimulcastSeason =
+                serde_json::from_value(serde_json::to_value(&original).unwrap()).unwrap();
+
+            assert_eq!(round_trip.year, Some(2024));
+            assert_eq!(round_trip.season, Some(SeasonOfYear::Spring));
+            assert_eq!(round_trip, original)
+        }
     }
 }
 
 mod query {
+    use crate::categories::Category;
     use crate::common::{Pagination, V2BulkResult, V2TypeBulkResult};
     use crate::media::{Episode, MovieListing, Series};
-    use crate::{Crunchyroll, MediaCollection, MusicVideo};
+    use crate::{options, Crunchyroll, MediaCollection, MusicVideo};
     use futures_util::FutureExt;
+    use serde::{Deserialize, Serialize};
+
+    options! {
+        /// Options how to query.
+        QueryOptions;
+        /// Specifies the categories of the results.
+        categories(Vec<Category>, "categories") = None,
+        /// Specifies whether the results should be dubbed.
+        is_dubbed(bool, "is_dubbed") = None,
+        /// Specifies whether the results should be subbed.
+        is_subbed(bool, "is_subbed") = None,
+        /// Specifies whether the results should include their ratings.
+        ratings(bool, "ratings") = None,
+        /// Only request the given fields of the results (e.g. `["id", "title"]`) to reduce the
+        /// response size. All other fields have their default value.
+        fields(Vec<String>, "fields") = None
+    }
 
     /// Results when querying Crunchyroll.
     pub struct QueryResults {
@@ -128,6 +543,14 @@ mod query {
     impl Crunchyroll {
         /// Search the Crunchyroll catalog by a given query / string.
         pub fn query<S: AsRef<str>>(&self, query: S) -> QueryResults {
+            self.query_with(query, QueryOptions::default())
+        }
+
+        /// Search the Crunchyroll catalog by a given query / string, filtered by the specified
+        /// options.
+        pub fn query_with<S: AsRef<str>>(&self, query: S, options: QueryOptions) -> QueryResults {
+            let query_options = options.into_query();
+            let extra = vec![("q", query.as_ref().to_string())];
             QueryResults {
                 top_results: Pagination::new(
                     |options| {
@@ -137,6 +560,7 @@ mod query {
                                 .executor
                                 .get(endpoint)
                                 .query(&[("q", options.extra.get("q").unwrap())])
+                                .query(&options.query)
                                 .query(&[("type", "top_results")])
                                 .query(&[("limit", options.page_size), ("start", options.start)])
                                 .apply_locale_query()
@@ -152,8 +576,8 @@ mod query {
                         .boxed()
                     },
                     self.executor.clone(),
-                    None,
-                    Some(vec![("q", query.as_ref().to_string())]),
+                    Some(query_options.clone()),
+                    Some(extra.clone()),
                 ),
                 series: Pagination::new(
                     |options| {
@@ -163,6 +587,7 @@ mod query {
                                 .executor
                                 .get(endpoint)
                                 .query(&[("q", options.extra.get("q").unwrap())])
+                                .query(&options.query)
                                 .query(&[("type", "series")])
                                 .query(&[("limit", options.page_size), ("start", options.start)])
                                 .apply_locale_query()
@@ -178,8 +603,8 @@ mod query {
                         .boxed()
                     },
                     self.executor.clone(),
-                    None,
-                    Some(vec![("q", query.as_ref().to_string())]),
+                    Some(query_options.clone()),
+                    Some(extra.clone()),
                 ),
                 movie_listing: Pagination::new(
                     |options| {
@@ -189,6 +614,7 @@ mod query {
                                 .executor
                                 .get(endpoint)
                                 .query(&[("q", options.extra.get("q").unwrap())])
+                                .query(&options.query)
                                 .query(&[("type", "movie_listing")])
                                 .query(&[("limit", options.page_size), ("start", options.start)])
                                 .apply_locale_query()
@@ -204,8 +630,8 @@ mod query {
                         .boxed()
                     },
                     self.executor.clone(),
-                    None,
-                    Some(vec![("q", query.as_ref().to_string())]),
+                    Some(query_options.clone()),
+                    Some(extra.clone()),
                 ),
                 episode: Pagination::new(
                     |options| {
@@ -215,6 +641,7 @@ mod query {
                                 .executor
                                 .get(endpoint)
                                 .query(&[("q", options.extra.get("q").unwrap())])
+                                .query(&options.query)
                                 .query(&[("type", "episode")])
                                 .query(&[("limit", options.page_size), ("start", options.start)])
                                 .apply_locale_query()
@@ -230,8 +657,8 @@ mod query {
                         .boxed()
                     },
                     self.executor.clone(),
-                    None,
-                    Some(vec![("q", query.as_ref().to_string())]),
+                    Some(query_options.clone()),
+                    Some(extra.clone()),
                 ),
                 music: Pagination::new(
                     |options| {
@@ -241,6 +668,7 @@ mod query {
                                 .executor
                                 .get(endpoint)
                                 .query(&[("q", options.extra.get("q").unwrap())])
+                                .query(&options.query)
                                 .query(&[("type", "music")])
                                 .query(&[("limit", options.page_size), ("start", options.start)])
                                 .apply_locale_query()
@@ -256,8 +684,8 @@ mod query {
                         .boxed()
                     },
                     self.executor.clone(),
-                    None,
-                    Some(vec![("q", query.as_ref().to_string())]),
+                    Some(query_options),
+                    Some(extra),
                 ),
             }
         }
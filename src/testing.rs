@@ -0,0 +1,672 @@
+//! Utilities to test code which uses this crate without network access.
+//!
+//! Create a [`MockTransport`], register canned responses for the endpoints your code calls and pass
+//! it to [`crate::crunchyroll::CrunchyrollBuilder::with_transport`]. Every request is then answered
+//! by the transport instead of Crunchyroll.
+//!
+//! ```
+//! use crunchyroll_rs::{Crunchyroll, Series};
+//! use crunchyroll_rs::testing::{fixtures, MockTransport};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), crunchyroll_rs::error::Error> {
+//! let transport = MockTransport::new().on(
+//!     "/content/v2/cms/series/GY8VEQ95Y",
+//!     fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+//! );
+//! let crunchy = Crunchyroll::builder()
+//!     .with_transport(transport.clone())
+//!     .login_anonymously()
+//!     .await?;
+//! let series: Series = crunchy.media_from_id("GY8VEQ95Y").await?;
+//! assert_eq!(series.title, "Darling in the Franxx");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Instead of writing the responses by hand, they can be recorded from the real api with a
+//! [`FixtureRecorder`]. It anonymizes the responses, which can then be stored and replayed offline
+//! via [`MockTransport::from_fixture_file`].
+//!
+//! ```no_run
+//! use crunchyroll_rs::{Crunchyroll, Series};
+//! use crunchyroll_rs::testing::{FixtureRecorder, MockTransport};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), crunchyroll_rs::error::Error> {
+//! let recorder = FixtureRecorder::new();
+//! let crunchy = Crunchyroll::builder()
+//!     .record_fixtures(recorder.clone())
+//!     .login_with_credentials("email", "password")
+//!     .await?;
+//! let series: Series = crunchy.media_from_id("GY8VEQ95Y").await?;
+//! recorder.save("tests/fixtures/series.json")?;
+//!
+//! // later, without credentials or network access
+//! let crunchy = Crunchyroll::builder()
+//!     .with_transport(MockTransport::from_fixture_file("tests/fixtures/series.json")?)
+//!     .login_anonymously()
+//!     .await?;
+//! let series: Series = crunchy.media_from_id("GY8VEQ95Y").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Error;
+use crate::Result;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug)]
+struct MockResponse {
+    method: Option<Method>,
+    path: String,
+    /// Query pairs the request must contain. Responses without a query match every query.
+    query: Option<Vec<(String, String)>>,
+    status: StatusCode,
+    content_type: &'static str,
+    body: String,
+}
+
+/// Answers requests with canned responses instead of sending them over the network. Responses are
+/// matched by the path of the request url, the host is ignored. The query is ignored too, except
+/// for recorded fixtures (see [`Fixture::query`]). If multiple responses match a request, the one
+/// which was registered last is used. Requests without a matching response
+/// are answered with status `404`.
+#[derive(Clone, Debug)]
+pub struct MockTransport {
+    responses: Arc<Mutex<Vec<MockResponse>>>,
+    requests: Arc<Mutex<Vec<(Method, Url, HeaderMap)>>>,
+    headers: Arc<Mutex<Vec<(String, String)>>>,
+    failures: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTransport {
+    /// Creates a new transport which already contains the responses that are required to log in
+    /// (see [`fixtures::token`] and [`fixtures::index`]).
+    pub fn new() -> Self {
+        let transport = Self {
+            responses: Arc::new(Mutex::new(vec![])),
+            requests: Arc::new(Mutex::new(vec![])),
+            headers: Arc::new(Mutex::new(vec![])),
+            failures: Arc::new(Mutex::new(vec![])),
+        };
+        transport.register(MockResponse {
+            method: None,
+            path: "/".to_string(),
+            query: None,
+            status: StatusCode::OK,
+            content_type: "text/html",
+            body: fixtures::app_config(),
+        });
+        transport
+            .on("/auth/v1/token", fixtures::token())
+            .on("/index/v2", fixtures::index())
+    }
+
+    /// Answers all requests to `path` with `body` as json and status `200`.
+    pub fn on<S: AsRef<str>>(self, path: S, body: Value) -> Self {
+        self.on_status(path, 200, body)
+    }
+
+    /// Answers all requests with the given method to `path` with `body` as json and status `200`.
+    pub fn on_method<S: AsRef<str>>(self, method: Method, path: S, body: Value) -> Self {
+        self.register(MockResponse {
+            method: Some(method),
+            path: normalize_path(path.as_ref()),
+            query: None,
+            status: StatusCode::OK,
+            content_type: "application/json",
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Answers all requests to `path` with `body` as json and the given status code. Use this to
+    /// test how your code handles api errors.
+    pub fn on_status<S: AsRef<str>>(self, path: S, status: u16, body: Value) -> Self {
+        self.register(MockResponse {
+            method: None,
+            path: normalize_path(path.as_ref()),
+            query: None,
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            content_type: "application/json",
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Answers all requests to `path` with `body` as the given content type and status `200`, e.g.
+    /// to serve a DASH manifest (`application/dash+xml`).
+    pub fn on_text<S: AsRef<str>, B: Into<String>>(
+        self,
+        path: S,
+        content_type: &'static str,
+        body: B,
+    ) -> Self {
+        self.on_text_status(path, 200, content_type, body)
+    }
+
+    /// Like [`MockTransport::on_text`] but with the given status code, e.g. to serve the html page
+    /// of a request which got blocked.
+    pub fn on_text_status<S: AsRef<str>, B: Into<String>>(
+        self,
+        path: S,
+        status: u16,
+        content_type: &'static str,
+        body: B,
+    ) -> Self {
+        self.register(MockResponse {
+            method: None,
+            path: normalize_path(path.as_ref()),
+            query: None,
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            content_type,
+            body: body.into(),
+        });
+        self
+    }
+
+    /// Sends the given header with every response, e.g. to test how your code handles request ids
+    /// (see the `request_id` field of [`crate::error::Error::Request`]).
+    pub fn header<S: AsRef<str>>(self, name: S, value: S) -> Self {
+        self.headers
+            .lock()
+            .unwrap()
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// Lets the next request to `path` fail like a broken connection, with an
+    /// [`crate::error::Error::Request`] which has no status. Following requests to `path` are
+    /// answered as usual, e.g. to test retries.
+    pub fn fail_once<S: AsRef<str>>(self, path: S) -> Self {
+        self.failures
+            .lock()
+            .unwrap()
+            .push(normalize_path(path.as_ref()));
+        self
+    }
+
+    /// Answers requests with the given recorded fixtures (see [`FixtureRecorder`]). Every fixture
+    /// only answers requests with the method and query it was recorded with.
+    pub fn with_fixtures(self, fixtures: Vec<Fixture>) -> Self {
+        for fixture in fixtures {
+            self.register(MockResponse {
+                method: Method::from_bytes(fixture.method.as_bytes()).ok(),
+                path: normalize_path(&fixture.path),
+                query: fixture.query.as_deref().map(query_pairs),
+                status: StatusCode::from_u16(fixture.status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                content_type: "application/json",
+                body: fixture.body.to_string(),
+            })
+        }
+        self
+    }
+
+    /// Creates a new transport (like [`MockTransport::new`]) which answers requests with the
+    /// fixtures of a file written by [`FixtureRecorder::save`].
+    pub fn from_fixture_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let raw = std::fs::read(path.as_ref()).map_err(|e| Error::Input {
+            message: format!(
+                "failed to read fixture file {}: {e}",
+                path.as_ref().to_string_lossy()
+            ),
+        })?;
+        let fixtures = serde_json::from_slice(&raw).map_err(|e| Error::Decode {
+            message: format!("invalid fixture file: {e}"),
+            content: raw.clone(),
+            url: path.as_ref().to_string_lossy().to_string(),
+            request_id: None,
+        })?;
+        Ok(Self::new().with_fixtures(fixtures))
+    }
+
+    /// All requests which were answered by this transport, in the order they were made.
+    pub fn requests(&self) -> Vec<(Method, Url)> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, url, _)| (method.clone(), url.clone()))
+            .collect()
+    }
+
+    /// Like [`MockTransport::requests`] but with the headers each request was sent with.
+    pub fn requests_with_headers(&self) -> Vec<(Method, Url, HeaderMap)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn register(&self, response: MockResponse) {
+        self.responses.lock().unwrap().push(response)
+    }
+
+    pub(crate) fn respond(&self, req: reqwest::Request) -> Result<reqwest::Response> {
+        self.requests.lock().unwrap().push((
+            req.method().clone(),
+            req.url().clone(),
+            req.headers().clone(),
+        ));
+
+        let path = normalize_path(req.url().path());
+        let mut failures = self.failures.lock().unwrap();
+        if let Some(pos) = failures.iter().position(|failure| *failure == path) {
+            failures.remove(pos);
+            return Err(Error::Request {
+                message: format!("mock connection to {} failed", req.url()),
+                status: None,
+                url: req.url().to_string(),
+                code: None,
+                request_id: None,
+            });
+        }
+        drop(failures);
+
+        let query = query_pairs(req.url().query().unwrap_or_default());
+        let responses = self.responses.lock().unwrap();
+        let response = responses.iter().rev().find(|r| {
+            r.path == path
+                && r.method
+                    .as_ref()
+                    .is_none_or(|method| method == req.method())
+                && r.query.as_ref().is_none_or(|q| *q == query)
+        });
+
+        let (status, content_type, body) = match response {
+            Some(response) => (
+                response.status,
+                response.content_type,
+                response.body.clone(),
+            ),
+            None => (
+                StatusCode::NOT_FOUND,
+                "application/json",
+                json!({ "message": format!("no mock response registered for '{path}'") })
+                    .to_string(),
+            ),
+        };
+
+        let mut builder = http::Response::builder()
+            .status(status)
+            .header(reqwest::header::CONTENT_TYPE, content_type);
+        for (name, value) in self.headers.lock().unwrap().iter() {
+            builder = builder.header(name, value)
+        }
+        let response = builder.body(body).map_err(|e| Error::Internal {
+            message: e.to_string(),
+        })?;
+        Ok(reqwest::Response::from(response))
+    }
+}
+
+/// A recorded api response. See [`FixtureRecorder`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Fixture {
+    pub method: String,
+    /// Path of the request url.
+    pub path: String,
+    /// Query of the request url, if it had one. Replayed fixtures only answer requests with the
+    /// same query parameters (in any order), so e.g. the pages of a paginated request are replayed
+    /// correctly. Remove it to answer requests with any query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub status: u16,
+    pub body: Value,
+}
+
+/// Keys whose values are replaced when recording a response. Their values are also replaced in all
+/// other strings of the recorded responses, e.g. if a url contains a token.
+const SENSITIVE_KEYS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "account_id",
+    "profile_id",
+    "external_id",
+    "etp_guid",
+    "device_id",
+    "email",
+    "username",
+    "phone",
+    "token",
+    "policy",
+    "signature",
+    "key_pair_id",
+];
+
+/// Paths which are answered by [`MockTransport::new`] and therefore not recorded.
+const UNRECORDED_PATHS: &[&str] = &["/", "/auth/v1/token", "/index/v2"];
+
+/// Records the json responses of all requests a [`crate::Crunchyroll`] instance makes, to replay
+/// them later offline with [`MockTransport::with_fixtures`] or
+/// [`MockTransport::from_fixture_file`]. Set it via
+/// [`crate::crunchyroll::CrunchyrollBuilder::record_fixtures`].
+///
+/// The recorded responses are anonymized: values of keys like `account_id`, `email` or
+/// `access_token` are replaced, and so is every occurrence of them in other values or the request
+/// path. The account id is replaced with [`fixtures::ACCOUNT_ID`], so the fixtures match the
+/// session of [`MockTransport::new`]. Responses of the login endpoints aren't recorded, as
+/// [`MockTransport::new`] already answers them.
+#[derive(Clone, Debug, Default)]
+pub struct FixtureRecorder {
+    fixtures: Arc<Mutex<Vec<Fixture>>>,
+    /// Sensitive values and their replacements.
+    secrets: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl FixtureRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All fixtures which were recorded so far, in the order the requests were made.
+    pub fn fixtures(&self) -> Vec<Fixture> {
+        self.fixtures.lock().unwrap().clone()
+    }
+
+    /// Write all recorded fixtures as json to `path`. Load them via
+    /// [`MockTransport::from_fixture_file`].
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.fixtures()).map_err(|e| Error::Internal {
+            message: e.to_string(),
+        })?;
+        std::fs::write(path.as_ref(), json).map_err(|e| Error::Input {
+            message: format!(
+                "failed to write fixture file {}: {e}",
+                path.as_ref().to_string_lossy()
+            ),
+        })
+    }
+
+    /// Record `resp` if it's json. As this consumes the body, a copy of the response is returned.
+    pub(crate) async fn record(
+        &self,
+        method: &Method,
+        url: &Url,
+        resp: reqwest::Response,
+    ) -> Result<reqwest::Response> {
+        let is_json = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.contains("json"));
+        if !is_json {
+            return Ok(resp);
+        }
+
+        let status = resp.status();
+        let path = url.path();
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in resp.headers() {
+            builder = builder.header(name, value)
+        }
+        let body = resp.bytes().await?;
+
+        if let Ok(mut value) = serde_json::from_slice::<Value>(&body) {
+            self.collect_secrets(&value);
+            if !UNRECORDED_PATHS.contains(&normalize_path(path).as_str()) {
+                self.anonymize(&mut value);
+                self.fixtures.lock().unwrap().push(Fixture {
+                    method: method.to_string(),
+                    path: self.replace_secrets(path),
+                    query: url.query().map(|query| self.replace_secrets(query)),
+                    status: status.as_u16(),
+                    body: value,
+                })
+            }
+        }
+
+        let response = builder.body(body).map_err(|e| Error::Internal {
+            message: e.to_string(),
+        })?;
+        Ok(reqwest::Response::from(response))
+    }
+
+    fn collect_secrets(&self, value: &Value) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    match value {
+                        Value::String(secret) if SENSITIVE_KEYS.contains(&key.as_str()) => {
+                            self.add_secret(key, secret)
+                        }
+                        _ => self.collect_secrets(value),
+                    }
+                }
+            }
+            Value::Array(array) => array.iter().for_each(|v| self.collect_secrets(v)),
+            _ => (),
+        }
+    }
+
+    fn add_secret(&self, key: &str, secret: &str) {
+        let replacement = secret_replacement(key);
+        let mut secrets = self.secrets.lock().unwrap();
+        // very short values would replace arbitrary parts of other strings. they're still replaced
+        // if they're the value of a sensitive key
+        if secret.len() < 6
+            || secret == replacement
+            || secrets.iter().any(|(s, r)| s == secret || r == secret)
+        {
+            return;
+        }
+        secrets.push((secret.to_string(), replacement));
+        // replace longer secrets first, in case one secret contains another
+        secrets.sort_by_key(|(secret, _)| std::cmp::Reverse(secret.len()))
+    }
+
+    fn anonymize(&self, value: &mut Value) {
+        match value {
+            Value::String(string) => *string = self.replace_secrets(string),
+            Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    match value {
+                        Value::String(secret)
+                            if !secret.is_empty() && SENSITIVE_KEYS.contains(&key.as_str()) =>
+                        {
+                            *secret = secret_replacement(key)
+                        }
+                        _ => self.anonymize(value),
+                    }
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(|v| self.anonymize(v)),
+            _ => (),
+        }
+    }
+
+    fn replace_secrets(&self, string: &str) -> String {
+        let mut string = string.to_string();
+        for (secret, replacement) in self.secrets.lock().unwrap().iter() {
+            string = string.replace(secret, replacement)
+        }
+        string
+    }
+}
+
+fn secret_replacement(key: &str) -> String {
+    if key == "account_id" {
+        fixtures::ACCOUNT_ID.to_string()
+    } else {
+        format!("anonymized-{}", key.replace('_', "-"))
+    }
+}
+
+/// Query pairs of `query`, sorted so that the order of the parameters doesn't matter.
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+    pairs.sort();
+    pairs
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = Url::parse(path)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| path.split('?').next().unwrap_or_default().to_string());
+    let trimmed = path.trim_matches('/');
+    format!("/{trimmed}")
+}
+
+/// Json fixtures of common api responses. They contain only the most important fields; all other
+/// fields have their default value when deserialized.
+pub mod fixtures {
+    use serde_json::{json, Value};
+
+    /// Account id of the session which is created by [`token`].
+    pub const ACCOUNT_ID: &str = "mock-account-id";
+
+    pub(crate) fn app_config() -> String {
+        r#"<!DOCTYPE html><html><script>window.__APP_CONFIG__ = {"cxApiParams":{"accountAuthClientId":"mock-client-id"}};</script></html>"#
+            .to_string()
+    }
+
+    /// Response of the `/auth/v1/token` endpoint. The session doesn't expire in the lifetime of a
+    /// test. The access token is an unsigned jwt which contains the country `US` and no premium
+    /// benefits.
+    pub fn token() -> Value {
+        json!({
+            "access_token": access_token(),
+            "refresh_token": "mock-refresh-token",
+            "expires_in": 86400,
+            "token_type": "Bearer",
+            "scope": "account content offline_access",
+            "country": "US",
+            "account_id": ACCOUNT_ID,
+            "profile_id": "mock-profile-id"
+        })
+    }
+
+    fn access_token() -> String {
+        use base64::Engine;
+
+        let encode = |value: Value| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+        };
+        format!(
+            "{}.{}.{}",
+            encode(json!({"alg": "HS256", "typ": "JWT"})),
+            encode(json!({
+                "sub": ACCOUNT_ID,
+                "country": "US",
+                "benefits": [],
+                "exp": 4102444800u64
+            })),
+            encode(json!("mock-signature"))
+        )
+    }
+
+    /// Response of the `/index/v2` endpoint.
+    pub fn index() -> Value {
+        json!({
+            "cms_web": {
+                "bucket": "/US/M3/crunchyroll",
+                "expires": "2100-01-01T00:00:00Z",
+                "key_pair_id": "mock-key-pair-id",
+                "policy": "mock-policy",
+                "signature": "mock-signature"
+            },
+            "default_marketing_opt_in": false,
+            "service_available": true
+        })
+    }
+
+    /// Wraps `items` like most `/content/v2` endpoints do.
+    pub fn bulk(items: Vec<Value>) -> Value {
+        json!({
+            "total": items.len(),
+            "data": items,
+            "meta": {}
+        })
+    }
+
+    /// A [`crate::Series`].
+    pub fn series(id: &str, title: &str) -> Value {
+        json!({
+            "id": id,
+            "type": "series",
+            "title": title,
+            "slug_title": slug(title),
+            "series_metadata": {
+                "audio_locales": ["ja-JP"],
+                "subtitle_locales": ["en-US"],
+                "season_count": 1,
+                "episode_count": 1
+            }
+        })
+    }
+
+    /// A [`crate::Season`] of the series with id `series_id`.
+    pub fn season(id: &str, series_id: &str, title: &str) -> Value {
+        json!({
+            "id": id,
+            "series_id": series_id,
+            "title": title,
+            "slug_title": slug(title),
+            "season_number": 1,
+            "season_sequence_number": 1,
+            "number_of_episodes": 1,
+            "audio_locales": ["ja-JP"],
+            "subtitle_locales": ["en-US"]
+        })
+    }
+
+    /// A [`crate::Episode`] of the season with id `season_id`.
+    pub fn episode(id: &str, season_id: &str, series_id: &str, title: &str) -> Value {
+        json!({
+            "id": id,
+            "type": "episode",
+            "title": title,
+            "slug_title": slug(title),
+            "episode_metadata": {
+                "season_id": season_id,
+                "series_id": series_id,
+                "episode": "1",
+                "episode_number": 1,
+                "sequence_number": 1.0,
+                "audio_locale": "ja-JP",
+                "subtitle_locales": ["en-US"],
+                "duration_ms": 1440000
+            },
+            "streams_link": format!("/content/v2/cms/videos/{id}/streams")
+        })
+    }
+
+    /// Response of the play endpoint which [`crate::media::Stream::from_id`] requests. The stream
+    /// data is read from the DASH manifest at `manifest_url`, register it via
+    /// [`super::MockTransport::on_text`]. The stream token is `mock-stream-token`.
+    pub fn stream(manifest_url: &str) -> Value {
+        json!({
+            "url": manifest_url,
+            "audioLocale": "ja-JP",
+            "token": "mock-stream-token",
+            "session": {
+                "renewSeconds": 30,
+                "noNetworkRetryIntervalSeconds": 5,
+                "noNetworkTimeoutSeconds": 30,
+                "maximumPauseSeconds": 600,
+                "endOfVideoUnloadSeconds": 60,
+                "sessionExpirationSeconds": 600,
+                "usesStreamLimits": true
+            }
+        })
+    }
+
+    fn slug(title: &str) -> String {
+        title
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<&str>>()
+            .join("-")
+    }
+}
@@ -2,6 +2,9 @@
 /// Begins with an underscore because this must be the first file to be called
 mod utils;
 
+use chrono::{DateTime, Utc};
+use crunchyroll_rs::crunchyroll::{BasicAuthToken, Clock, Endpoints, RateLimiter};
+use crunchyroll_rs::pool::CrunchyrollPool;
 use crunchyroll_rs::Crunchyroll;
 use std::env;
 
@@ -103,3 +106,476 @@ async fn login_anonymously() {
         utils::session::set_session(crunchy.unwrap()).await.unwrap()
     }
 }
+
+#[test]
+fn basic_auth_token_from_client_credentials() {
+    assert_eq!(
+        BasicAuthToken::from_client_credentials("noaihdevm_6iyg0a8l0q", ""),
+        BasicAuthToken::WEB
+    )
+}
+
+#[tokio::test]
+async fn pool_login_anonymously() {
+    let pool = CrunchyrollPool::new().rate_limiter(RateLimiter::per_second(5));
+
+    let crunchy = pool.builder().login_anonymously().await;
+    assert_result!(crunchy);
+
+    pool.insert("anonymous", crunchy.unwrap()).await;
+    assert!(pool.get(&"anonymous").await.is_some())
+}
+
+#[tokio::test]
+async fn login_anonymously_expired_clock() {
+    // a clock which is always ahead of the session expiry, so every request refreshes the session
+    #[derive(Debug)]
+    struct FutureClock;
+
+    impl Clock for FutureClock {
+        fn now(&self) -> DateTime<Utc> {
+            Utc::now() + chrono::Duration::days(1)
+        }
+    }
+
+    let crunchy = Crunchyroll::builder()
+        .clock(FutureClock)
+        .login_anonymously()
+        .await
+        .unwrap();
+    let access_token = crunchy.access_token().await;
+
+    assert_result!(crunchy.categories().await);
+    assert_ne!(crunchy.access_token().await, access_token)
+}
+
+#[tokio::test]
+async fn login_anonymously_custom_endpoints() {
+    // nothing listens on this port, so the login must fail with a request to the overwritten host
+    let result = Crunchyroll::builder()
+        .endpoints(Endpoints::default().api("http://127.0.0.1:1"))
+        .login_anonymously()
+        .await;
+
+    let error = result.err().unwrap();
+    assert!(format!("{error:?}").contains("127.0.0.1"), "{error}")
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::crunchyroll::{
+        Clock, ExternalLoginProvider, RegisterOptions, SessionToken, TokenStore,
+    };
+    use crunchyroll_rs::error::{ApiErrorCode, Error};
+    use crunchyroll_rs::list::WatchlistOptions;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::{Crunchyroll, Locale, Series};
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn register() {
+        let transport = MockTransport::new().on("/accounts/v1/signup", serde_json::json!({}));
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .register(
+                "user@example.com",
+                "correct horse battery staple",
+                RegisterOptions::new().locale(Locale::de_DE),
+            )
+            .await
+            .unwrap();
+        assert!(!crunchy.access_token().await.is_empty());
+
+        let requests = transport.requests();
+        let signup = requests
+            .iter()
+            .position(|(_, url)| url.path() == "/accounts/v1/signup")
+            .unwrap();
+        // the account is logged in after it got created
+        assert!(requests[signup..]
+            .iter()
+            .any(|(_, url)| url.path() == "/auth/v1/token"))
+    }
+
+    #[tokio::test]
+    async fn register_email_exists() {
+        let transport = MockTransport::new().on_status(
+            "/accounts/v1/signup",
+            409,
+            serde_json::json!({ "code": "accounts.create_account.email_already_exists", "context": [] }),
+        );
+        let result = Crunchyroll::builder()
+            .with_transport(transport)
+            .register(
+                "user@example.com",
+                "correct horse battery staple",
+                RegisterOptions::new(),
+            )
+            .await;
+
+        let Err(Error::Request { code, .. }) = result else {
+            panic!("expected a request error")
+        };
+        assert_eq!(code, Some(ApiErrorCode::EmailAlreadyExists))
+    }
+
+    #[tokio::test]
+    async fn token_store() {
+        #[derive(Clone, Debug, Default)]
+        struct MemoryTokenStore(std::sync::Arc<std::sync::Mutex<Option<SessionToken>>>);
+
+        #[async_trait::async_trait]
+        impl TokenStore for MemoryTokenStore {
+            async fn get(&self) -> Result<Option<SessionToken>, Error> {
+                Ok(self.0.lock().unwrap().clone())
+            }
+
+            async fn set(&self, token: &SessionToken) -> Result<(), Error> {
+                *self.0.lock().unwrap() = Some(token.clone());
+                Ok(())
+            }
+        }
+
+        // clock which can be moved forward to make the session expire
+        #[derive(Clone, Debug, Default)]
+        struct OffsetClock(std::sync::Arc<std::sync::atomic::AtomicI64>);
+
+        impl Clock for OffsetClock {
+            fn now(&self) -> chrono::DateTime<chrono::Utc> {
+                let offset = self.0.load(std::sync::atomic::Ordering::SeqCst);
+                chrono::Utc::now() + chrono::Duration::days(offset)
+            }
+        }
+
+        let store = MemoryTokenStore::default();
+        let clock = OffsetClock::default();
+        let rotations = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let transport = MockTransport::new().on(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+        );
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .token_store(store.clone())
+            .clock(clock.clone())
+            .on_token_rotation({
+                let rotations = rotations.clone();
+                move |token| rotations.lock().unwrap().push(token.clone())
+            })
+            .login_with_credentials("user@example.com", "password")
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.get().await.unwrap(),
+            Some(SessionToken::RefreshToken(token)) if token == "mock-refresh-token"
+        ));
+
+        let mut rotated = fixtures::token();
+        rotated["refresh_token"] = "rotated-refresh-token".into();
+        transport.clone().on("/auth/v1/token", rotated);
+        clock.0.store(2, std::sync::atomic::Ordering::SeqCst);
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await.unwrap();
+        assert!(matches!(
+            store.get().await.unwrap(),
+            Some(SessionToken::RefreshToken(token)) if token == "rotated-refresh-token"
+        ));
+        assert!(matches!(
+            rotations.lock().unwrap().as_slice(),
+            [SessionToken::RefreshToken(token)] if token == "rotated-refresh-token"
+        ));
+
+        assert!(Crunchyroll::builder()
+            .with_transport(transport)
+            .token_store(store)
+            .login_with_token_store()
+            .await
+            .is_ok())
+    }
+
+    #[tokio::test]
+    async fn token_store_error() {
+        // store which fails once `fail` is set
+        #[derive(Clone, Debug, Default)]
+        struct FailingTokenStore {
+            fail: std::sync::Arc<std::sync::atomic::AtomicBool>,
+        }
+
+        #[async_trait::async_trait]
+        impl TokenStore for FailingTokenStore {
+            async fn get(&self) -> Result<Option<SessionToken>, Error> {
+                Ok(None)
+            }
+
+            async fn set(&self, _: &SessionToken) -> Result<(), Error> {
+                if self.fail.load(std::sync::atomic::Ordering::SeqCst) {
+                    return Err(Error::Internal {
+                        message: "storage is full".to_string(),
+                    });
+                }
+                Ok(())
+            }
+        }
+
+        #[derive(Clone, Debug, Default)]
+        struct OffsetClock(std::sync::Arc<std::sync::atomic::AtomicI64>);
+
+        impl Clock for OffsetClock {
+            fn now(&self) -> chrono::DateTime<chrono::Utc> {
+                let offset = self.0.load(std::sync::atomic::Ordering::SeqCst);
+                chrono::Utc::now() + chrono::Duration::days(offset)
+            }
+        }
+
+        let store = FailingTokenStore::default();
+        let clock = OffsetClock::default();
+        let errors = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let transport = MockTransport::new().on(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+        );
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .token_store(store.clone())
+            .clock(clock.clone())
+            .on_token_store_error({
+                let errors = errors.clone();
+                move |error| errors.lock().unwrap().push(error.to_string())
+            })
+            .login_with_credentials("user@example.com", "password")
+            .await
+            .unwrap();
+
+        store.fail.store(true, std::sync::atomic::Ordering::SeqCst);
+        clock.0.store(2, std::sync::atomic::Ordering::SeqCst);
+        // the session got refreshed, so the request succeeds even if the new token wasn't stored
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await.unwrap();
+        assert!(matches!(
+            errors.lock().unwrap().as_slice(),
+            [error] if error.contains("storage is full")
+        ));
+
+        // the refreshed session is used without refreshing it again
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await.unwrap();
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn login_with_tokens() {
+        let access_token = fixtures::token()["access_token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/series/GY8VEQ95Y",
+                fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+            )
+            .on(
+                format!("/content/v2/discover/{}/watchlist", fixtures::ACCOUNT_ID),
+                fixtures::bulk(vec![]),
+            );
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .login_with_tokens(
+                access_token.as_str(),
+                "external-refresh-token",
+                chrono::Utc::now() + chrono::Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(crunchy.access_token().await, access_token);
+        // the account id is taken from the access token
+        assert!(crunchy
+            .watchlist(WatchlistOptions::default())
+            .await
+            .unwrap()
+            .is_empty());
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await.unwrap();
+        assert!(!transport
+            .requests()
+            .iter()
+            .any(|(_, url)| url.path() == "/auth/v1/token"));
+
+        // an expired session is refreshed with the given refresh token
+        let expired = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .login_with_tokens(
+                access_token.as_str(),
+                "external-refresh-token",
+                chrono::Utc::now() - chrono::Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        expired.media_from_id::<Series>("GY8VEQ95Y").await.unwrap();
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(_, url)| url.path() == "/auth/v1/token"));
+
+        assert!(Crunchyroll::builder()
+            .with_transport(transport)
+            .login_with_tokens("invalid", "external-refresh-token", chrono::Utc::now())
+            .await
+            .is_err())
+    }
+
+    #[tokio::test]
+    async fn device_code_login() {
+        let transport = MockTransport::new()
+            .on(
+                "/auth/v1/device/code",
+                serde_json::json!({
+                    "device_code": "device-code",
+                    "user_code": "ABC123",
+                    "expires_in": 300,
+                    "interval": 1
+                }),
+            )
+            .on("/auth/v1/device/token", fixtures::token());
+        let login = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .login_with_device_code()
+            .await
+            .unwrap();
+        assert_eq!(login.user_code, "ABC123");
+        assert_eq!(login.interval, Duration::from_secs(1));
+
+        login.wait().await.unwrap();
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(_, url)| url.path() == "/auth/v1/device/token"))
+    }
+
+    #[tokio::test]
+    async fn device_code_expired() {
+        let transport = MockTransport::new()
+            .on(
+                "/auth/v1/device/code",
+                serde_json::json!({
+                    "device_code": "device-code",
+                    "user_code": "ABC123",
+                    "expires_in": 0
+                }),
+            )
+            .on_status(
+                "/auth/v1/device/token",
+                400,
+                serde_json::json!({ "error": "authorization_pending" }),
+            );
+        let login = Crunchyroll::builder()
+            .with_transport(transport)
+            .login_with_device_code()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            login.wait().await,
+            Err(Error::Authentication { .. })
+        ))
+    }
+
+    async fn device_code_wait(
+        transport: MockTransport,
+        expires_in: u32,
+    ) -> Result<Crunchyroll, Error> {
+        let transport = transport.on(
+            "/auth/v1/device/code",
+            serde_json::json!({
+                "device_code": "device-code",
+                "user_code": "ABC123",
+                "expires_in": expires_in,
+                "interval": 0
+            }),
+        );
+        Crunchyroll::builder()
+            .with_transport(transport)
+            .login_with_device_code()
+            .await
+            .unwrap()
+            .wait()
+            .await
+    }
+
+    #[tokio::test]
+    async fn device_code_slow_down() {
+        let transport = MockTransport::new().on_status(
+            "/auth/v1/device/token",
+            400,
+            serde_json::json!({ "error": "slow_down" }),
+        );
+
+        // the increased interval exceeds the expiry, so no second request is made
+        assert!(matches!(
+            device_code_wait(transport.clone(), 3).await,
+            Err(Error::Authentication { .. })
+        ));
+        assert_eq!(mock::requests_to(&transport, "/auth/v1/device/token"), 1)
+    }
+
+    #[tokio::test]
+    async fn device_code_denied() {
+        for code in ["expired_token", "access_denied"] {
+            let transport = MockTransport::new().on_status(
+                "/auth/v1/device/token",
+                400,
+                serde_json::json!({ "error": code }),
+            );
+
+            assert!(matches!(
+                device_code_wait(transport.clone(), 300).await,
+                Err(Error::Authentication { .. })
+            ));
+            assert_eq!(mock::requests_to(&transport, "/auth/v1/device/token"), 1)
+        }
+    }
+
+    #[tokio::test]
+    async fn device_code_unauthorized() {
+        let transport = MockTransport::new().on_status(
+            "/auth/v1/device/token",
+            401,
+            serde_json::json!({ "error": "invalid_client" }),
+        );
+
+        // errors which don't mean that the code is still pending aren't polled until expiry
+        assert!(matches!(
+            device_code_wait(transport.clone(), 300).await,
+            Err(Error::Request {
+                status: Some(StatusCode::UNAUTHORIZED),
+                ..
+            })
+        ));
+        assert_eq!(mock::requests_to(&transport, "/auth/v1/device/token"), 1)
+    }
+
+    #[tokio::test]
+    async fn external_token_login() {
+        assert!(Crunchyroll::builder()
+            .with_transport(MockTransport::new())
+            .login_with_external_token(ExternalLoginProvider::Google, "id-token")
+            .await
+            .is_ok());
+
+        let transport = MockTransport::new().on_status(
+            "/auth/v1/token",
+            400,
+            serde_json::json!({
+                "code": "auth.obtain_access_token.external_account_not_linked",
+                "context": []
+            }),
+        );
+        let result = Crunchyroll::builder()
+            .with_transport(transport)
+            .login_with_external_token(ExternalLoginProvider::Apple, "id-token")
+            .await;
+
+        let Err(Error::Request { code, .. }) = result else {
+            panic!("expected a request error")
+        };
+        assert_eq!(code, Some(ApiErrorCode::ExternalAccountNotLinked))
+    }
+}
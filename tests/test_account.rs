@@ -2,6 +2,7 @@ use crate::utils::{Store, SESSION};
 use crunchyroll_rs::account::{Account, UpdatePreferences, Wallpaper};
 use crunchyroll_rs::crunchyroll::MaturityRating;
 use crunchyroll_rs::Locale;
+use std::time::Duration;
 
 mod utils;
 
@@ -18,6 +19,21 @@ async fn account() {
     assert_result!(ACCOUNT.get().await)
 }
 
+#[tokio::test]
+async fn health_check() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let health_check = crunchy.health_check(Duration::from_secs(30)).await;
+    assert!(health_check.authenticated, "{:?}", health_check.error)
+}
+
+#[tokio::test]
+async fn region() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    assert!(crunchy.region().await.is_some_and(|r| r.len() == 2))
+}
+
 #[tokio::test]
 async fn account_update_preferences() {
     let mut account = ACCOUNT.get().await.unwrap().clone();
@@ -45,9 +61,106 @@ async fn account_update_preferences() {
     assert_result!(account.update_preferences(old_preferences.clone()).await)
 }
 
+#[tokio::test]
+async fn account_set_languages() {
+    let mut account = ACCOUNT.get().await.unwrap().clone();
+    let audio_language = account.preferred_audio_language.clone();
+    let subtitle_language = account.preferred_subtitle_language.clone();
+
+    assert_result!(account.set_audio_language(audio_language.clone()).await);
+    assert_result!(
+        account
+            .set_subtitle_language(subtitle_language.clone())
+            .await
+    )
+}
+
 #[tokio::test]
 async fn all_wallpapers() {
     let crunchy = SESSION.get().await.unwrap();
 
     assert_result!(Wallpaper::all_wallpapers(crunchy).await)
 }
+
+#[tokio::test]
+async fn subscription() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    assert_result!(crunchy.subscription().await)
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::account::{EmailVerification, UpdatePreferences};
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use reqwest::Method;
+
+    #[tokio::test]
+    async fn credential_lifecycle() {
+        let transport = MockTransport::new()
+            .on("/accounts/v1/reset_password", serde_json::json!({}))
+            .on("/accounts/v1/me/verify_email", serde_json::json!({}))
+            .on(
+                "/accounts/v1/me",
+                serde_json::json!({ "account_id": fixtures::ACCOUNT_ID, "email_verified": false }),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        crunchy
+            .request_password_reset("user@example.com")
+            .await
+            .unwrap();
+        assert_eq!(
+            crunchy.resend_verification_email().await.unwrap(),
+            EmailVerification::Sent
+        );
+
+        let transport = transport.on(
+            "/accounts/v1/me",
+            serde_json::json!({ "account_id": fixtures::ACCOUNT_ID, "email_verified": true }),
+        );
+        assert_eq!(
+            crunchy.resend_verification_email().await.unwrap(),
+            EmailVerification::AlreadyVerified
+        );
+        assert_eq!(
+            mock::requests_to(&transport, "/accounts/v1/me/verify_email"),
+            1
+        )
+    }
+
+    #[tokio::test]
+    async fn update_notification_settings() {
+        let transport = MockTransport::new()
+            .on(
+                "/accounts/v1/me",
+                serde_json::json!({ "account_id": fixtures::ACCOUNT_ID, "opt_out_newsletters": false }),
+            )
+            .on("/accounts/v1/me/profile", serde_json::json!({}))
+            .on("/accounts/v1/me/notification_settings", serde_json::json!({}));
+        let crunchy = mock::login(&transport).await;
+
+        let mut account = crunchy.account().await.unwrap();
+        assert!(!account.email_newsletter);
+
+        account
+            .update_preferences(UpdatePreferences::default().email_newsletter(true))
+            .await
+            .unwrap();
+        assert!(account.email_newsletter);
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(method, url)| method == Method::PATCH
+                && url.path() == "/accounts/v1/me/notification_settings"));
+        assert!(
+            !transport
+                .requests()
+                .iter()
+                .any(|(method, url)| method == Method::PATCH
+                    && url.path() == "/accounts/v1/me/profile")
+        )
+    }
+}
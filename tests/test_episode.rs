@@ -1,6 +1,9 @@
 use crate::utils::Store;
 use crate::utils::SESSION;
+use chrono::TimeDelta;
+use crunchyroll_rs::media::PlayheadSync;
 use crunchyroll_rs::Episode;
+use futures_util::StreamExt;
 
 mod utils;
 
@@ -19,6 +22,26 @@ static END_EPISODE: Store<Episode> = Store::new(|| {
     })
 });
 
+/// Second episode of the season of [`START_EPISODE`]. Its playhead is modified by
+/// [`episode_mark_watched_unwatched`], so no other test may use it, as the tests run in parallel.
+static WATCHED_EPISODE: Store<Episode> = Store::new(|| {
+    Box::pin(async {
+        let start_episode = START_EPISODE.get().await?;
+        let episodes = start_episode.season().await?.episodes().await?;
+        Ok(episodes[1].clone())
+    })
+});
+
+/// Third episode of the season of [`START_EPISODE`]. Its playhead is modified by
+/// [`episode_sync_playhead`], so no other test may use it.
+static SYNC_EPISODE: Store<Episode> = Store::new(|| {
+    Box::pin(async {
+        let start_episode = START_EPISODE.get().await?;
+        let episodes = start_episode.season().await?.episodes().await?;
+        Ok(episodes[2].clone())
+    })
+});
+
 #[tokio::test]
 async fn episode_from_id() {
     assert_result!(START_EPISODE.get().await)
@@ -32,6 +55,25 @@ async fn episode_stream() {
     stream.invalidate().await.unwrap()
 }
 
+#[tokio::test]
+async fn episode_subtitles() {
+    let episode = START_EPISODE.get().await.unwrap();
+
+    let subtitles = episode.subtitles().await.unwrap();
+    assert!(subtitles
+        .iter()
+        .all(|(locale, subtitle)| &subtitle.locale == locale))
+}
+
+#[tokio::test]
+async fn episode_presence_metadata() {
+    let episode = START_EPISODE.get().await.unwrap();
+    let presence = episode.presence_metadata();
+
+    assert_eq!(presence.title, episode.title);
+    assert!(presence.url.contains(&episode.id))
+}
+
 #[tokio::test]
 async fn episode_get_playhead() {
     let episode = START_EPISODE.get().await.unwrap();
@@ -46,6 +88,55 @@ async fn episode_set_playhead() {
     assert_result!(episode.set_playhead(69).await)
 }
 
+#[tokio::test]
+async fn episode_mark_watched_unwatched() {
+    let episode = WATCHED_EPISODE.get().await.unwrap();
+
+    assert_result!(episode.mark_watched().await);
+    let playhead = episode.playhead().await.unwrap().unwrap();
+    assert!(playhead.fully_watched);
+
+    assert_result!(episode.mark_unwatched().await);
+    assert!(episode.playhead().await.unwrap().is_none())
+}
+
+#[tokio::test]
+async fn episode_sync_playhead() {
+    let episode = SYNC_EPISODE.get().await.unwrap();
+
+    // make sure that a remote playhead exists and get the time it was updated
+    episode.set_playhead(42).await.unwrap();
+    let remote = episode.playhead().await.unwrap().unwrap();
+
+    // a local playhead which is newer than the remote one is pushed
+    let local = episode
+        .sync_playhead(
+            69,
+            remote.last_modified + TimeDelta::try_minutes(1).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(local, PlayheadSync::Local));
+
+    // a local playhead which is older than the remote one is discarded
+    let remote = episode.playhead().await.unwrap().unwrap();
+    let synced = episode
+        .sync_playhead(
+            42,
+            remote.last_modified - TimeDelta::try_minutes(1).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(synced, PlayheadSync::Remote(p) if p.playhead == 69))
+}
+
+#[tokio::test]
+async fn episode_similar() {
+    let episode = START_EPISODE.get().await.unwrap();
+
+    assert_result!(episode.similar().next().await.unwrap())
+}
+
 #[tokio::test]
 async fn episode_some_previous() {
     let episode = END_EPISODE.get().await.unwrap();
@@ -79,3 +170,204 @@ async fn episode_skip_events() {
     let episode = START_EPISODE.get().await.unwrap();
     episode.skip_events().await.unwrap();
 }
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use chrono::{DateTime, Utc};
+    use crunchyroll_rs::common::Image;
+    use crunchyroll_rs::crunchyroll::Clock;
+    use crunchyroll_rs::media::WatchProgressTracker;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::{Crunchyroll, Episode};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[cfg(feature = "legacy-cms")]
+    #[tokio::test]
+    async fn legacy_episode() {
+        let legacy: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/legacy_episode.json")).unwrap();
+        let transport =
+            MockTransport::new().on("/cms/v2/US/M3/crunchyroll/episodes/GRDQPM1ZY", legacy);
+        let crunchy = mock::login(&transport).await;
+
+        let episode = crunchy.legacy_episode("GRDQPM1ZY").await.unwrap();
+        assert_eq!(episode.id, "GRDQPM1ZY");
+        assert_eq!(episode.title, "Alone and Lonesome");
+        assert_eq!(episode.season_id, "GYE5CQNJ2");
+        assert_eq!(episode.series_title, "DARLING in the FRANXX");
+        assert_eq!(episode.sequence_number, 1.0);
+        assert_eq!(episode.duration, chrono::Duration::milliseconds(1450000));
+        // the legacy cms has the stream link in '__links__'
+        assert_eq!(episode.stream_id, "GRDQPM1ZY");
+        assert_eq!(episode.images.len(), 2);
+        assert_eq!(episode.versions.len(), 1);
+        assert!(episode.versions[0].original)
+    }
+
+    #[tokio::test]
+    async fn image_data() {
+        let transport = MockTransport::new().on_text("/thumbnail.jpg", "image/jpeg", "jpeg data");
+        let crunchy = mock::login(&transport).await;
+
+        let image = Image {
+            source: "https://imgsrv.crunchyroll.com/thumbnail.jpg".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(image.data(&crunchy).await.unwrap(), b"jpeg data");
+
+        // the image cdn doesn't need the session token
+        let requests = transport.requests_with_headers();
+        let (_, _, headers) = requests
+            .iter()
+            .find(|(_, url, _)| url.path() == "/thumbnail.jpg")
+            .unwrap();
+        assert!(!headers.contains_key(reqwest::header::AUTHORIZATION))
+    }
+
+    #[tokio::test]
+    async fn watch_progress_tracker() {
+        let playheads_path = format!("/content/v2/{}/playheads", fixtures::ACCOUNT_ID);
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/episodes/GRDKJZ81Y",
+                fixtures::bulk(vec![fixtures::episode(
+                    "GRDKJZ81Y",
+                    "GR49C7EPD",
+                    "GY8VEQ95Y",
+                    "Alone and Lonesome",
+                )]),
+            )
+            .on(&playheads_path, serde_json::json!({}));
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+        let playhead_updates = || mock::requests_to(&transport, &playheads_path);
+
+        let mut tracker = WatchProgressTracker::new(&episode).interval(Duration::from_secs(3600));
+        tracker.position(10).await;
+        tracker.position(20).await;
+        assert_eq!(playhead_updates(), 1);
+        assert_eq!(tracker.reported_position(), Some(10));
+
+        tracker.pause(25).await;
+        assert_eq!(playhead_updates(), 2);
+        assert!(!tracker.completed());
+
+        // the episode fixture is 1440 seconds long
+        tracker.position(1400).await;
+        assert_eq!(playhead_updates(), 3);
+        assert!(tracker.completed());
+
+        tracker.finish().await;
+        assert_eq!(playhead_updates(), 3);
+        assert!(tracker.last_error().is_none())
+    }
+
+    #[tokio::test]
+    async fn watch_progress_tracker_error() {
+        let playheads_path = format!("/content/v2/{}/playheads", fixtures::ACCOUNT_ID);
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/episodes/GRDKJZ81Y",
+                fixtures::bulk(vec![fixtures::episode(
+                    "GRDKJZ81Y",
+                    "GR49C7EPD",
+                    "GY8VEQ95Y",
+                    "Alone and Lonesome",
+                )]),
+            )
+            .on_status(
+                &playheads_path,
+                500,
+                serde_json::json!({ "error": "internal error" }),
+            );
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+
+        let mut tracker = WatchProgressTracker::new(&episode)
+            .retries(1)
+            .retry_delay(Duration::from_millis(1));
+        tracker.position(10).await;
+        assert!(tracker.last_error().is_some());
+        assert_eq!(tracker.reported_position(), None);
+        assert_eq!(mock::requests_to(&transport, &playheads_path), 2)
+    }
+
+    #[tokio::test]
+    async fn watch_progress_tracker_no_retry() {
+        let playheads_path = format!("/content/v2/{}/playheads", fixtures::ACCOUNT_ID);
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/episodes/GRDKJZ81Y",
+                fixtures::bulk(vec![fixtures::episode(
+                    "GRDKJZ81Y",
+                    "GR49C7EPD",
+                    "GY8VEQ95Y",
+                    "Alone and Lonesome",
+                )]),
+            )
+            .on_status(
+                &playheads_path,
+                401,
+                serde_json::json!({ "code": "unauthorized" }),
+            );
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+
+        // an error which won't go away by sending the update again isn't retried
+        let mut tracker = WatchProgressTracker::new(&episode)
+            .retries(2)
+            .retry_delay(Duration::from_millis(1));
+        tracker.position(10).await;
+        assert!(tracker.last_error().is_some());
+        assert_eq!(mock::requests_to(&transport, &playheads_path), 1)
+    }
+
+    #[tokio::test]
+    async fn watch_progress_tracker_interval() {
+        #[derive(Clone, Debug)]
+        struct SharedClock(Arc<Mutex<DateTime<Utc>>>);
+
+        impl Clock for SharedClock {
+            fn now(&self) -> DateTime<Utc> {
+                *self.0.lock().unwrap()
+            }
+        }
+
+        let playheads_path = format!("/content/v2/{}/playheads", fixtures::ACCOUNT_ID);
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/episodes/GRDKJZ81Y",
+                fixtures::bulk(vec![fixtures::episode(
+                    "GRDKJZ81Y",
+                    "GR49C7EPD",
+                    "GY8VEQ95Y",
+                    "Alone and Lonesome",
+                )]),
+            )
+            .on(&playheads_path, serde_json::json!({}));
+        let now = Arc::new(Mutex::new(Utc::now()));
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .clock(SharedClock(now.clone()))
+            .login_with_credentials("user@example.com", "password")
+            .await
+            .unwrap();
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+        let playhead_updates = || mock::requests_to(&transport, &playheads_path);
+        let advance = |secs: i64| *now.lock().unwrap() += chrono::Duration::seconds(secs);
+
+        let mut tracker = WatchProgressTracker::new(&episode).interval(Duration::from_secs(30));
+        tracker.position(10).await;
+        advance(29);
+        tracker.position(39).await;
+        assert_eq!(playhead_updates(), 1);
+
+        advance(1);
+        tracker.position(40).await;
+        assert_eq!(playhead_updates(), 2);
+        assert_eq!(tracker.reported_position(), Some(40))
+    }
+}
@@ -0,0 +1,342 @@
+// the strict feature requires every field to be present, which the fixtures don't contain
+#![cfg(all(feature = "testing", not(feature = "__test_strict")))]
+
+use crate::utils::mock;
+use crunchyroll_rs::crunchyroll::{BlockMitigation, Clock, RateLimitRetry};
+use crunchyroll_rs::error::{ApiErrorCode, Error};
+use crunchyroll_rs::media::{Stream, StreamPlatform};
+use crunchyroll_rs::testing::{fixtures, MockTransport};
+use crunchyroll_rs::{Crunchyroll, Series};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+mod utils;
+
+#[tokio::test]
+async fn error_status() {
+    let transport = MockTransport::new().on_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        500,
+        serde_json::json!({ "error": "internal error" }),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+
+    assert!(crunchy.media_from_id::<Series>("GY8VEQ95Y").await.is_err());
+    assert!(crunchy
+        .media_from_id::<Series>("unregistered")
+        .await
+        .is_err())
+}
+
+#[tokio::test]
+async fn geo_restricted() {
+    let transport = MockTransport::new().on_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        403,
+        serde_json::json!({
+            "code": "content.geo_restricted",
+            "context": [{ "code": "geo", "available_in": ["US", "CA"] }]
+        }),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+
+    assert_eq!(crunchy.region().await, Some("US".to_string()));
+    assert!(!crunchy.premium().await);
+
+    let Err(Error::GeoRestricted {
+        status,
+        available_in,
+        ..
+    }) = crunchy.media_from_id::<Series>("GY8VEQ95Y").await
+    else {
+        panic!("expected a geo restriction error")
+    };
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(available_in, vec!["US".to_string(), "CA".to_string()])
+}
+
+#[tokio::test]
+async fn geo_restriction_code_only() {
+    // error codes which only sound like a geo restriction aren't converted
+    let transport = MockTransport::new().on_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        403,
+        serde_json::json!({ "code": "account.region_mismatch", "context": [] }),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+
+    assert!(matches!(
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await,
+        Err(Error::Request {
+            status: Some(StatusCode::FORBIDDEN),
+            ..
+        })
+    ))
+}
+
+#[tokio::test]
+async fn api_error_code() {
+    let transport = MockTransport::new().on_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        403,
+        serde_json::json!({ "code": "subscription.required", "context": [] }),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+
+    let Err(Error::Request { code, .. }) = crunchy.media_from_id::<Series>("GY8VEQ95Y").await
+    else {
+        panic!("expected a request error")
+    };
+    assert_eq!(code, Some(ApiErrorCode::SubscriptionRequired))
+}
+
+#[tokio::test]
+async fn rate_limited() {
+    let transport = MockTransport::new().on_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        429,
+        serde_json::json!({}),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+    assert!(matches!(
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await,
+        Err(Error::RateLimited {
+            retry_after: None,
+            ..
+        })
+    ));
+
+    let retrying = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .rate_limit_retry(
+            RateLimitRetry::default()
+                .max_retries(2)
+                .delay(Duration::from_millis(1)),
+        )
+        .login_anonymously()
+        .await
+        .unwrap();
+    let requests_before = transport.requests().len();
+    assert!(matches!(
+        retrying.media_from_id::<Series>("GY8VEQ95Y").await,
+        Err(Error::RateLimited { .. })
+    ));
+    let series_requests = transport.requests()[requests_before..]
+        .iter()
+        .filter(|(_, url)| url.path() == "/content/v2/cms/series/GY8VEQ95Y")
+        .count();
+    assert_eq!(series_requests, 3)
+}
+
+#[tokio::test]
+async fn rate_limited_raw_request() {
+    #[derive(Clone, Debug)]
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now(&self) -> chrono::DateTime<chrono::Utc> {
+            "2024-11-20T00:00:00Z".parse().unwrap()
+        }
+    }
+
+    let transport = MockTransport::new()
+        .header("Retry-After", "Wed, 20 Nov 2024 00:00:30 GMT")
+        .on(
+            "/v1/GRDKJZ81Y/web/chrome/play",
+            fixtures::stream("https://example.com/manifest.mpd"),
+        )
+        .on_status("/manifest.mpd", 429, serde_json::json!({}));
+    let crunchy = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .clock(FixedClock)
+        .login_anonymously()
+        .await
+        .unwrap();
+    let stream = Stream::from_id(&crunchy, "GRDKJZ81Y", StreamPlatform::WebChrome, None)
+        .await
+        .unwrap();
+
+    // the http date of the header is relative to the clock of the client
+    assert!(matches!(
+        stream.stream_data(None).await,
+        Err(Error::RateLimited {
+            retry_after: Some(retry_after),
+            ..
+        }) if retry_after == Duration::from_secs(30)
+    ));
+}
+
+#[tokio::test]
+async fn rate_limit_retry_raw_request() {
+    let transport = MockTransport::new()
+        .on(
+            "/v1/GRDKJZ81Y/web/chrome/play",
+            fixtures::stream("https://example.com/manifest.mpd"),
+        )
+        .on_status("/manifest.mpd", 429, serde_json::json!({}));
+    let crunchy = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .rate_limit_retry(
+            RateLimitRetry::default()
+                .max_retries(2)
+                .delay(Duration::from_millis(1)),
+        )
+        .login_anonymously()
+        .await
+        .unwrap();
+    let stream = Stream::from_id(&crunchy, "GRDKJZ81Y", StreamPlatform::WebChrome, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        stream.stream_data(None).await,
+        Err(Error::RateLimited { .. })
+    ));
+    assert_eq!(mock::requests_to(&transport, "/manifest.mpd"), 3)
+}
+
+const BLOCK_PAGE: &str =
+    "<!DOCTYPE html><html><head><title>Just a moment...</title></head><body></body></html>";
+
+#[tokio::test]
+async fn block_mitigation_user_agent() {
+    let transport = MockTransport::new().on_text_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        403,
+        "text/html",
+        BLOCK_PAGE,
+    );
+    let crunchy = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .block_mitigation(
+            BlockMitigation::default()
+                .user_agents(vec!["rotated-agent".to_string()])
+                .max_retries(1)
+                .retry_delay(Duration::from_millis(1))
+                .jitter(Duration::ZERO),
+        )
+        .login_anonymously()
+        .await
+        .unwrap();
+    let requests_before = transport.requests().len();
+
+    assert!(matches!(
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await,
+        Err(Error::Block { .. })
+    ));
+    // the cookie refresh and the retry are sent with the rotated user agent
+    let requests: Vec<(String, Option<String>)> = transport.requests_with_headers()
+        [requests_before..]
+        .iter()
+        .map(|(_, url, headers)| {
+            (
+                url.path().to_string(),
+                headers
+                    .get(reqwest::header::USER_AGENT)
+                    .map(|user_agent| user_agent.to_str().unwrap().to_string()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        requests[1..],
+        [
+            ("/".to_string(), Some("rotated-agent".to_string())),
+            (
+                "/content/v2/cms/series/GY8VEQ95Y".to_string(),
+                Some("rotated-agent".to_string())
+            )
+        ]
+    )
+}
+
+#[tokio::test]
+async fn block_mitigation_refresh_failed() {
+    let transport = MockTransport::new().on_text_status(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        403,
+        "text/html",
+        BLOCK_PAGE,
+    );
+    let crunchy = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .block_mitigation(
+            BlockMitigation::default()
+                .max_retries(2)
+                .retry_delay(Duration::from_millis(1))
+                .jitter(Duration::ZERO),
+        )
+        .login_anonymously()
+        .await
+        .unwrap();
+    let transport = transport.on_status("/", 500, serde_json::json!({}));
+
+    // the block is returned instead of the error of the refresh, without retrying
+    assert!(matches!(
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await,
+        Err(Error::Block { .. })
+    ));
+    assert_eq!(
+        mock::requests_to(&transport, "/content/v2/cms/series/GY8VEQ95Y"),
+        1
+    )
+}
+
+#[tokio::test]
+async fn request_id() {
+    let transport = MockTransport::new()
+        .header("x-request-id", "8f0c2a4e-request")
+        .on_status(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            500,
+            serde_json::json!({ "error": "internal error" }),
+        );
+    let crunchy = mock::anonymous(&transport).await;
+
+    let Err(err @ Error::Request { .. }) = crunchy.media_from_id::<Series>("GY8VEQ95Y").await
+    else {
+        panic!("expected a request error")
+    };
+    let Error::Request { request_id, .. } = &err else {
+        unreachable!()
+    };
+    assert_eq!(request_id.as_deref(), Some("8f0c2a4e-request"));
+    assert!(err.to_string().contains("8f0c2a4e-request"))
+}
+
+#[tokio::test]
+async fn request_id_rate_limited() {
+    let transport = MockTransport::new()
+        .header("x-request-id", "8f0c2a4e-request")
+        .on_status(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            429,
+            serde_json::json!({}),
+        );
+    let crunchy = mock::anonymous(&transport).await;
+
+    let Err(Error::RateLimited { request_id, .. }) =
+        crunchy.media_from_id::<Series>("GY8VEQ95Y").await
+    else {
+        panic!("expected a rate limit error")
+    };
+    assert_eq!(request_id.as_deref(), Some("8f0c2a4e-request"))
+}
+
+#[tokio::test]
+async fn request_id_cdn_headers() {
+    let transport = MockTransport::new()
+        .header("cf-ray", "8f0c2a4e1b2c3d4e-FRA")
+        .on_status(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            500,
+            serde_json::json!({ "error": "internal error" }),
+        );
+    let crunchy = mock::anonymous(&transport).await;
+
+    // the id of the cdn edge server isn't the id of the request
+    let Err(Error::Request { request_id, .. }) = crunchy.media_from_id::<Series>("GY8VEQ95Y").await
+    else {
+        panic!("expected a request error")
+    };
+    assert_eq!(request_id, None)
+}
@@ -31,6 +31,24 @@ async fn news_feed() {
         .unwrap())
 }
 
+#[tokio::test]
+async fn news_feed_article() {
+    let news = SESSION
+        .get()
+        .await
+        .unwrap()
+        .news_feed()
+        .latest_news
+        .next()
+        .await
+        .unwrap()
+        .unwrap();
+
+    let article = news.article().await.unwrap();
+    assert!(!article.title.is_empty());
+    assert!(!article.paragraphs.is_empty())
+}
+
 #[tokio::test]
 async fn recommendations() {
     assert_result!(SESSION
@@ -42,3 +60,126 @@ async fn recommendations() {
         .await
         .unwrap())
 }
+
+#[tokio::test]
+async fn music_landing() {
+    assert_result!(SESSION
+        .get()
+        .await
+        .unwrap()
+        .music_landing()
+        .next()
+        .await
+        .unwrap())
+}
+
+#[tokio::test]
+async fn music_landing_hydrate() {
+    let mut music_landing = SESSION.get().await.unwrap().music_landing();
+
+    while let Some(feed) = music_landing.next().await {
+        match feed.unwrap() {
+            HomeFeed::MusicVideoFeed(music_video_feed) => {
+                assert_result!(music_video_feed.music_videos().await);
+                break;
+            }
+            HomeFeed::ConcertFeed(concert_feed) => {
+                assert_result!(concert_feed.concerts().await);
+                break;
+            }
+            _ => continue,
+        }
+    }
+}
+
+#[tokio::test]
+async fn up_next() {
+    // the account might have nothing to continue watching
+    if let Some(result) = SESSION.get().await.unwrap().up_next().next().await {
+        assert_result!(result)
+    }
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::feed::HomeFeed;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use futures_util::{Stream, StreamExt, TryStreamExt};
+
+    #[tokio::test]
+    async fn game_vault() {
+        let transport = MockTransport::new()
+            .on(
+                format!("/content/v2/discover/{}/home_feed", fixtures::ACCOUNT_ID),
+                fixtures::bulk(vec![
+                    serde_json::json!({
+                        "resource_type": "curated_collection",
+                        "response_type": "game_vault",
+                        "title": "Game Vault",
+                        "description": "Games included in your membership",
+                        "ids": ["G1"]
+                    }),
+                    serde_json::json!({
+                        "resource_type": "game",
+                        "game": { "title": "Crunchyroll Game", "link": "https://example.com/g1" }
+                    }),
+                ]),
+            )
+            .on(
+                "/content/v2/discover/games",
+                fixtures::bulk(vec![
+                    serde_json::json!({ "id": "G1", "title": "Crunchyroll Game", "link": "https://example.com/g1" }),
+                ]),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        let feed: Vec<HomeFeed> = crunchy.home_feed().try_collect().await.unwrap();
+        let [HomeFeed::GameVault(game_vault), HomeFeed::Game(game)] = feed.as_slice() else {
+            panic!("expected a game vault and a game feed")
+        };
+        assert_eq!(game_vault.ids, vec!["G1".to_string()]);
+        assert_eq!(game.title, "Crunchyroll Game");
+
+        let games = crunchy.games().await.unwrap();
+        assert_eq!(games[0].id, game_vault.ids[0]);
+        assert_eq!(games[0].link, "https://example.com/g1")
+    }
+
+    #[tokio::test]
+    async fn pagination_stream() {
+        let transport = MockTransport::new().on(
+            format!("/content/v2/discover/{}/home_feed", fixtures::ACCOUNT_ID),
+            fixtures::bulk(vec![
+                serde_json::json!({ "resource_type": "dynamic_collection", "response_type": "history" }),
+                serde_json::json!({ "resource_type": "dynamic_collection", "response_type": "watchlist" }),
+            ]),
+        );
+        let crunchy = mock::login(&transport).await;
+
+        let mut home_feed = crunchy.home_feed();
+        assert_eq!(home_feed.size_hint(), (0, None));
+        assert!(matches!(
+            home_feed.next().await,
+            Some(Ok(HomeFeed::History))
+        ));
+        assert_eq!(home_feed.size_hint(), (1, Some(1)));
+
+        let rest: Vec<HomeFeed> = home_feed.try_collect().await.unwrap();
+        assert!(matches!(rest.as_slice(), [HomeFeed::Watchlist]))
+    }
+
+    #[tokio::test]
+    async fn pagination_total_unknown() {
+        // no home feed response is registered, so the first page request fails
+        let transport = MockTransport::new();
+        let crunchy = mock::login(&transport).await;
+
+        assert_eq!(crunchy.home_feed().total().await, None);
+
+        let mut home_feed = crunchy.home_feed();
+        home_feed.cancellation().cancel();
+        assert_eq!(home_feed.total().await, None)
+    }
+}
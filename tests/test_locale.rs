@@ -0,0 +1,74 @@
+use crunchyroll_rs::Locale;
+
+#[test]
+fn locale_from_language_tag() {
+    assert_eq!(Locale::from_language_tag("en-US"), Some(Locale::en_US));
+    assert_eq!(
+        Locale::from_language_tag("de_DE.UTF-8"),
+        Some(Locale::de_DE)
+    );
+    assert_eq!(Locale::from_language_tag("es-419"), Some(Locale::es_419));
+    assert_eq!(Locale::from_language_tag("zh-HK"), Some(Locale::zh_HK));
+    assert_eq!(Locale::from_language_tag("en-GB"), None);
+    assert_eq!(Locale::from_language_tag("en"), None);
+}
+
+#[test]
+fn locale_closest() {
+    assert_eq!(Locale::closest("en-GB"), Some(Locale::en_US));
+    assert_eq!(Locale::closest("es-MX"), Some(Locale::es_419));
+    assert_eq!(Locale::closest("es"), Some(Locale::es_ES));
+    assert_eq!(Locale::closest("pt"), Some(Locale::pt_BR));
+    assert_eq!(Locale::closest("zh-Hant"), Some(Locale::zh_TW));
+    assert_eq!(Locale::closest("fr-CA"), Some(Locale::fr_FR));
+    assert_eq!(Locale::closest("de-AT-u-co-phonebk"), Some(Locale::de_DE));
+    assert_eq!(Locale::closest("xx"), None);
+}
+
+// the strict feature requires every field to be present, which would bloat the test json
+#[cfg(not(feature = "__test_strict"))]
+#[test]
+fn locale_sentinels_normalized() {
+    use crunchyroll_rs::media::Stream;
+    use crunchyroll_rs::{Episode, Season};
+
+    let stream: Stream = serde_json::from_value(serde_json::json!({
+        "audioLocale": ":",
+        "burnedInLocale": "",
+        "hardSubs": {
+            "": { "url": "https://example.com/empty" },
+            "de-DE": { "url": "https://example.com/de" }
+        },
+        "subtitles": {
+            ":": { "language": ":", "url": "", "format": "ass" }
+        }
+    }))
+    .unwrap();
+    assert!(stream.audio_locale.is_unknown());
+    assert_eq!(stream.burned_in_locale, None);
+    assert_eq!(
+        stream.hard_subs.keys().collect::<Vec<_>>(),
+        vec![&Locale::de_DE]
+    );
+    assert!(stream.subtitles.is_empty());
+
+    let season: Season = serde_json::from_value(serde_json::json!({
+        "audio_locale": " ",
+        "audio_locales": ["ja-JP", ":", ""],
+        "subtitle_locales": null
+    }))
+    .unwrap();
+    assert_eq!(season.audio_locales, vec![Locale::ja_JP]);
+    assert!(season.subtitle_locales.is_empty());
+
+    // a missing locale is unknown too
+    let episode: Episode = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(episode.audio_locale.is_unknown());
+}
+
+#[test]
+fn locale_is_unknown() {
+    assert!(Locale::default().is_unknown());
+    assert!(!Locale::ja_JP.is_unknown());
+    assert!(!Locale::Custom("xx-XX".to_string()).is_unknown())
+}
@@ -1,6 +1,7 @@
 use crate::utils::Store;
 use crate::utils::SESSION;
 use crunchyroll_rs::Movie;
+use futures_util::StreamExt;
 
 mod utils;
 
@@ -22,3 +23,8 @@ async fn movie_stream() {
     let stream = MOVIE.get().await.unwrap().stream().await.unwrap();
     stream.invalidate().await.unwrap()
 }
+
+#[tokio::test]
+async fn movie_similar() {
+    assert_result!(MOVIE.get().await.unwrap().similar().next().await.unwrap())
+}
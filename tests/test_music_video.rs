@@ -1,5 +1,6 @@
 use crate::utils::{Store, SESSION};
 use crunchyroll_rs::MusicVideo;
+use futures_util::StreamExt;
 
 mod utils;
 
@@ -22,6 +23,22 @@ async fn music_video_stream() {
     stream.invalidate().await.unwrap()
 }
 
+#[tokio::test]
+async fn music_video_stream_data() {
+    let stream = MUSIC_VIDEO.get().await.unwrap().stream().await.unwrap();
+    let stream_data = stream.stream_data(None).await;
+    stream.invalidate().await.unwrap();
+    assert_result!(stream_data)
+}
+
+#[tokio::test]
+async fn music_video_playhead() {
+    let music_video = MUSIC_VIDEO.get().await.unwrap();
+
+    assert_result!(music_video.set_playhead(10).await);
+    assert_result!(music_video.playhead().await)
+}
+
 #[tokio::test]
 async fn music_video_related_anime() {
     assert_result!(MUSIC_VIDEO.get().await.unwrap().related_anime().await)
@@ -35,3 +52,13 @@ async fn music_video_artist() {
             .await
     )
 }
+
+#[tokio::test]
+async fn music_video_artist_music_videos() {
+    let artist = MUSIC_VIDEO.get().await.unwrap().artists.main_artist[0]
+        .artist()
+        .await
+        .unwrap();
+
+    assert_result!(artist.music_videos().next().await.unwrap())
+}
@@ -1,6 +1,7 @@
 #![cfg(feature = "parse")]
 
-use crunchyroll_rs::UrlType;
+use crate::utils::SESSION;
+use crunchyroll_rs::{Episode, MovieListing, Series, UrlType};
 
 mod utils;
 
@@ -82,3 +83,107 @@ fn parse_concert_url() {
         unreachable!()
     }
 }
+
+#[test]
+fn parse_artist_url() {
+    let url = "https://www.crunchyroll.com/de/artist/MA179CB50D/lisa";
+    let parsed = crunchyroll_rs::parse_url(url);
+
+    assert!(parsed.is_some());
+    assert!(matches!(parsed.clone().unwrap(), UrlType::Artist { .. }));
+    if let UrlType::Artist(id) = parsed.unwrap() {
+        assert_eq!(id, "MA179CB50D")
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn parse_share_url() {
+    let url = "crunchyroll.com/watch/GRDQPM1ZY?utm_source=share";
+    let parsed = crunchyroll_rs::parse_url(url);
+
+    assert!(parsed.is_some());
+    if let UrlType::EpisodeOrMovie(id) = parsed.unwrap() {
+        assert_eq!(id, "GRDQPM1ZY")
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn parse_beta_url() {
+    let url = "https://beta.crunchyroll.com/series/GY8VEQ95Y/darling-in-the-franxx";
+    let parsed = crunchyroll_rs::parse_url(url);
+
+    assert!(parsed.is_some());
+    if let UrlType::Series(id) = parsed.unwrap() {
+        assert_eq!(id, "GY8VEQ95Y")
+    } else {
+        unreachable!()
+    }
+}
+
+#[tokio::test]
+async fn web_url_round_trip() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let series: Series = crunchy.media_from_id("GY8VEQ95Y").await.unwrap();
+    let Some(UrlType::Series(id)) = crunchyroll_rs::parse_url(series.web_url()) else {
+        panic!("series url could not be parsed: {}", series.web_url())
+    };
+    assert_eq!(id, series.id);
+
+    let episode: Episode = crunchy.media_from_id("GRDQPM1ZY").await.unwrap();
+    let Some(UrlType::EpisodeOrMovie(id)) = crunchyroll_rs::parse_url(episode.web_url()) else {
+        panic!("episode url could not be parsed: {}", episode.web_url())
+    };
+    assert_eq!(id, episode.id);
+
+    let movie_listing: MovieListing = crunchy.media_from_id("G6MG10746").await.unwrap();
+    let Some(UrlType::MovieListing(id)) = crunchyroll_rs::parse_url(movie_listing.web_url()) else {
+        panic!(
+            "movie listing url could not be parsed: {}",
+            movie_listing.web_url()
+        )
+    };
+    assert_eq!(id, movie_listing.id)
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crunchyroll_rs::error::Error;
+    use crunchyroll_rs::testing::MockTransport;
+    use crunchyroll_rs::Crunchyroll;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn short_link_uses_hooks() {
+        let transport = MockTransport::new();
+        let requested = Arc::new(Mutex::new(vec![]));
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .on_request({
+                let requested = requested.clone();
+                move |_, url| requested.lock().unwrap().push(url.to_string())
+            })
+            .login_anonymously()
+            .await
+            .unwrap();
+
+        // the mock doesn't redirect, so the short link can't be resolved
+        let result = crunchy
+            .resolve_url("https://crunchyroll.onelink.me/abc")
+            .await;
+        assert!(matches!(result, Err(Error::Input { .. })));
+        assert!(requested
+            .lock()
+            .unwrap()
+            .contains(&"https://crunchyroll.onelink.me/abc".to_string()));
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(_, url)| url.host_str() == Some("crunchyroll.onelink.me")))
+    }
+}
@@ -1,6 +1,6 @@
 use crate::utils::SESSION;
 use crunchyroll_rs::Locale;
-use futures_util::StreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 
 mod utils;
 
@@ -11,6 +11,47 @@ async fn by_browse() {
     assert_result!(crunchy.browse(Default::default()).next().await.unwrap());
 }
 
+// sparse responses miss fields which are required in strict mode
+#[cfg(not(feature = "__test_strict"))]
+#[tokio::test]
+async fn by_browse_fields() {
+    use crunchyroll_rs::search::BrowseOptions;
+
+    let crunchy = SESSION.get().await.unwrap();
+
+    let options = BrowseOptions::default().fields(vec!["id".to_string(), "title".to_string()]);
+    let result = crunchy.browse(options).next().await.unwrap();
+    assert_result!(result);
+}
+
+#[tokio::test]
+async fn by_browse_music() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    assert_result!(crunchy
+        .browse_music(Default::default())
+        .next()
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn browse_totals() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    assert_result!(crunchy.browse_totals().await)
+}
+
+#[tokio::test]
+async fn browse_index() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let index = crunchy.browse_index().await.unwrap();
+    let prefix = index.iter().find(|p| p.total > 0).unwrap();
+    let series: Vec<_> = prefix.series().try_collect().await.unwrap();
+    assert_eq!(series.len() as u32, prefix.total)
+}
+
 #[tokio::test]
 async fn by_query() {
     let crunchy = SESSION.get().await.unwrap();
@@ -26,9 +67,131 @@ async fn by_query() {
     assert_result!(default_result.episode.next().await.unwrap())
 }
 
+#[tokio::test]
+async fn by_query_with() {
+    use crunchyroll_rs::search::QueryOptions;
+
+    let crunchy = SESSION.get().await.unwrap();
+
+    let mut result = crunchy.query_with("darling", QueryOptions::default().is_dubbed(true));
+    assert_result!(result.series.next().await.unwrap())
+}
+
 #[tokio::test]
 async fn simulcast_seasons() {
     let crunchy = SESSION.get().await.unwrap();
 
     assert_result!(crunchy.simulcast_seasons(Locale::en_US).await)
 }
+
+#[tokio::test]
+async fn current_simulcast_season() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let current = crunchy
+        .current_simulcast_season(Locale::en_US)
+        .await
+        .unwrap()
+        .unwrap();
+    let year = current.year.unwrap();
+    assert!(current.id.contains(&year.to_string()))
+}
+
+#[tokio::test]
+async fn by_browse_cancelled() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let mut browse = crunchy.browse(Default::default());
+    browse.cancellation().cancel();
+    assert!(browse.next().await.is_none())
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::crunchyroll::Clock;
+    use crunchyroll_rs::search::SeasonOfYear;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::{Crunchyroll, Locale, Series};
+    use futures_util::TryStreamExt;
+
+    #[tokio::test]
+    async fn simulcast_seasons() {
+        #[derive(Clone, Debug)]
+        struct FixedClock;
+
+        impl Clock for FixedClock {
+            fn now(&self) -> chrono::DateTime<chrono::Utc> {
+                "2024-11-20T00:00:00Z".parse().unwrap()
+            }
+        }
+
+        let transport = MockTransport::new().on(
+            "/content/v1/season_list",
+            serde_json::json!({
+                "items": [
+                    { "id": "winter-2025" },
+                    { "id": "summer-2024" },
+                    { "id": "fall-2024" },
+                    { "id": "winter-2024" }
+                ],
+                "total": 4
+            }),
+        );
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport)
+            .clock(FixedClock)
+            .login_anonymously()
+            .await
+            .unwrap();
+
+        let mut seasons = crunchy.simulcast_seasons(Locale::en_US).await.unwrap();
+        seasons.sort();
+        assert_eq!(
+            seasons.iter().map(|s| s.id.as_str()).collect::<Vec<&str>>(),
+            vec!["winter-2024", "summer-2024", "fall-2024", "winter-2025"]
+        );
+        assert_eq!(seasons[2].year, Some(2024));
+        assert_eq!(seasons[2].season, Some(SeasonOfYear::Fall));
+
+        let current = crunchy
+            .current_simulcast_season(Locale::en_US)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(current.id, "fall-2024")
+    }
+
+    #[tokio::test]
+    async fn browse_index() {
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/discover/browse/index",
+                fixtures::bulk(vec![
+                    serde_json::json!({ "prefix": "#", "offset": 0, "total": 3 }),
+                    serde_json::json!({ "prefix": "D", "offset": 3, "total": 1 }),
+                ]),
+            )
+            .on(
+                "/content/v2/discover/browse",
+                fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        let index = crunchy.browse_index().await.unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[1].prefix, "D");
+
+        let series: Vec<Series> = index[1].series().try_collect().await.unwrap();
+        assert_eq!(series.len(), 1);
+        let (_, url) = transport
+            .requests()
+            .into_iter()
+            .rfind(|(_, url)| url.path() == "/content/v2/discover/browse")
+            .unwrap();
+        let query: Vec<_> = url.query_pairs().into_owned().collect();
+        assert!(query.contains(&("start".to_string(), "3".to_string())));
+        assert!(query.contains(&("n".to_string(), "1".to_string())))
+    }
+}
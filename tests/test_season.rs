@@ -1,6 +1,7 @@
 use crate::utils::Store;
 use crate::utils::SESSION;
-use crunchyroll_rs::Season;
+use crunchyroll_rs::media::EpisodesOptions;
+use crunchyroll_rs::{Locale, Season};
 
 mod utils;
 
@@ -20,3 +21,31 @@ async fn season_from_id() {
 async fn season_episodes() {
     assert_result!(SEASON.get().await.unwrap().episodes().await)
 }
+
+#[tokio::test]
+async fn season_episodes_with() {
+    assert_result!(
+        SEASON
+            .get()
+            .await
+            .unwrap()
+            .episodes_with(
+                EpisodesOptions::default()
+                    .locale(Locale::de_DE)
+                    .include_versions(false)
+            )
+            .await
+    )
+}
+
+#[tokio::test]
+async fn season_episodes_with_audio() {
+    assert_result!(
+        SEASON
+            .get()
+            .await
+            .unwrap()
+            .episodes_with_audio(Locale::ja_JP)
+            .await
+    )
+}
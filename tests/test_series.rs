@@ -1,6 +1,6 @@
 use crate::utils::Store;
 use crate::utils::SESSION;
-use crunchyroll_rs::Series;
+use crunchyroll_rs::{Episode, MediaCollection, Series};
 use futures_util::StreamExt;
 
 mod utils;
@@ -23,12 +23,126 @@ async fn series_seasons() {
     assert_result!(SERIES.get().await.unwrap().seasons().await)
 }
 
+#[tokio::test]
+async fn series_episodes() {
+    assert_result!(SERIES.get().await.unwrap().episodes().await)
+}
+
+#[tokio::test]
+async fn series_next_unwatched() {
+    assert_result!(SERIES.get().await.unwrap().next_unwatched().await)
+}
+
+#[tokio::test]
+async fn series_audio_locales_deep() {
+    assert_result!(SERIES.get().await.unwrap().audio_locales_deep().await)
+}
+
+#[tokio::test]
+async fn series_seasons_grouped() {
+    assert_result!(SERIES.get().await.unwrap().seasons_grouped().await)
+}
+
+#[tokio::test]
+async fn series_export_attach() {
+    let crunchy = SESSION.get().await.unwrap();
+    let series = SERIES.get().await.unwrap();
+
+    let exported = serde_json::to_value(series).unwrap();
+    let imported: Series = serde_json::from_value(exported.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&imported).unwrap(), exported);
+
+    let attached = crunchy.attach(imported).await;
+    assert_result!(attached.seasons().await)
+}
+
+#[tokio::test]
+async fn series_media_collection_conversion() {
+    let series = SERIES.get().await.unwrap();
+
+    let collection = MediaCollection::from(series.clone());
+    assert!(collection.is_series());
+    assert_eq!(collection.id(), series.id);
+    assert_eq!(collection.title(), series.title);
+    assert!(collection.as_episode().is_none());
+    assert!(Episode::try_from(collection.clone()).is_err());
+    assert_eq!(Series::try_from(collection).unwrap().id, series.id)
+}
+
+#[tokio::test]
+async fn series_objects_from_ids() {
+    let crunchy = SESSION.get().await.unwrap();
+    let series = SERIES.get().await.unwrap();
+
+    let objects = crunchy
+        .objects_from_ids(&[series.id.as_str(), "GRDKJZ81Y"])
+        .await
+        .unwrap();
+    assert!(objects.iter().any(|o| o.is_series() && o.id() == series.id));
+    assert!(objects.iter().any(|o| o.is_episode()))
+}
+
+#[tokio::test]
+async fn series_image_data() {
+    let crunchy = SESSION.get().await.unwrap();
+    let series = SERIES.get().await.unwrap();
+
+    let largest = series.images.largest_tall().unwrap();
+    let closest = series.images.closest_tall_to(1, 1).unwrap();
+    assert!(largest.width >= closest.width);
+    assert_result!(closest.data(crunchy).await)
+}
+
 #[tokio::test]
 async fn series_featured_music() {
     assert_result!(SERIES.get().await.unwrap().featured_music().await)
 }
 
+#[tokio::test]
+async fn series_extras() {
+    assert_result!(SERIES.get().await.unwrap().extras().await)
+}
+
 #[tokio::test]
 async fn series_similar() {
     assert_result!(SERIES.get().await.unwrap().similar().next().await.unwrap())
 }
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::media::ExtraVideoType;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::{Locale, Series};
+
+    #[tokio::test]
+    async fn extras() {
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/series/GY8VEQ95Y",
+                fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+            )
+            .on(
+                "/content/v2/cms/series/GY8VEQ95Y/extra_videos",
+                fixtures::bulk(vec![serde_json::json!({
+                    "id": "G4VUQ588P",
+                    "title": "Official Trailer",
+                    "extra_video_type": "trailer",
+                    "series_id": "GY8VEQ95Y",
+                    "audio_locale": "ja-JP",
+                    "duration_ms": 90000,
+                    "streams_link": "/content/v2/cms/videos/G4VUQ588P/streams"
+                })]),
+            );
+        let crunchy = mock::anonymous(&transport).await;
+
+        let series: Series = crunchy.media_from_id("GY8VEQ95Y").await.unwrap();
+        let extras = series.extras().await.unwrap();
+        assert_eq!(extras.len(), 1);
+        assert_eq!(extras[0].video_type, ExtraVideoType::Trailer);
+        assert_eq!(extras[0].stream_id, "G4VUQ588P");
+        assert_eq!(extras[0].audio_locale, Locale::ja_JP);
+        assert_eq!(extras[0].duration.num_seconds(), 90)
+    }
+}
@@ -1,9 +1,16 @@
 use crate::utils::Store;
 use crate::utils::SESSION;
-use crunchyroll_rs::media::{Media, MediaStream, Stream, StreamSegment};
-use crunchyroll_rs::Episode;
+use crunchyroll_rs::common::Cancellation;
+use crunchyroll_rs::error::Error;
+use crunchyroll_rs::media::{
+    Codec, Media, MediaStream, RollingThroughput, Stream, StreamPreference, StreamRequirements,
+    StreamSegment, StreamSessionWarning, ThroughputObserver,
+};
+use crunchyroll_rs::{Episode, Locale};
 use rand::seq::SliceRandom;
+use std::collections::HashSet;
 use std::io::Write;
+use std::time::Duration;
 
 mod utils;
 
@@ -42,11 +49,255 @@ async fn stream_data() {
     assert_result!(VIDEO_STREAM.get().await)
 }
 
+#[tokio::test]
+async fn stream_manifest() {
+    assert_result!(STREAM.get().await.unwrap().manifest(None).await)
+}
+
+#[tokio::test]
+async fn stream_bifs() {
+    let stream = STREAM.get().await.unwrap();
+
+    if let Some(bif) = stream.bifs.first() {
+        assert_result!(bif.images().await)
+    }
+}
+
+#[tokio::test]
+async fn stream_data_selection() {
+    let stream_data = STREAM
+        .get()
+        .await
+        .unwrap()
+        .stream_data(None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let best = stream_data.best_video().unwrap();
+    let worst = stream_data.worst_video().unwrap();
+    assert!(best.resolution().unwrap().height >= worst.resolution().unwrap().height);
+    assert!(stream_data
+        .video_by_max_height(worst.resolution().unwrap().height)
+        .is_some());
+    assert!(stream_data
+        .preferred(&StreamPreference::default().max_resolution(best.resolution().unwrap()))
+        .0
+        .is_some())
+}
+
+#[tokio::test]
+async fn stream_data_trickplay() {
+    let stream_data = STREAM
+        .get()
+        .await
+        .unwrap()
+        .stream_data(None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(!stream_data.video.is_empty());
+    assert!(stream_data
+        .trickplay
+        .iter()
+        .all(|trickplay| trickplay.resolution().is_some()));
+    let representation_id =
+        |stream: &MediaStream| stream.segments()[0].identity().representation_id;
+    assert!(stream_data.video.iter().all(|video| !stream_data
+        .trickplay
+        .iter()
+        .any(|trickplay| representation_id(trickplay) == representation_id(video))))
+}
+
+#[tokio::test]
+async fn stream_data_audio_metadata() {
+    let stream_data = STREAM
+        .get()
+        .await
+        .unwrap()
+        .stream_data(None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for audio in &stream_data.audio {
+        assert!(audio.channels().is_none_or(|channels| channels > 0));
+        if audio.codecs == "mp4a.40.2" {
+            assert_eq!(audio.codec_profile(), Some("AAC-LC"))
+        }
+    }
+    assert!(stream_data
+        .video
+        .iter()
+        .all(|video| video.channels().is_none() && video.audio_role().is_none()))
+}
+
+#[tokio::test]
+async fn stream_duration() {
+    let video = VIDEO_STREAM.get().await.unwrap();
+
+    assert!(!video.duration().is_zero());
+    assert_eq!(
+        video.duration(),
+        video.segments().iter().map(|s| s.length).sum::<Duration>()
+    )
+}
+
+#[tokio::test]
+async fn stream_periods() {
+    let video = VIDEO_STREAM.get().await.unwrap();
+
+    let periods = video.periods();
+    assert!(!periods.is_empty());
+    assert_eq!(
+        video.duration(),
+        periods.iter().map(|p| p.duration).sum::<Duration>()
+    );
+    // every period starts with an initialization segment
+    let segments = video.segments();
+    for period in &periods {
+        assert!(segments
+            .iter()
+            .any(|s| s.identity().number.is_none() && s.identity().start == period.start))
+    }
+}
+
+#[tokio::test]
+async fn stream_data_estimated_size() {
+    let stream_data = STREAM
+        .get()
+        .await
+        .unwrap()
+        .stream_data(None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let (video, audio) = stream_data.preferred(&StreamPreference::default());
+    let (video, audio) = (video.unwrap(), audio.unwrap());
+    assert!(video.estimated_size() > 0);
+    assert_eq!(
+        stream_data.estimated_size(&StreamPreference::default()),
+        Some(video.estimated_size() + audio.estimated_size())
+    )
+}
+
+#[tokio::test]
+async fn stream_data_select_for_bandwidth() {
+    let stream_data = STREAM
+        .get()
+        .await
+        .unwrap()
+        .stream_data(None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let (video, audio) = stream_data.select_for_bandwidth(u64::MAX / 2);
+    assert_eq!(
+        video.unwrap().bandwidth,
+        stream_data.video.iter().map(|v| v.bandwidth).max().unwrap()
+    );
+    assert!(audio.is_some());
+
+    let (video, _) = stream_data.select_for_bandwidth(0);
+    assert_eq!(
+        video.unwrap().bandwidth,
+        stream_data.video.iter().map(|v| v.bandwidth).min().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn stream_versions() {
+    let stream = STREAM.get().await.unwrap();
+
+    let audio_locales = stream.available_audio_locales();
+    assert!(audio_locales.contains(&stream.audio_locale));
+    if let Some(original_version) = stream.original_version() {
+        assert!(stream
+            .version(std::slice::from_ref(&original_version.audio_locale))
+            .is_some())
+    }
+}
+
+#[tokio::test]
+async fn stream_subtitle_fallback() {
+    let stream = STREAM.get().await.unwrap();
+
+    assert!(stream
+        .subtitle(&[Locale::Custom("xx-XX".to_string()), Locale::en_IN])
+        .is_some_and(|s| s.locale.to_string().starts_with("en")))
+}
+
+#[tokio::test]
+async fn stream_session_guard() {
+    let stream = STREAM.get().await.unwrap();
+
+    assert_eq!(
+        stream.session.maximum_pause().as_secs(),
+        stream.session.maximum_pause_seconds as u64
+    );
+
+    let mut guard = stream.session_guard().warn_before(Duration::ZERO);
+    guard.pause();
+    guard.resume();
+    assert!(guard.check().is_none());
+
+    // warn about every limit which isn't 0
+    let guard = stream.session_guard().warn_before(Duration::MAX);
+    if stream.session.session_expiration_seconds > 0 {
+        assert!(matches!(
+            guard.check(),
+            Some(StreamSessionWarning::SessionExpiring { .. })
+        ))
+    }
+}
+
+#[tokio::test]
+async fn stream_renew() {
+    assert_result!(STREAM.get().await.unwrap().renew(None).await)
+}
+
 #[tokio::test]
 async fn stream_segments() {
     assert_result!(STREAM_SEGMENTS.get().await)
 }
 
+#[tokio::test]
+async fn stream_segments_between() {
+    let media_stream = VIDEO_STREAM.get().await.unwrap();
+    let segments = media_stream.segments();
+
+    let start = segments[1].length + Duration::from_millis(1);
+    let end = start + segments[2].length;
+    let between = media_stream.segments_between(start, end);
+    assert_eq!(between.len(), 3);
+    assert_eq!(between[0].url, segments[0].url);
+    assert_eq!(between[1].url, segments[2].url);
+    assert_eq!(between[2].url, segments[3].url);
+
+    assert_eq!(
+        media_stream
+            .segments_between(Duration::ZERO, Duration::MAX)
+            .len(),
+        segments.len()
+    )
+}
+
+#[tokio::test]
+async fn stream_segment_identity() {
+    let segments = STREAM_SEGMENTS.get().await.unwrap();
+
+    let init = segments[0].identity();
+    assert!(init.number.is_none());
+    assert!(init.to_string().ends_with("-init"));
+
+    assert_eq!(segments[2].identity().start, segments[1].length);
+    let identities: HashSet<String> = segments.iter().map(|s| s.identity().to_string()).collect();
+    assert_eq!(identities.len(), segments.len())
+}
+
 #[tokio::test]
 async fn process_segments() {
     let segments = STREAM_SEGMENTS.get().await.unwrap();
@@ -57,7 +308,7 @@ async fn process_segments() {
     // if the test passes, it's unlikely that some error will occur when streaming all segments (
     // and if it does, hopefully someone using this in production will report it)
     for _ in 0..10 {
-        sink.write(
+        sink.write_all(
             &segments
                 .choose(&mut rand::thread_rng())
                 .unwrap()
@@ -69,8 +320,784 @@ async fn process_segments() {
     }
 }
 
+#[tokio::test]
+async fn process_segments_validated() {
+    let segments = STREAM_SEGMENTS.get().await.unwrap();
+
+    assert_result!(
+        segments
+            .choose(&mut rand::thread_rng())
+            .unwrap()
+            .data_validated(2)
+            .await
+    )
+}
+
+#[tokio::test]
+async fn process_segments_cancellable() {
+    let segments = STREAM_SEGMENTS.get().await.unwrap();
+    let segment = segments.choose(&mut rand::thread_rng()).unwrap();
+    let cancellation = Cancellation::new();
+
+    assert_result!(segment.data_cancellable(&cancellation).await);
+
+    cancellation.cancel();
+    assert!(matches!(
+        segment.data_cancellable(&cancellation).await,
+        Err(Error::Cancelled { .. })
+    ))
+}
+
+#[tokio::test]
+async fn process_segments_resumable() {
+    let segments = STREAM_SEGMENTS.get().await.unwrap();
+    let segment = segments.choose(&mut rand::thread_rng()).unwrap();
+    let data = segment.data().await.unwrap();
+    let offset = data.len() as u64 / 2;
+
+    assert_eq!(
+        segment.data_from(offset).await.unwrap(),
+        data[offset as usize..]
+    );
+
+    let mut written = data[..offset as usize].to_vec();
+    let length = segment
+        .write_resumable(&mut written, offset, 3)
+        .await
+        .unwrap();
+    assert_eq!(length, data.len() as u64);
+    assert_eq!(written, data)
+}
+
+#[test]
+fn rolling_throughput() {
+    let throughput = RollingThroughput::new(Duration::from_secs(60));
+    assert_eq!(throughput.bytes_per_second(), 0.0);
+
+    throughput.clone().observe(1024, Duration::from_secs(1));
+    throughput.observe(1024, Duration::from_millis(500));
+    assert_eq!(throughput.total_bytes(), 2048);
+
+    // both chunks were received in a bit more than the first chunks' duration
+    let bytes_per_second = throughput.bytes_per_second();
+    assert!(bytes_per_second > 1500.0 && bytes_per_second <= 2048.0)
+}
+
 // will throw a too many active streams error
 /*#[tokio::test]
 async fn stream_versions_drm() {
     assert_result!(STREAM_DRM.get().await.unwrap().versions().await)
 }*/
+
+#[tokio::test]
+async fn active_streams() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    assert_result!(crunchy.active_streams().await)
+}
+
+#[tokio::test]
+async fn stream_from_id_auto() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let auto = Stream::from_id_auto(
+        crunchy,
+        "GRDKJZ81Y",
+        StreamRequirements::new().video_codec("avc1"),
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(auto
+        .stream_data
+        .video
+        .iter()
+        .any(|video| video.codecs.starts_with("avc1")));
+    auto.stream.invalidate().await.unwrap()
+}
+
+#[tokio::test]
+async fn stream_prefer_h265() {
+    let crunchy = SESSION.get().await.unwrap();
+
+    let preference = StreamPreference::default()
+        .prefer_h265(true)
+        .prefer_4k(true);
+    let stream = Stream::from_id_with_preference(crunchy, "GRDKJZ81Y", &preference, None)
+        .await
+        .unwrap();
+    let stream_data = stream.stream_data(None).await.unwrap().unwrap();
+    assert!(stream_data
+        .video
+        .iter()
+        .all(|video| video.codec() != Codec::Unknown));
+    assert!(stream_data.preferred(&preference).0.is_some());
+    stream.invalidate().await.unwrap()
+}
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::crunchyroll::RateLimiter;
+    use crunchyroll_rs::error::Error;
+    use crunchyroll_rs::media::{
+        Codec, EpisodeDownloader, MediaStream, Resolution, Stream, StreamData, StreamPlatform,
+        StreamPreference, StreamRequirements,
+    };
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::{Crunchyroll, Episode, Locale};
+    use reqwest::Method;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// Transport which serves the stream of the episode `GRDKJZ81Y` with `mpd` as manifest.
+    fn mpd_transport(mpd: &str) -> MockTransport {
+        MockTransport::new()
+            .on(
+                "/v1/GRDKJZ81Y/web/chrome/play",
+                fixtures::stream("https://example.com/manifest.mpd"),
+            )
+            .on_text("/manifest.mpd", "application/dash+xml", mpd)
+    }
+
+    async fn web_stream(crunchy: &Crunchyroll) -> Stream {
+        Stream::from_id(crunchy, "GRDKJZ81Y", StreamPlatform::WebChrome, None)
+            .await
+            .unwrap()
+    }
+
+    async fn stream_data_from_mpd(mpd: &str) -> StreamData {
+        let crunchy = mock::login(&mpd_transport(mpd)).await;
+        web_stream(&crunchy)
+            .await
+            .stream_data(None)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Transport which serves an episode whose stream is described by `fixtures/codecs.mpd`. Every
+    /// segment contains its own path, segments in `failing` are answered with status `500`.
+    fn download_transport(failing: &[&str]) -> MockTransport {
+        let mut stream = fixtures::stream("https://example.com/manifest.mpd");
+        stream["subtitles"] = serde_json::json!({
+            "en-US": {
+                "language": "en-US",
+                "url": "https://example.com/subtitles/en-US.ass",
+                "format": "ass"
+            }
+        });
+        let mut transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/episodes/GRDKJZ81Y",
+                fixtures::bulk(vec![fixtures::episode(
+                    "GRDKJZ81Y",
+                    "GR49C7EPD",
+                    "GY8VEQ95Y",
+                    "Alone and Lonesome",
+                )]),
+            )
+            .on("/v1/GRDKJZ81Y/web/chrome/play", stream)
+            .on_text(
+                "/manifest.mpd",
+                "application/dash+xml",
+                include_str!("fixtures/codecs.mpd"),
+            )
+            .on_text("/subtitles/en-US.ass", "text/plain", "en-US subtitle")
+            .on_method(
+                Method::DELETE,
+                "/v1/token/GRDKJZ81Y/mock-stream-token",
+                serde_json::json!({}),
+            );
+        for representation in [
+            "video-2160-h265",
+            "video-1080-h265",
+            "video-1080-h264",
+            "audio-eac3",
+            "audio-aac",
+            "audio-opus",
+        ] {
+            for file in ["init.mp4", "1.m4s", "2.m4s"] {
+                let path = format!("/content/{representation}/{file}");
+                transport = if failing.contains(&path.as_str()) {
+                    transport.on_status(&path, 500, serde_json::json!({}))
+                } else {
+                    transport.on_text(&path, "video/mp4", path.clone())
+                }
+            }
+        }
+        transport
+    }
+
+    fn segments(representation: &str) -> Vec<u8> {
+        ["init.mp4", "1.m4s", "2.m4s"]
+            .iter()
+            .map(|file| format!("/content/{representation}/{file}"))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn episode_downloader() {
+        let transport = download_transport(&[]);
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+
+        let (mut video, mut audio) = (vec![], vec![]);
+        let mut subtitles: HashMap<Locale, Vec<u8>> =
+            HashMap::from([(Locale::en_US, vec![]), (Locale::de_DE, vec![])]);
+        let summary = EpisodeDownloader::new()
+            .max_resolution(Resolution {
+                width: 1920,
+                height: 1080,
+            })
+            .download(&episode, &mut video, &mut audio, &mut subtitles)
+            .await
+            .unwrap();
+
+        let video_id = &summary.video.segments()[0].identity().representation_id;
+        assert!(video_id.starts_with("video-1080-"), "{video_id}");
+        assert_eq!(video, segments(video_id));
+        let audio_id = &summary.audio.segments()[0].identity().representation_id;
+        assert_eq!(audio, segments(audio_id));
+        // there is no german subtitle, so it's skipped
+        assert_eq!(summary.subtitles, vec![Locale::en_US]);
+        assert_eq!(subtitles[&Locale::en_US], b"en-US subtitle");
+        assert!(subtitles[&Locale::de_DE].is_empty());
+        assert_eq!(
+            mock::requests_to(&transport, "/v1/token/GRDKJZ81Y/mock-stream-token"),
+            1
+        )
+    }
+
+    #[tokio::test]
+    async fn do_not_track_api_only() {
+        let transport = download_transport(&[]);
+        let crunchy = Crunchyroll::builder()
+            .with_transport(transport.clone())
+            .do_not_track(true)
+            .login_with_credentials("user@example.com", "password")
+            .await
+            .unwrap();
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+        let (mut video, mut audio) = (vec![], vec![]);
+        EpisodeDownloader::new()
+            .download(
+                &episode,
+                &mut video,
+                &mut audio,
+                &mut HashMap::<_, Vec<u8>>::new(),
+            )
+            .await
+            .unwrap();
+
+        let requests = transport.requests_with_headers();
+        let (api, cdn): (Vec<_>, Vec<_>) = requests
+            .iter()
+            .partition(|(_, url, _)| url.host_str() != Some("example.com"));
+        assert!(!api.is_empty() && !cdn.is_empty());
+        for (_, url, headers) in api {
+            assert_eq!(headers.get("DNT").unwrap(), "1", "{url}");
+            assert_eq!(headers.get("Sec-GPC").unwrap(), "1", "{url}")
+        }
+        for (_, url, headers) in cdn {
+            assert!(headers.get("DNT").is_none(), "{url}");
+            assert!(headers.get("Sec-GPC").is_none(), "{url}")
+        }
+    }
+
+    #[tokio::test]
+    async fn episode_downloader_errors() {
+        let transport = download_transport(&[]);
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+        let (mut video, mut audio) = (vec![], vec![]);
+        let mut subtitles: HashMap<Locale, Vec<u8>> = HashMap::new();
+
+        // the episode has no german dub, no stream is requested
+        let result = EpisodeDownloader::new()
+            .audio_locale(Locale::de_DE)
+            .download(&episode, &mut video, &mut audio, &mut subtitles)
+            .await;
+        assert!(matches!(result, Err(Error::Input { .. })));
+        assert_eq!(
+            mock::requests_to(&transport, "/v1/GRDKJZ81Y/web/chrome/play"),
+            0
+        );
+
+        // no video stream is that small
+        let result = EpisodeDownloader::new()
+            .max_resolution(Resolution {
+                width: 640,
+                height: 360,
+            })
+            .download(&episode, &mut video, &mut audio, &mut subtitles)
+            .await;
+        assert!(matches!(result, Err(Error::Input { .. })));
+        // the stream was requested, so it must be invalidated again
+        assert_eq!(
+            mock::requests_to(&transport, "/v1/token/GRDKJZ81Y/mock-stream-token"),
+            1
+        )
+    }
+
+    #[tokio::test]
+    async fn episode_downloader_segment_error() {
+        let transport = download_transport(&[
+            "/content/audio-eac3/2.m4s",
+            "/content/audio-aac/2.m4s",
+            "/content/audio-opus/2.m4s",
+        ]);
+        let crunchy = mock::login(&transport).await;
+        let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+        let (mut video, mut audio) = (vec![], vec![]);
+        let mut subtitles: HashMap<Locale, Vec<u8>> = HashMap::new();
+
+        // the stream is invalidated even though the download failed
+        let result = EpisodeDownloader::new()
+            .download(&episode, &mut video, &mut audio, &mut subtitles)
+            .await;
+        assert!(
+            matches!(result, Err(Error::Request { status: Some(status), .. }) if status == 500)
+        );
+        assert_eq!(
+            mock::requests_to(&transport, "/v1/token/GRDKJZ81Y/mock-stream-token"),
+            1
+        )
+    }
+
+    #[tokio::test]
+    async fn from_id_auto_stream_data_error() {
+        let transport = MockTransport::new()
+            .on(
+                "/v1/GRDKJZ81Y/web/chrome/play",
+                // no response is registered for the manifest, so requesting it fails
+                fixtures::stream("https://example.com/manifest.mpd"),
+            )
+            .on_method(
+                Method::DELETE,
+                "/v1/token/GRDKJZ81Y/mock-stream-token",
+                serde_json::json!({}),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        let result = Stream::from_id_auto(
+            &crunchy,
+            "GRDKJZ81Y",
+            StreamRequirements::new().platforms(vec![StreamPlatform::WebChrome]),
+            None,
+        )
+        .await;
+        let Err(Error::NoMatchingStream { tried, .. }) = result else {
+            panic!("expected a no matching stream error")
+        };
+        assert_eq!(tried.len(), 1);
+        assert!(tried[0].starts_with("WebChrome ("));
+        // the stream whose stream data failed must be invalidated
+        assert_eq!(
+            mock::requests_to(&transport, "/v1/token/GRDKJZ81Y/mock-stream-token"),
+            1
+        )
+    }
+
+    #[tokio::test]
+    async fn from_id_auto_invalidate_error() {
+        let transport = MockTransport::new()
+            .on(
+                "/v1/GRDKJZ81Y/web/chrome/play",
+                fixtures::stream("https://example.com/manifest.mpd"),
+            )
+            .on(
+                "/v1/GRDKJZ81Y/web/firefox/play",
+                fixtures::stream("https://example.com/manifest.mpd"),
+            )
+            .on_text(
+                "/manifest.mpd",
+                "application/dash+xml",
+                include_str!("fixtures/codecs.mpd"),
+            )
+            .on_status(
+                "/v1/token/GRDKJZ81Y/mock-stream-token",
+                429,
+                serde_json::json!({}),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        // no stream has the hardsub, so every stream must be invalidated, which fails
+        let result = Stream::from_id_auto(
+            &crunchy,
+            "GRDKJZ81Y",
+            StreamRequirements::new()
+                .platforms(vec![StreamPlatform::WebChrome, StreamPlatform::WebFirefox])
+                .hardsub(Locale::de_DE),
+            None,
+        )
+        .await;
+        let Err(Error::NoMatchingStream { tried, .. }) = result else {
+            panic!("expected a no matching stream error")
+        };
+        assert_eq!(tried.len(), 2);
+        assert!(tried[0].starts_with("WebChrome (invalidate failed: "));
+        assert!(tried[1].starts_with("WebFirefox (invalidate failed: "))
+    }
+
+    #[tokio::test]
+    async fn multi_period_manifest() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/multi_period.mpd")).await;
+
+        // both video streams are matched with the single ad stream by bandwidth
+        assert_eq!(stream_data.video.len(), 2);
+        for video in &stream_data.video {
+            let starts: Vec<Duration> = video.periods().iter().map(|p| p.start).collect();
+            assert_eq!(
+                starts,
+                vec![
+                    Duration::ZERO,
+                    Duration::from_secs(4),
+                    Duration::from_secs(6)
+                ]
+            );
+            // every period starts with an initialization segment
+            assert_eq!(video.segments().len(), 3 + 5)
+        }
+
+        // the audio stream which only exists in the ad period isn't dropped
+        assert_eq!(stream_data.audio.len(), 2);
+        assert_eq!(stream_data.audio[0].periods().len(), 3);
+        assert_eq!(stream_data.audio[1].bandwidth, 64000);
+        assert_eq!(
+            stream_data.audio[1].periods()[0].start,
+            Duration::from_secs(4)
+        )
+    }
+
+    #[tokio::test]
+    async fn multi_period_timescale() {
+        let stream_data =
+            stream_data_from_mpd(include_str!("fixtures/multi_period_timescale.mpd")).await;
+
+        let periods = |stream: &MediaStream| -> Vec<(Duration, Duration)> {
+            stream
+                .periods()
+                .iter()
+                .map(|period| (period.start, period.duration))
+                .collect()
+        };
+        // the periods start after the longest stream of the previous period, which is the video
+        let video = &stream_data.video[0];
+        assert_eq!(
+            periods(video),
+            vec![
+                (Duration::ZERO, Duration::from_millis(4004)),
+                (Duration::from_millis(4004), Duration::from_millis(1001)),
+                (Duration::from_millis(5005), Duration::from_millis(2002))
+            ]
+        );
+        assert_eq!(video.duration(), Duration::from_millis(7007));
+        // the ad period has another timescale than the content periods around it
+        let starts: Vec<Duration> = video
+            .segments()
+            .iter()
+            .map(|segment| segment.identity().start)
+            .collect();
+        assert_eq!(
+            starts,
+            [0, 0, 2002, 4004, 4004, 5005, 5005]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+        let audio = &stream_data.audio[0];
+        assert_eq!(
+            periods(audio),
+            vec![
+                (Duration::ZERO, Duration::from_secs(4)),
+                (Duration::from_millis(4004), Duration::from_secs(1)),
+                (Duration::from_millis(5005), Duration::from_secs(2))
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn renew_playhead() {
+        let transport = mpd_transport(include_str!("fixtures/codecs.mpd")).on_method(
+            Method::PATCH,
+            "/v1/token/GRDKJZ81Y/mock-stream-token/keepAlive",
+            serde_json::json!({}),
+        );
+        let stream = web_stream(&mock::login(&transport).await).await;
+
+        stream.renew(None).await.unwrap();
+        stream.renew(Some(42)).await.unwrap();
+
+        let queries: Vec<Option<String>> = transport
+            .requests()
+            .into_iter()
+            .filter(|(method, _)| method == Method::PATCH)
+            .map(|(_, url)| url.query().map(|query| query.to_string()))
+            .collect();
+        assert_eq!(queries, vec![None, Some("playhead=42".to_string())])
+    }
+
+    #[tokio::test]
+    async fn trickplay_and_trailer_adaptation_sets() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/adaptation_sets.mpd")).await;
+
+        let representation_ids = |streams: &[MediaStream]| -> Vec<String> {
+            streams
+                .iter()
+                .map(|stream| stream.segments()[0].identity().representation_id)
+                .collect()
+        };
+        assert_eq!(representation_ids(&stream_data.video), vec!["video-1080"]);
+        assert_eq!(
+            representation_ids(&stream_data.trickplay),
+            vec!["trickplay-180"]
+        );
+        assert_eq!(
+            representation_ids(&stream_data.trailers),
+            vec!["trailer-720"]
+        );
+        assert_eq!(representation_ids(&stream_data.audio), vec!["audio-aac"])
+    }
+
+    #[tokio::test]
+    async fn adaptation_set_fallbacks() {
+        let stream_data =
+            stream_data_from_mpd(include_str!("fixtures/adaptation_fallbacks.mpd")).await;
+
+        // the values of the adaptation set are used if the representation doesn't have them
+        let fallback = &stream_data.video[0];
+        assert_eq!(fallback.resolution().unwrap().to_string(), "1920x1080");
+        assert_eq!(fallback.fps(), Some(24000.0 / 1001.0));
+        assert_eq!(fallback.codecs, "avc1.640028");
+        // but the values of the representation have precedence
+        let own = &stream_data.video[1];
+        assert_eq!(own.resolution().unwrap().to_string(), "1280x720");
+        assert_eq!(own.fps(), Some(30.0));
+        assert_eq!(own.codecs, "avc1.4d401f");
+
+        let audio = &stream_data.audio[0];
+        assert_eq!(audio.codecs, "mp4a.40.2");
+        assert_eq!(audio.sampling_rate(), Some(48000));
+        assert_eq!(audio.channels(), Some(2));
+        assert_eq!(audio.audio_role(), Some("main"))
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_skips_media() {
+        let crunchy = Crunchyroll::builder()
+            .with_transport(mpd_transport(include_str!("fixtures/codecs.mpd")))
+            .rate_limiter(RateLimiter::new(Duration::from_millis(500)))
+            .login_anonymously()
+            .await
+            .unwrap();
+        let stream = web_stream(&crunchy).await;
+
+        // the manifest isn't requested from the api, so the limiter doesn't delay it
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            stream.stream_data(None).await.unwrap().unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(500))
+    }
+
+    #[tokio::test]
+    async fn write_resumable_range_ignored() {
+        let transport = mpd_transport(include_str!("fixtures/codecs.mpd"))
+            // the whole segment is returned with status 200 although only a range was requested
+            .on_text(
+                "/content/video-2160-h265/init.mp4",
+                "video/mp4",
+                "0123456789",
+            );
+        let stream = web_stream(&mock::login(&transport).await).await;
+        let stream_data = stream.stream_data(None).await.unwrap().unwrap();
+        let video = stream_data
+            .video
+            .iter()
+            .find(|video| video.codecs.starts_with("hvc1"))
+            .unwrap();
+        let segment = &video.segments()[0];
+        assert_eq!(
+            segment.url,
+            "https://example.com/content/video-2160-h265/init.mp4"
+        );
+
+        let mut data = vec![];
+        let total = segment.write_resumable(&mut data, 4, 0).await.unwrap();
+        assert_eq!(total, 10);
+        assert_eq!(data, b"456789")
+    }
+
+    #[tokio::test]
+    async fn write_resumable_connection_error() {
+        let transport = mpd_transport(include_str!("fixtures/codecs.mpd"))
+            .on_text(
+                "/content/video-2160-h265/init.mp4",
+                "video/mp4",
+                "0123456789",
+            )
+            .fail_once("/content/video-2160-h265/init.mp4");
+        let stream = web_stream(&mock::login(&transport).await).await;
+        let stream_data = stream.stream_data(None).await.unwrap().unwrap();
+        let segment = &stream_data.video[0].segments()[0];
+
+        // the first attempt fails, the second one is made after a delay
+        let start = std::time::Instant::now();
+        let mut data = vec![];
+        let total = segment.write_resumable(&mut data, 0, 1).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(total, 10);
+        assert_eq!(data, b"0123456789");
+        assert_eq!(
+            mock::requests_to(&transport, "/content/video-2160-h265/init.mp4"),
+            2
+        );
+
+        // without retries, the connection error is returned
+        let transport = transport.fail_once("/content/video-2160-h265/init.mp4");
+        let result = segment.write_resumable(&mut vec![], 0, 0).await;
+        assert!(matches!(result, Err(Error::Request { status: None, .. })));
+        assert_eq!(
+            mock::requests_to(&transport, "/content/video-2160-h265/init.mp4"),
+            3
+        )
+    }
+
+    #[tokio::test]
+    async fn data_validated_server_error() {
+        let transport = mpd_transport(include_str!("fixtures/codecs.mpd")).on_text_status(
+            "/content/video-2160-h265/init.mp4",
+            503,
+            "text/plain",
+            "unavailable",
+        );
+        let stream = web_stream(&mock::login(&transport).await).await;
+        let stream_data = stream.stream_data(None).await.unwrap().unwrap();
+        let segment = &stream_data.video[0].segments()[0];
+
+        // the server error is retried after a delay instead of right away
+        let start = std::time::Instant::now();
+        let result = segment.data_validated(1).await;
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert!(
+            matches!(result, Err(Error::Request { status: Some(status), .. }) if status == 503)
+        );
+        assert_eq!(
+            mock::requests_to(&transport, "/content/video-2160-h265/init.mp4"),
+            2
+        )
+    }
+
+    #[tokio::test]
+    async fn estimated_size() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/codecs.mpd")).await;
+        let stream = |id: &str| {
+            stream_data
+                .video
+                .iter()
+                .chain(&stream_data.audio)
+                .find(|stream| stream.segments()[0].identity().representation_id == id)
+                .unwrap()
+        };
+
+        // 4 seconds at 15 Mbit/s and 192 kbit/s
+        assert_eq!(stream("video-2160-h265").estimated_size(), 7_500_000);
+        assert_eq!(stream("audio-aac").estimated_size(), 96_000)
+    }
+
+    #[tokio::test]
+    async fn segment_timescale() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/timescale.mpd")).await;
+        let starts = |stream: &MediaStream| -> Vec<Duration> {
+            stream
+                .segments()
+                .iter()
+                .map(|segment| segment.identity().start)
+                .collect()
+        };
+
+        // timescale 90000
+        let video = &stream_data.video[0];
+        assert_eq!(video.duration(), Duration::from_millis(5005));
+        assert_eq!(
+            starts(video),
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(2002),
+                Duration::from_millis(4004)
+            ]
+        );
+        assert_eq!(video.segments()[3].length, Duration::from_millis(1001));
+        assert_eq!(video.estimated_size(), 5_005_000);
+        let between: Vec<Option<u32>> = video
+            .segments_between(Duration::from_secs(3), Duration::from_millis(4500))
+            .iter()
+            .map(|segment| segment.identity().number)
+            .collect();
+        assert_eq!(between, vec![None, Some(2), Some(3)]);
+
+        // timescale 48000 and the default timescale of 1
+        for audio in &stream_data.audio {
+            assert_eq!(audio.duration(), Duration::from_secs(5));
+            assert_eq!(
+                starts(audio),
+                vec![
+                    Duration::ZERO,
+                    Duration::ZERO,
+                    Duration::from_secs(2),
+                    Duration::from_secs(4)
+                ]
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn codec_matches_codec_profile() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/codecs.mpd")).await;
+
+        let codecs: Vec<(Codec, Option<&str>)> = stream_data
+            .video
+            .iter()
+            .chain(&stream_data.audio)
+            .map(|stream| (stream.codec(), stream.codec_profile()))
+            .collect();
+        assert_eq!(
+            codecs,
+            vec![
+                (Codec::H265, Some("H.265 Main 10")),
+                (Codec::H265, Some("H.265 Main")),
+                (Codec::H264, Some("H.264 High")),
+                (Codec::Eac3, Some("E-AC-3")),
+                (Codec::Aac, Some("AAC-LC")),
+                (Codec::Opus, Some("Opus")),
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn preferred_h265_4k() {
+        let stream_data = stream_data_from_mpd(include_str!("fixtures/codecs.mpd")).await;
+        let preferred_video = |preference: StreamPreference| {
+            stream_data.preferred(&preference).0.unwrap().codecs.clone()
+        };
+
+        // 4k streams are only selected if preferred
+        assert_eq!(preferred_video(StreamPreference::default()), "avc1.640028");
+        assert_eq!(
+            preferred_video(StreamPreference::default().prefer_h265(true)),
+            "hvc1.1.6.L120.90"
+        );
+        assert_eq!(
+            preferred_video(StreamPreference::default().prefer_4k(true)),
+            "hvc1.2.4.L153.90"
+        );
+        // ...unless no other stream matches
+        assert_eq!(
+            preferred_video(StreamPreference::default().video_codec("hvc1.2")),
+            "hvc1.2.4.L153.90"
+        )
+    }
+}
@@ -0,0 +1,157 @@
+// the strict feature requires every field to be present, which the fixtures don't contain
+#![cfg(all(feature = "testing", not(feature = "__test_strict")))]
+
+use crate::utils::mock;
+use crunchyroll_rs::account::EmailVerification;
+use crunchyroll_rs::list::WatchlistOptions;
+use crunchyroll_rs::testing::{fixtures, FixtureRecorder, MockTransport};
+use crunchyroll_rs::{Crunchyroll, Episode, Series};
+
+mod utils;
+
+#[tokio::test]
+async fn mock_transport_media() {
+    let transport = MockTransport::new()
+        .on(
+            "/content/v2/cms/series/GY8VEQ95Y",
+            fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+        )
+        .on(
+            "/content/v2/cms/episodes/GRDKJZ81Y",
+            fixtures::bulk(vec![fixtures::episode(
+                "GRDKJZ81Y",
+                "GR49C7EPD",
+                "GY8VEQ95Y",
+                "Alone and Lonesome",
+            )]),
+        );
+    let crunchy = mock::anonymous(&transport).await;
+
+    let series: Series = crunchy.media_from_id("GY8VEQ95Y").await.unwrap();
+    assert_eq!(series.title, "Darling in the Franxx");
+    assert_eq!(series.slug_title, "darling-in-the-franxx");
+
+    let episode: Episode = crunchy.media_from_id("GRDKJZ81Y").await.unwrap();
+    assert_eq!(episode.season_id, "GR49C7EPD");
+    assert_eq!(episode.sequence_number, 1.0);
+
+    assert!(transport
+        .requests()
+        .iter()
+        .any(|(_, url)| url.path() == "/content/v2/cms/series/GY8VEQ95Y"))
+}
+
+#[tokio::test]
+async fn mock_transport_lenient_parsing() {
+    let mut series = fixtures::series("GY8VEQ95Y", "Darling in the Franxx");
+    series["description"] = serde_json::Value::Null;
+    let transport = MockTransport::new().on(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        fixtures::bulk(vec![series]),
+    );
+
+    let strict = Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .login_anonymously()
+        .await
+        .unwrap();
+    assert!(strict.media_from_id::<Series>("GY8VEQ95Y").await.is_err());
+    assert!(strict.parse_warnings().is_empty());
+
+    let lenient = Crunchyroll::builder()
+        .with_transport(transport)
+        .lenient_parsing(true)
+        .login_anonymously()
+        .await
+        .unwrap();
+    let series: Series = lenient.media_from_id("GY8VEQ95Y").await.unwrap();
+    assert_eq!(series.description, "");
+
+    let warnings = lenient.parse_warnings().take();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].fields, vec!["data.0.description".to_string()]);
+    assert!(lenient.parse_warnings().is_empty())
+}
+
+#[cfg(feature = "raw-json")]
+#[tokio::test]
+async fn mock_transport_raw_json() {
+    let transport = MockTransport::new().on(
+        "/content/v2/cms/series/GY8VEQ95Y",
+        fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+    );
+    let crunchy = mock::anonymous(&transport).await;
+
+    let series: Series = crunchy.media_from_id("GY8VEQ95Y").await.unwrap();
+    let raw_json = series.raw_json().unwrap();
+    assert_eq!(raw_json["title"], "Darling in the Franxx");
+    assert_eq!(raw_json["series_metadata"]["season_count"], 1)
+}
+
+#[tokio::test]
+async fn mock_transport_record_replay() {
+    let mut token = fixtures::token();
+    token["account_id"] = "real-account-id".into();
+    let source = MockTransport::new()
+        .on("/auth/v1/token", token)
+        .on(
+            "/content/v2/discover/real-account-id/watchlist",
+            fixtures::bulk(vec![serde_json::json!({
+                "is_favorite": true,
+                "panel": fixtures::series("GY8VEQ95Y", "Darling in the Franxx")
+            })]),
+        )
+        .on(
+            "/accounts/v1/me",
+            serde_json::json!({
+                "account_id": "real-account-id",
+                "email": "real@example.com",
+                "email_verified": true
+            }),
+        );
+    let recorder = FixtureRecorder::new();
+    let crunchy = Crunchyroll::builder()
+        .with_transport(source)
+        .record_fixtures(recorder.clone())
+        .login_with_credentials("real@example.com", "password")
+        .await
+        .unwrap();
+    crunchy
+        .watchlist(WatchlistOptions::default())
+        .await
+        .unwrap();
+    crunchy.resend_verification_email().await.unwrap();
+
+    // tests of different feature sets may run at the same time, so the file must be unique
+    let fixture_file = std::env::temp_dir().join(format!(
+        "crunchyroll-rs-record-replay-{}.json",
+        std::process::id()
+    ));
+    recorder.save(&fixture_file).unwrap();
+    let recorded = std::fs::read_to_string(&fixture_file).unwrap();
+    assert!(!recorded.contains("real-account-id"));
+    assert!(!recorded.contains("real@example.com"));
+    assert!(recorder
+        .fixtures()
+        .iter()
+        .all(|fixture| fixture.path != "/auth/v1/token"));
+    assert!(recorder.fixtures().iter().any(|fixture| fixture
+        .query
+        .as_ref()
+        .is_some_and(|query| query.contains("is_favorite=false"))));
+
+    let replay = Crunchyroll::builder()
+        .with_transport(MockTransport::from_fixture_file(&fixture_file).unwrap())
+        .login_with_credentials("user@example.com", "password")
+        .await
+        .unwrap();
+    std::fs::remove_file(fixture_file).unwrap();
+    let watchlist = replay.watchlist(WatchlistOptions::default()).await.unwrap();
+    assert!(watchlist[0].is_favorite);
+    // only requests with the recorded query are answered
+    assert!(replay.favorites(WatchlistOptions::default()).await.is_err());
+    assert_eq!(
+        replay.resend_verification_email().await.unwrap(),
+        EmailVerification::AlreadyVerified
+    )
+}
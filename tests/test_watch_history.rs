@@ -1,6 +1,8 @@
 use crate::utils::SESSION;
 mod utils;
 use crunchyroll_rs::list::WatchHistoryEntry;
+use crunchyroll_rs::media::MediaFilter;
+use crunchyroll_rs::Locale;
 use futures_util::StreamExt;
 
 #[tokio::test]
@@ -13,6 +15,19 @@ async fn watch_history() {
         .unwrap_or(Ok(WatchHistoryEntry::default())))
 }
 
+#[tokio::test]
+async fn watch_history_filter_collect() {
+    let crunchy = SESSION.get().await.unwrap();
+    let filter = MediaFilter::new().audio_locale(Locale::ja_JP);
+
+    assert_result!(
+        crunchy
+            .watch_history()
+            .filter_collect(|entry| filter.matches(entry))
+            .await
+    )
+}
+
 #[tokio::test]
 async fn clear_watch_history() {
     let crunchy = SESSION.get().await.unwrap();
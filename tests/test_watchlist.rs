@@ -1,5 +1,6 @@
 use crate::utils::{Store, SESSION};
-use crunchyroll_rs::list::WatchlistOptions;
+use crunchyroll_rs::list::{WatchlistLanguage, WatchlistOptions, WatchlistOrder, WatchlistSort};
+use crunchyroll_rs::media::{MediaFilter, MediaType};
 use crunchyroll_rs::Series;
 
 mod utils;
@@ -18,6 +19,30 @@ async fn watchlist() {
     assert_result!(crunchy.watchlist(WatchlistOptions::default()).await)
 }
 
+#[tokio::test]
+async fn watchlist_filtered() {
+    let crunchy = SESSION.get().await.unwrap();
+    let options = WatchlistOptions::default()
+        .order(WatchlistOrder::Oldest)
+        .sort(WatchlistSort::Watched)
+        .media_type(MediaType::Series)
+        .language(WatchlistLanguage::Dubbed);
+    assert_result!(crunchy.watchlist(options.clone()).await)
+}
+
+#[tokio::test]
+async fn watchlist_media_filter() {
+    let crunchy = SESSION.get().await.unwrap();
+    let filter = MediaFilter::new().years(2000..=2030);
+
+    let watchlist = crunchy
+        .watchlist(WatchlistOptions::default())
+        .await
+        .unwrap();
+    let filtered = filter.apply(watchlist.clone());
+    assert!(filtered.len() <= watchlist.len())
+}
+
 #[tokio::test]
 async fn add_to_watchlist() {
     let series = SERIES.get().await.unwrap();
@@ -40,3 +65,126 @@ async fn into_watchlist_entry() {
     let series = SERIES.get().await.unwrap();
     assert_result!(series.into_watchlist_entry().await);
 }
+
+// the strict feature requires every field to be present, which the fixtures don't contain
+#[cfg(all(feature = "testing", not(feature = "__test_strict")))]
+mod offline {
+    use crate::utils::mock;
+    use crunchyroll_rs::list::{WatchlistExportEntry, WatchlistImportConflict, WatchlistOptions};
+    use crunchyroll_rs::media::MediaType;
+    use crunchyroll_rs::testing::{fixtures, MockTransport};
+    use crunchyroll_rs::Series;
+    use reqwest::Method;
+
+    #[tokio::test]
+    async fn watchlist_import_export() {
+        let watchlist_path = format!("/content/v2/{}/watchlist", fixtures::ACCOUNT_ID);
+        let transport = MockTransport::new()
+            .on(
+                format!("/content/v2/discover/{}/watchlist", fixtures::ACCOUNT_ID),
+                fixtures::bulk(vec![
+                    serde_json::json!({
+                        "is_favorite": true,
+                        "panel": fixtures::series("GY8VEQ95Y", "Darling in the Franxx")
+                    }),
+                    // can't be imported, so it's skipped
+                    serde_json::json!({
+                        "panel": fixtures::episode(
+                            "GRDKJZ81Y",
+                            "GR49C7EPD",
+                            "GY8VEQ95Y",
+                            "Alone and Lonesome"
+                        )
+                    }),
+                ]),
+            )
+            .on_method(
+                Method::GET,
+                &watchlist_path,
+                fixtures::bulk(vec![
+                    serde_json::json!({ "id": "GY8VEQ95Y", "is_favorite": false }),
+                    serde_json::json!({ "id": "GRDV0019R", "is_favorite": false }),
+                ]),
+            )
+            .on_method(Method::POST, &watchlist_path, serde_json::json!({}))
+            .on(format!("{watchlist_path}/GY8VEQ95Y"), serde_json::json!({}))
+            .on(format!("{watchlist_path}/G6NQ5DWZ6"), serde_json::json!({}));
+        let crunchy = mock::login(&transport).await;
+
+        let mut exported = crunchy.export_watchlist().await.unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].title, "Darling in the Franxx");
+        assert_eq!(exported[0].media_type, MediaType::Series);
+        assert!(exported[0].is_favorite);
+        let (_, export_url) = transport
+            .requests()
+            .into_iter()
+            .find(|(_, url)| url.path().starts_with("/content/v2/discover/"))
+            .unwrap();
+        // favorites must not be filtered out
+        assert!(!export_url.query_pairs().any(|(k, _)| k == "is_favorite"));
+        assert!(export_url
+            .query_pairs()
+            .any(|(k, v)| k == "start" && v == "0"));
+
+        exported.push(WatchlistExportEntry {
+            id: "GRDV0019R".to_string(),
+            is_favorite: false,
+            ..Default::default()
+        });
+        exported.push(WatchlistExportEntry {
+            id: "G6NQ5DWZ6".to_string(),
+            is_favorite: true,
+            ..Default::default()
+        });
+        let summary = crunchy
+            .import_watchlist(exported, WatchlistImportConflict::Overwrite)
+            .await
+            .unwrap();
+        assert_eq!(summary.added, vec!["G6NQ5DWZ6".to_string()]);
+        assert_eq!(summary.updated, vec!["GY8VEQ95Y".to_string()]);
+        assert_eq!(summary.skipped, vec!["GRDV0019R".to_string()]);
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(method, url)| *method == Method::POST && url.path() == watchlist_path))
+    }
+
+    #[tokio::test]
+    async fn favorites() {
+        let transport = MockTransport::new()
+            .on(
+                "/content/v2/cms/series/GY8VEQ95Y",
+                fixtures::bulk(vec![fixtures::series("GY8VEQ95Y", "Darling in the Franxx")]),
+            )
+            .on(
+                format!("/content/v2/discover/{}/watchlist", fixtures::ACCOUNT_ID),
+                fixtures::bulk(vec![serde_json::json!({
+                    "is_favorite": true,
+                    "panel": fixtures::series("GY8VEQ95Y", "Darling in the Franxx")
+                })]),
+            )
+            .on_method(
+                Method::PATCH,
+                format!("/content/v2/{}/watchlist/GY8VEQ95Y", fixtures::ACCOUNT_ID),
+                serde_json::json!({}),
+            );
+        let crunchy = mock::login(&transport).await;
+
+        let series: Series = crunchy.media_from_id("GY8VEQ95Y").await.unwrap();
+        series.mark_favorite(true).await.unwrap();
+
+        let favorites = crunchy
+            .favorites(WatchlistOptions::default())
+            .await
+            .unwrap();
+        assert!(favorites[0].is_favorite);
+        assert!(transport
+            .requests()
+            .iter()
+            .any(|(_, url)| url.path().ends_with("/watchlist")
+                && url
+                    .query_pairs()
+                    .any(|(k, v)| k == "is_favorite" && v == "true")))
+    }
+}
@@ -0,0 +1,32 @@
+#![allow(dead_code)]
+
+use crunchyroll_rs::testing::MockTransport;
+use crunchyroll_rs::Crunchyroll;
+
+/// Anonymous session which sends all requests to `transport`.
+pub async fn anonymous(transport: &MockTransport) -> Crunchyroll {
+    Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .login_anonymously()
+        .await
+        .unwrap()
+}
+
+/// Account session which sends all requests to `transport`. The account id of the session is
+/// [`crunchyroll_rs::testing::fixtures::ACCOUNT_ID`].
+pub async fn login(transport: &MockTransport) -> Crunchyroll {
+    Crunchyroll::builder()
+        .with_transport(transport.clone())
+        .login_with_credentials("user@example.com", "password")
+        .await
+        .unwrap()
+}
+
+/// Number of requests `transport` received for `path`.
+pub fn requests_to(transport: &MockTransport, path: &str) -> usize {
+    transport
+        .requests()
+        .iter()
+        .filter(|(_, url)| url.path() == path)
+        .count()
+}
@@ -1,6 +1,8 @@
 #![allow(unused_imports)]
 
 pub mod macros;
+#[cfg(feature = "testing")]
+pub mod mock;
 pub mod session;
 pub mod store;
 